@@ -0,0 +1,357 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Group, Ident, Spacing, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericParam, Generics, Index, ItemImpl,
+    TypeParamBound,
+};
+
+struct Family {
+    variant: Ident,
+    suffix: &'static str,
+    marker: TokenStream2,
+    addr: TokenStream2,
+    socket_addr: TokenStream2,
+}
+
+fn families() -> Vec<Family> {
+    vec![
+        #[cfg(feature = "v4")]
+        Family {
+            variant: Ident::new("V4", proc_macro2::Span::call_site()),
+            suffix: "V4",
+            marker: quote!(::ip_family::IpFamilyV4),
+            addr: quote!(::std::net::Ipv4Addr),
+            socket_addr: quote!(::std::net::SocketAddrV4),
+        },
+        #[cfg(feature = "v6")]
+        Family {
+            variant: Ident::new("V6", proc_macro2::Span::call_site()),
+            suffix: "V6",
+            marker: quote!(::ip_family::IpFamilyV6),
+            addr: quote!(::std::net::Ipv6Addr),
+            socket_addr: quote!(::std::net::SocketAddrV6),
+        },
+    ]
+}
+
+fn is_path_sep(first: Option<&TokenTree>, second: Option<&TokenTree>) -> bool {
+    matches!(
+        (first, second),
+        (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+            if a.as_char() == ':' && a.spacing() == Spacing::Joint && b.as_char() == ':'
+    )
+}
+
+fn substitute(tokens: TokenStream2, placeholder: &Ident, family: &Family) -> TokenStream2 {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut out = TokenStream2::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Ident(ident) if ident == placeholder => {
+                if let (true, Some(TokenTree::Ident(assoc))) = (
+                    is_path_sep(tokens.get(i + 1), tokens.get(i + 2)),
+                    tokens.get(i + 3),
+                ) {
+                    let marker = &family.marker;
+                    out.extend(match assoc.to_string().as_str() {
+                        "Addr" => family.addr.clone(),
+                        "SocketAddr" => family.socket_addr.clone(),
+                        _ => quote!(<#marker as ::ip_family::AnyIpFamily>::#assoc),
+                    });
+                    i += 4;
+                    continue;
+                }
+                out.extend(family.marker.clone());
+            }
+            TokenTree::Group(group) => {
+                let mut new = Group::new(
+                    group.delimiter(),
+                    substitute(group.stream(), placeholder, family),
+                );
+                new.set_span(group.span());
+                out.extend([TokenTree::Group(new)]);
+            }
+            other => out.extend([other.clone()]),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Expands an impl block written against a placeholder family (`F` unless
+/// another identifier is given) once for `IpFamilyV4` and once for
+/// `IpFamilyV6`.
+///
+/// `F::Addr` and `F::SocketAddr` are replaced by the concrete std types,
+/// other `F::Item` paths by `<IpFamilyVx as AnyIpFamily>::Item`.
+#[proc_macro_attribute]
+pub fn ip_family_generic(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let placeholder = if attr.is_empty() {
+        Ident::new("F", proc_macro2::Span::call_site())
+    } else {
+        parse_macro_input!(attr as Ident)
+    };
+    let mut item = parse_macro_input!(item as ItemImpl);
+    item.generics.params = item
+        .generics
+        .params
+        .into_iter()
+        .filter(|param| !matches!(param, GenericParam::Type(ty) if ty.ident == placeholder))
+        .collect();
+    let tokens = item.into_token_stream();
+    families()
+        .iter()
+        .map(|family| substitute(tokens.clone(), &placeholder, family))
+        .collect::<TokenStream2>()
+        .into()
+}
+
+fn family_field(fields: &Fields) -> syn::Result<(TokenStream2, &syn::Type)> {
+    let marked: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| {
+            field
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("family"))
+        })
+        .collect();
+    let (index, field) = match (marked.as_slice(), fields.len()) {
+        ([single], _) => *single,
+        ([], 1) => (0, fields.iter().next().unwrap()),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                fields,
+                "expected a single field or one field marked #[family]",
+            ))
+        }
+    };
+    let member = match &field.ident {
+        Some(ident) => quote!(#ident),
+        None => {
+            let index = Index::from(index);
+            quote!(#index)
+        }
+    };
+    Ok((member, &field.ty))
+}
+
+fn binding(member: &TokenStream2) -> TokenStream2 {
+    quote!({ #member: __family, .. })
+}
+
+fn derive_has_family_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (output, body) = match &input.data {
+        Data::Struct(data) => {
+            let (member, ty) = family_field(&data.fields)?;
+            (ty, quote!(::ip_family::HasFamily::ip_family(&self.#member)))
+        }
+        Data::Enum(data) => {
+            let mut output = None;
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let (member, ty) = family_field(&variant.fields)?;
+                output.get_or_insert(ty);
+                let ident = &variant.ident;
+                let pattern = binding(&member);
+                arms.push(
+                    quote!(Self::#ident #pattern => ::ip_family::HasFamily::ip_family(__family)),
+                );
+            }
+            let output = output.ok_or_else(|| {
+                syn::Error::new_spanned(&input.ident, "cannot derive HasFamily for an empty enum")
+            })?;
+            (output, quote!(match self { #(#arms,)* }))
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "cannot derive HasFamily for a union",
+            ))
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics ::ip_family::HasFamily for #name #ty_generics #where_clause {
+            type Output = <#output as ::ip_family::HasFamily>::Output;
+
+            fn ip_family(&self) -> Self::Output {
+                #body
+            }
+        }
+    })
+}
+
+/// Derives `HasFamily` by delegating to the single field, or to the field
+/// marked `#[family]`. Every enum variant must have such a field.
+#[proc_macro_derive(HasFamily, attributes(family))]
+pub fn derive_has_family(input: TokenStream) -> TokenStream {
+    derive_has_family_impl(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The type parameter bounded by `AnyIpFamily`, or else the one named `F`.
+fn family_param(generics: &Generics) -> syn::Result<Ident> {
+    let params: Vec<_> = generics.type_params().collect();
+    params
+        .iter()
+        .find(|param| {
+            param.bounds.iter().any(|bound| {
+                matches!(bound, TypeParamBound::Trait(bound)
+                    if bound.path.segments.last().is_some_and(|segment| segment.ident == "AnyIpFamily"))
+            })
+        })
+        .or_else(|| params.iter().find(|param| param.ident == "F"))
+        .map(|param| param.ident.clone())
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                generics,
+                "expected a type parameter bounded by AnyIpFamily or named F",
+            )
+        })
+}
+
+fn derive_per_ip_family_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let placeholder = family_param(&input.generics)?;
+    let dyn_name = Ident::new(&format!("Dyn{name}"), name.span());
+
+    let mut generics = input.generics.clone();
+    generics.params = generics
+        .params
+        .into_iter()
+        .filter(|param| !matches!(param, GenericParam::Type(ty) if ty.ident == placeholder))
+        .collect();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let params = &generics.params;
+
+    let instance = |family: &Family| {
+        let args = input.generics.params.iter().map(|param| match param {
+            GenericParam::Type(ty) if ty.ident == placeholder => family.marker.clone(),
+            GenericParam::Type(ty) => ty.ident.to_token_stream(),
+            GenericParam::Lifetime(lifetime) => lifetime.lifetime.to_token_stream(),
+            GenericParam::Const(param) => param.ident.to_token_stream(),
+        });
+        quote!(#name<#(#args),*>)
+    };
+    let families = families();
+
+    let aliases = families.iter().map(|family| {
+        let alias = Ident::new(&format!("{name}{}", family.suffix), name.span());
+        let instance = instance(family);
+        let params = params.iter().map(|param| match param {
+            GenericParam::Type(ty) => ty.ident.to_token_stream(),
+            GenericParam::Lifetime(lifetime) => lifetime.lifetime.to_token_stream(),
+            GenericParam::Const(param) => {
+                let (ident, ty) = (&param.ident, &param.ty);
+                quote!(const #ident: #ty)
+            }
+        });
+        let doc = format!("[`{name}`] over IPv{}.", &family.suffix[1..]);
+        quote! {
+            #[doc = #doc]
+            #vis type #alias<#(#params),*> = #instance;
+        }
+    });
+    let variants = families.iter().map(|family| {
+        let (variant, instance) = (&family.variant, instance(family));
+        quote!(#variant(#instance))
+    });
+    let conversions = families.iter().map(|family| {
+        let (variant, instance) = (&family.variant, instance(family));
+        quote! {
+            impl #impl_generics ::std::convert::From<#instance> for #dyn_name #ty_generics #where_clause {
+                fn from(value: #instance) -> Self {
+                    Self::#variant(value)
+                }
+            }
+
+            impl #impl_generics ::std::convert::TryFrom<#dyn_name #ty_generics> for #instance #where_clause {
+                type Error = #dyn_name #ty_generics;
+
+                fn try_from(value: #dyn_name #ty_generics) -> ::std::result::Result<Self, Self::Error> {
+                    #[allow(unreachable_patterns)]
+                    match value {
+                        #dyn_name::#variant(value) => ::std::result::Result::Ok(value),
+                        other => ::std::result::Result::Err(other),
+                    }
+                }
+            }
+        }
+    });
+    let family_arms = families.iter().map(|family| {
+        let variant = &family.variant;
+        quote!(Self::#variant(_) => ::ip_family::IpFamily::#variant)
+    });
+    let default_arms = families.iter().map(|family| {
+        let variant = &family.variant;
+        quote!(::ip_family::IpFamily::#variant => Self::#variant(::std::default::Default::default()))
+    });
+    let default_bounds = families.iter().map(|family| {
+        let instance = instance(family);
+        quote!(#instance: ::std::default::Default)
+    });
+    let doc = format!("[`{name}`] of either family, chosen at runtime.");
+
+    Ok(quote! {
+        #(#aliases)*
+
+        #[doc = #doc]
+        #vis enum #dyn_name<#params> #where_clause {
+            #(#variants,)*
+        }
+
+        impl #impl_generics #dyn_name #ty_generics #where_clause {
+            #vis fn family(&self) -> ::ip_family::IpFamily {
+                match self {
+                    #(#family_arms,)*
+                }
+            }
+
+            /// The default value of the instantiation for `family`.
+            ///
+            /// # Panics
+            ///
+            /// If the crate was built without support for `family`.
+            #vis fn default_for(family: ::ip_family::IpFamily) -> Self
+            where
+                #(#default_bounds,)*
+            {
+                #[allow(unreachable_patterns)]
+                match family {
+                    #(#default_arms,)*
+                    family => panic!("{family} support is disabled"),
+                }
+            }
+        }
+
+        impl #impl_generics ::ip_family::HasFamily for #dyn_name #ty_generics #where_clause {
+            type Output = ::ip_family::IpFamily;
+
+            fn ip_family(&self) -> ::ip_family::IpFamily {
+                self.family()
+            }
+        }
+
+        #(#conversions)*
+    })
+}
+
+/// For a type generic over `F: AnyIpFamily`, generates the aliases
+/// `NameV4` and `NameV6` and an enum `DynName` with one variant per family,
+/// so the family can be chosen at runtime.
+///
+/// `DynName` implements `HasFamily`, `From` each instantiation and
+/// `TryFrom` into it, and has `family()` and `default_for(IpFamily)`.
+#[proc_macro_derive(PerIpFamily)]
+pub fn derive_per_ip_family(input: TokenStream) -> TokenStream {
+    derive_per_ip_family_impl(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    ops::{Add, AddAssign},
+};
+
+use crate::{
+    prefix::{clamp_prefix, truncate},
+    IpFamily, IpFamilyExt, PerFamily,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Traffic {
+    pub bytes: u64,
+    pub packets: u64,
+    pub events: u64,
+}
+
+impl Add for Traffic {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            bytes: self.bytes.saturating_add(rhs.bytes),
+            packets: self.packets.saturating_add(rhs.packets),
+            events: self.events.saturating_add(rhs.events),
+        }
+    }
+}
+
+impl AddAssign for Traffic {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+/// A row of a [`PrefixAccounting`] table.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PrefixTraffic {
+    /// First address of the prefix.
+    pub prefix: IpAddr,
+    pub prefix_len: u8,
+    pub traffic: Traffic,
+}
+
+/// Accumulates [`Traffic`] per prefix, truncating addresses to a
+/// configurable prefix length per family.
+#[derive(Clone, Debug)]
+pub struct PrefixAccounting {
+    granularity: PerFamily<u8>,
+    table: HashMap<IpAddr, Traffic>,
+}
+
+impl PrefixAccounting {
+    /// Prefix lengths above 32 / 128 are clamped.
+    pub fn new(v4_prefix_len: u8, v6_prefix_len: u8) -> Self {
+        Self {
+            granularity: PerFamily::new(
+                clamp_prefix(IpFamily::V4, v4_prefix_len),
+                clamp_prefix(IpFamily::V6, v6_prefix_len),
+            ),
+            table: HashMap::new(),
+        }
+    }
+
+    pub fn prefix_len(&self, family: IpFamily) -> u8 {
+        self.granularity[family]
+    }
+
+    pub fn record(&mut self, addr: IpAddr, traffic: Traffic) {
+        let prefix = truncate(addr, self.prefix_len(addr.family()));
+        *self.table.entry(prefix).or_default() += traffic;
+    }
+
+    /// Traffic of the prefix containing `addr`.
+    pub fn get(&self, addr: IpAddr) -> Traffic {
+        let prefix = truncate(addr, self.prefix_len(addr.family()));
+        self.table.get(&prefix).copied().unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = PrefixTraffic> + '_ {
+        self.table.iter().map(|(&prefix, &traffic)| PrefixTraffic {
+            prefix,
+            prefix_len: self.prefix_len(prefix.family()),
+            traffic,
+        })
+    }
+
+    /// Aggregates into coarser prefixes. Lengths finer than the current
+    /// granularity are clamped to it.
+    pub fn rollup(&self, v4_prefix_len: u8, v6_prefix_len: u8) -> Self {
+        let mut rolled = Self::new(
+            v4_prefix_len.min(self.granularity.v4),
+            v6_prefix_len.min(self.granularity.v6),
+        );
+        for (&prefix, &traffic) in &self.table {
+            rolled.record(prefix, traffic);
+        }
+        rolled
+    }
+
+    /// The `n` prefixes with the highest `key`, ties broken by prefix.
+    pub fn top_n(&self, n: usize, key: impl Fn(&Traffic) -> u64) -> Vec<PrefixTraffic> {
+        let mut rows: Vec<_> = self.iter().collect();
+        rows.sort_by(|a, b| {
+            key(&b.traffic)
+                .cmp(&key(&a.traffic))
+                .then(a.prefix.cmp(&b.prefix))
+        });
+        rows.truncate(n);
+        rows
+    }
+}
+
+impl Default for PrefixAccounting {
+    /// Accounts IPv4 per /24 and IPv6 per /48.
+    fn default() -> Self {
+        Self::new(24, 48)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn traffic(bytes: u64) -> Traffic {
+        Traffic {
+            bytes,
+            packets: 1,
+            events: 1,
+        }
+    }
+
+    #[test]
+    fn accumulates_per_prefix_and_family() {
+        let mut accounting = PrefixAccounting::default();
+        accounting.record(ip("192.0.2.1"), traffic(100));
+        accounting.record(ip("192.0.2.200"), traffic(50));
+        accounting.record(ip("198.51.100.1"), traffic(10));
+        accounting.record(ip("2001:db8:1:2::1"), traffic(1000));
+        accounting.record(ip("2001:db8:1:ffff::1"), traffic(500));
+        accounting.record(ip("2001:db8:2::1"), traffic(5));
+
+        assert_eq!(accounting.len(), 4);
+        assert_eq!(
+            accounting.get(ip("192.0.2.99")),
+            Traffic {
+                bytes: 150,
+                packets: 2,
+                events: 2
+            }
+        );
+        assert_eq!(accounting.get(ip("2001:db8:1::")).bytes, 1500);
+        assert_eq!(accounting.get(ip("2001:db8:2:3::")).bytes, 5);
+        assert_eq!(accounting.get(ip("203.0.113.1")), Traffic::default());
+
+        let mut rows: Vec<_> = accounting
+            .iter()
+            .map(|row| (row.prefix, row.prefix_len, row.traffic.bytes))
+            .collect();
+        rows.sort();
+        assert_eq!(
+            rows,
+            [
+                (ip("192.0.2.0"), 24, 150),
+                (ip("198.51.100.0"), 24, 10),
+                (ip("2001:db8:1::"), 48, 1500),
+                (ip("2001:db8:2::"), 48, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn clear_resets_every_family() {
+        let mut accounting = PrefixAccounting::new(32, 64);
+        accounting.record(ip("192.0.2.1"), traffic(1));
+        accounting.record(ip("2001:db8::1"), traffic(1));
+        assert!(!accounting.is_empty());
+        accounting.clear();
+        assert!(accounting.is_empty());
+        assert_eq!(accounting.get(ip("192.0.2.1")), Traffic::default());
+        assert_eq!(accounting.prefix_len(IpFamily::V6), 64);
+        accounting.record(ip("2001:db8::1"), traffic(7));
+        assert_eq!(accounting.get(ip("2001:db8::2")).bytes, 7);
+    }
+
+    #[test]
+    fn clamps_granularity() {
+        let accounting = PrefixAccounting::new(40, 200);
+        assert_eq!(accounting.prefix_len(IpFamily::V4), 32);
+        assert_eq!(accounting.prefix_len(IpFamily::V6), 128);
+    }
+
+    #[test]
+    fn rollup_and_top_n() {
+        let mut accounting = PrefixAccounting::new(32, 64);
+        accounting.record(ip("10.0.0.1"), traffic(10));
+        accounting.record(ip("10.0.1.1"), traffic(20));
+        accounting.record(ip("10.1.0.1"), traffic(40));
+        accounting.record(ip("2001:db8:0:1::1"), traffic(30));
+        accounting.record(ip("2001:db8:0:2::1"), traffic(30));
+
+        let rolled = accounting.rollup(16, 48);
+        assert_eq!(rolled.len(), 3);
+        assert_eq!(rolled.get(ip("10.0.255.255")).bytes, 30);
+        assert_eq!(rolled.get(ip("2001:db8::")).bytes, 60);
+        assert_eq!(accounting.rollup(40, 128).prefix_len(IpFamily::V6), 64);
+
+        let top: Vec<_> = accounting
+            .top_n(3, |traffic| traffic.bytes)
+            .into_iter()
+            .map(|row| row.prefix)
+            .collect();
+        assert_eq!(
+            top,
+            [ip("10.1.0.1"), ip("2001:db8:0:1::"), ip("2001:db8:0:2::")]
+        );
+    }
+
+    #[test]
+    fn traffic_saturates() {
+        let mut total = traffic(u64::MAX);
+        total += traffic(1);
+        assert_eq!(total.bytes, u64::MAX);
+        assert_eq!(total.packets, 2);
+    }
+}
@@ -0,0 +1,22 @@
+use libc::sa_family_t;
+
+use crate::IpFamily;
+
+impl IpFamily {
+    /// `AF_INET` or `AF_INET6`.
+    pub const fn to_af(&self) -> sa_family_t {
+        match self {
+            Self::V4 => libc::AF_INET as sa_family_t,
+            Self::V6 => libc::AF_INET6 as sa_family_t,
+        }
+    }
+
+    /// Returns `None` for families other than `AF_INET` and `AF_INET6`.
+    pub const fn from_af(af: sa_family_t) -> Option<Self> {
+        match af as libc::c_int {
+            libc::AF_INET => Some(Self::V4),
+            libc::AF_INET6 => Some(Self::V6),
+            _ => None,
+        }
+    }
+}
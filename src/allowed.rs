@@ -0,0 +1,315 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    net::IpAddr,
+    str::FromStr,
+};
+
+use crate::{IpFamily, IpFamilyExt, IpPrefix, ParsePrefixError, TextError, TextErrorKind};
+
+const V4_HEADER: &str = "# ipv4";
+const V6_HEADER: &str = "# ipv6";
+
+/// A list of prefixes of both families, in the style of WireGuard's
+/// `AllowedIPs = 10.0.0.0/8, fd00::/8`.
+///
+/// The list is kept sorted and prefixes covered by another entry are
+/// dropped, so equal sets compare and display equal.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct AllowedIps {
+    prefixes: Vec<IpPrefix>,
+}
+
+impl AllowedIps {
+    pub const fn new() -> Self {
+        Self {
+            prefixes: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, prefix: IpPrefix) {
+        self.prefixes.push(prefix);
+        self.normalize();
+    }
+
+    /// Removes an entry equal to `prefix`. Addresses it covers that are
+    /// also covered by another entry stay contained.
+    pub fn remove(&mut self, prefix: &IpPrefix) -> bool {
+        match self.prefixes.binary_search(prefix) {
+            Ok(index) => {
+                self.prefixes.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        self.prefixes.iter().any(|prefix| prefix.contains(addr))
+    }
+
+    pub fn prefixes(&self) -> &[IpPrefix] {
+        &self.prefixes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+
+    /// One prefix per line in sorted order, each line ending in `\n`, for
+    /// storing sets in version control with minimal diffs.
+    ///
+    /// With `headers`, the prefixes of each family are preceded by a
+    /// `# ipv4` or `# ipv6` line.
+    pub fn to_canonical_text(&self, headers: bool) -> String {
+        let mut text = String::new();
+        let mut family = None;
+        for prefix in &self.prefixes {
+            if headers && family != Some(prefix.family()) {
+                family = Some(prefix.family());
+                text.push_str(match prefix.family() {
+                    IpFamily::V4 => V4_HEADER,
+                    IpFamily::V6 => V6_HEADER,
+                });
+                text.push('\n');
+            }
+            text.push_str(&prefix.to_string());
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Parses exactly the output of [`to_canonical_text`], with or without
+    /// headers, rejecting anything that would not be written back
+    /// byte-for-byte.
+    ///
+    /// [`to_canonical_text`]: Self::to_canonical_text
+    pub fn from_canonical_text(text: &str) -> Result<Self, TextError> {
+        let Some(body) = text.strip_suffix('\n') else {
+            if text.is_empty() {
+                return Ok(Self::new());
+            }
+            return Err(TextError {
+                line: text.split('\n').count(),
+                kind: TextErrorKind::MissingNewline,
+            });
+        };
+        let mut prefixes: Vec<IpPrefix> = Vec::new();
+        let headers = body.starts_with('#');
+        // The family of the last header and whether a prefix followed it.
+        let mut section: Option<(IpFamily, bool)> = None;
+        for (i, line) in body.split('\n').enumerate() {
+            let error = |kind| TextError { line: i + 1, kind };
+            let header = match line {
+                V4_HEADER => Some(IpFamily::V4),
+                V6_HEADER => Some(IpFamily::V6),
+                _ => None,
+            };
+            if let Some(family) = header {
+                match section {
+                    _ if !headers => return Err(error(TextErrorKind::Header)),
+                    Some((prev, filled)) if prev >= family || !filled => {
+                        return Err(error(TextErrorKind::Header))
+                    }
+                    _ => section = Some((family, false)),
+                }
+                continue;
+            }
+            let prefix: IpPrefix = line
+                .parse()
+                .map_err(|err| error(TextErrorKind::Prefix(err)))?;
+            // A bare address parses as a host prefix but is written with
+            // its length.
+            if prefix.to_string() != line {
+                return Err(error(TextErrorKind::NotCanonical));
+            }
+            if let Some((family, filled)) = &mut section {
+                if *family != prefix.family() {
+                    return Err(error(TextErrorKind::Header));
+                }
+                *filled = true;
+            }
+            if let Some(last) = prefixes.last() {
+                if last.covers(&prefix) {
+                    return Err(error(TextErrorKind::Covered(*last)));
+                }
+                if *last >= prefix {
+                    return Err(error(TextErrorKind::Unsorted));
+                }
+            }
+            prefixes.push(prefix);
+        }
+        if section.is_some_and(|(_, filled)| !filled) {
+            return Err(TextError {
+                line: body.split('\n').count(),
+                kind: TextErrorKind::Header,
+            });
+        }
+        Ok(Self { prefixes })
+    }
+
+    fn normalize(&mut self) {
+        self.prefixes.sort_unstable();
+        let mut kept: Vec<IpPrefix> = Vec::with_capacity(self.prefixes.len());
+        for prefix in self.prefixes.drain(..) {
+            // Sorting places a prefix right before the ones it covers.
+            if !kept.last().is_some_and(|last| last.covers(&prefix)) {
+                kept.push(prefix);
+            }
+        }
+        self.prefixes = kept;
+    }
+}
+
+impl FromIterator<IpPrefix> for AllowedIps {
+    fn from_iter<I: IntoIterator<Item = IpPrefix>>(iter: I) -> Self {
+        let mut allowed = Self {
+            prefixes: iter.into_iter().collect(),
+        };
+        allowed.normalize();
+        allowed
+    }
+}
+
+impl Display for AllowedIps {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, prefix) in self.prefixes.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            Display::fmt(prefix, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Entries are separated by commas and/or whitespace.
+impl FromStr for AllowedIps {
+    type Err = ParsePrefixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|entry| !entry.is_empty())
+            .map(IpPrefix::from_str)
+            .collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AllowedIps {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AllowedIps {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WITH_HEADERS: &str = "# ipv4\n10.0.0.0/8\n192.0.2.0/24\n# ipv6\nfd00::/8\n";
+
+    fn allowed(s: &str) -> AllowedIps {
+        s.parse().unwrap()
+    }
+
+    fn error(text: &str) -> (usize, TextErrorKind) {
+        let err = AllowedIps::from_canonical_text(text).unwrap_err();
+        (err.line, err.kind)
+    }
+
+    #[test]
+    fn normalizes_order_and_covered_entries() {
+        let list = allowed("fd00::/8, 192.0.2.0/24 10.1.0.0/16,10.0.0.0/8");
+        assert_eq!(list.to_string(), "10.0.0.0/8, 192.0.2.0/24, fd00::/8");
+        assert_eq!(list, allowed("10.0.0.0/8 192.0.2.0/24 fd00::/8"));
+        assert!(list.contains("10.1.2.3".parse().unwrap()));
+        assert!(!list.contains("::ffff:10.1.2.3".parse().unwrap()));
+    }
+
+    #[test]
+    fn remove_keeps_addresses_covered_elsewhere() {
+        let mut list = allowed("0.0.0.0/0");
+        list.insert("10.0.0.0/8".parse().unwrap());
+        assert_eq!(list.prefixes().len(), 1);
+        assert!(!list.remove(&"10.0.0.0/8".parse().unwrap()));
+        assert!(list.remove(&"0.0.0.0/0".parse().unwrap()));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn canonical_text_round_trips() {
+        let list = allowed("fd00::/8 192.0.2.0/24 10.0.0.0/8");
+        assert_eq!(list.to_canonical_text(true), WITH_HEADERS);
+        assert_eq!(
+            list.to_canonical_text(false),
+            "10.0.0.0/8\n192.0.2.0/24\nfd00::/8\n"
+        );
+        for headers in [true, false] {
+            let text = list.to_canonical_text(headers);
+            assert_eq!(AllowedIps::from_canonical_text(&text), Ok(list.clone()));
+        }
+        assert_eq!(AllowedIps::new().to_canonical_text(true), "");
+        assert_eq!(AllowedIps::from_canonical_text(""), Ok(AllowedIps::new()));
+    }
+
+    #[test]
+    fn canonical_text_of_zero_and_full_length_prefixes() {
+        let list = allowed("::/0 0.0.0.0/0 2001:db8::1/128");
+        assert_eq!(list.to_canonical_text(false), "0.0.0.0/0\n::/0\n");
+        let list = allowed("2001:db8::1 192.0.2.1");
+        assert_eq!(
+            list.to_canonical_text(true),
+            "# ipv4\n192.0.2.1/32\n# ipv6\n2001:db8::1/128\n"
+        );
+    }
+
+    #[test]
+    fn rejects_non_canonical_text() {
+        assert_eq!(error("10.0.0.0/8"), (1, TextErrorKind::MissingNewline));
+        assert_eq!(
+            error("10.0.0.0/8\n\n"),
+            (2, TextErrorKind::Prefix(ParsePrefixError::Addr))
+        );
+        assert_eq!(error("192.0.2.1\n"), (1, TextErrorKind::NotCanonical));
+        assert_eq!(
+            error("fd00::/8\n10.0.0.0/8\n"),
+            (2, TextErrorKind::Unsorted)
+        );
+        assert_eq!(
+            error("10.0.0.0/8\n10.1.0.0/16\n"),
+            (2, TextErrorKind::Covered("10.0.0.0/8".parse().unwrap()))
+        );
+        assert_eq!(
+            error("10.0.0.0/8\n10.0.0.0/8\n"),
+            (2, TextErrorKind::Covered("10.0.0.0/8".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn rejects_misplaced_headers() {
+        assert_eq!(
+            error("10.0.0.0/8\n# ipv6\nfd00::/8\n"),
+            (2, TextErrorKind::Header)
+        );
+        assert_eq!(
+            error("# ipv6\nfd00::/8\n# ipv4\n10.0.0.0/8\n"),
+            (3, TextErrorKind::Header)
+        );
+        assert_eq!(
+            error("# ipv4\n# ipv6\nfd00::/8\n"),
+            (2, TextErrorKind::Header)
+        );
+        assert_eq!(error("# ipv4\nfd00::/8\n"), (2, TextErrorKind::Header));
+        assert_eq!(
+            error("# ipv4\n10.0.0.0/8\n# ipv6\n"),
+            (3, TextErrorKind::Header)
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::IpFamilyAddr;
+
+pub(crate) fn max<A: IpFamilyAddr>() -> u128 {
+    u128::MAX >> (128 - A::BYTES * 8)
+}
+
+pub(crate) fn to_u128<A: IpFamilyAddr>(addr: A) -> u128 {
+    addr.octets()
+        .as_ref()
+        .iter()
+        .fold(0, |bits, &octet| bits << 8 | u128::from(octet))
+}
+
+pub(crate) fn from_u128<A: IpFamilyAddr>(bits: u128) -> Option<A> {
+    if bits > max::<A>() {
+        return None;
+    }
+    A::from_byte_slice(&bits.to_be_bytes()[16 - A::BYTES..]).ok()
+}
+
+pub(crate) fn checked_add<A: IpFamilyAddr>(addr: A, n: u128) -> Option<A> {
+    from_u128(to_u128(addr).checked_add(n)?)
+}
+
+pub(crate) fn checked_sub<A: IpFamilyAddr>(addr: A, n: u128) -> Option<A> {
+    from_u128(to_u128(addr).checked_sub(n)?)
+}
+
+pub(crate) fn saturating_add<A: IpFamilyAddr>(addr: A, n: u128) -> A {
+    let bits = to_u128(addr).saturating_add(n).min(max::<A>());
+    from_u128(bits).expect("saturated value fits the family")
+}
+
+pub(crate) fn saturating_sub<A: IpFamilyAddr>(addr: A, n: u128) -> A {
+    from_u128(to_u128(addr).saturating_sub(n)).expect("smaller value fits the family")
+}
@@ -0,0 +1,395 @@
+use std::{
+    env,
+    io::{self, BufRead},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    process::ExitCode,
+};
+
+use ip_family::{
+    AddrDisplay, AnyIpNet, IpFamily, IpFamilyAddr, IpFamilyExt, IpFamilyV4, IpFamilyV6, IpPrefix,
+    Nat64Prefix, Network,
+};
+
+const USAGE: &str = "usage: ip-family classify [ADDR...]
+       ip-family convert <mapped|unmapped|exploded> [ADDR...]
+       ip-family nat64 [--prefix PREFIX] [ADDR...]
+       ip-family ptr [ADDR|NAME...]
+       ip-family aggregate [PREFIX...]
+       ip-family subnet [PREFIX...]
+
+Inputs are read from stdin, one per line, when none are given.
+
+nat64 synthesizes IPv6 addresses from IPv4 ones and extracts IPv4 addresses
+from IPv6 ones, under 64:ff9b::/96 unless another RFC 6052 prefix is given.
+ptr turns addresses into reverse DNS names and names back into addresses.";
+
+fn family_name(family: IpFamily) -> &'static str {
+    match family {
+        IpFamily::V4 => "IPv4",
+        IpFamily::V6 => "IPv6",
+    }
+}
+
+fn parse_addr(input: &str) -> Result<IpAddr, String> {
+    input.parse().map_err(|err| format!("{input}: {err}"))
+}
+
+fn parse_net(input: &str) -> Result<AnyIpNet, String> {
+    input.parse().map_err(|err| format!("{input}: {err}"))
+}
+
+fn v4_classes(addr: &Ipv4Addr) -> Vec<&'static str> {
+    [
+        (addr.is_unspecified(), "unspecified"),
+        (addr.is_loopback(), "loopback"),
+        (addr.is_private(), "private"),
+        (addr.is_link_local(), "link-local"),
+        (addr.is_multicast(), "multicast"),
+        (addr.is_broadcast(), "broadcast"),
+        (addr.is_documentation(), "documentation"),
+    ]
+    .into_iter()
+    .filter_map(|(matches, name)| matches.then_some(name))
+    .collect()
+}
+
+fn v6_classes(addr: &Ipv6Addr) -> Vec<&'static str> {
+    [
+        (addr.is_unspecified(), "unspecified"),
+        (addr.is_loopback(), "loopback"),
+        (addr.is_unique_local(), "unique-local"),
+        (addr.is_unicast_link_local(), "link-local"),
+        (addr.is_multicast(), "multicast"),
+        (addr.to_ipv4_mapped().is_some(), "ipv4-mapped"),
+    ]
+    .into_iter()
+    .filter_map(|(matches, name)| matches.then_some(name))
+    .collect()
+}
+
+fn classify(input: &str) -> Result<String, String> {
+    let addr = parse_addr(input)?;
+    let classes = match &addr {
+        IpAddr::V4(addr) => v4_classes(addr),
+        IpAddr::V6(addr) => v6_classes(addr),
+    };
+    let classes = if classes.is_empty() {
+        "unicast".to_owned()
+    } else {
+        classes.join(",")
+    };
+    Ok(format!("{addr} {} {classes}", family_name(addr.family())))
+}
+
+fn convert(form: &str, input: &str) -> Result<String, String> {
+    match (form, parse_addr(input)?) {
+        ("mapped", IpAddr::V4(addr)) => Ok(addr.to_ipv6_mapped().to_string()),
+        ("mapped", IpAddr::V6(addr)) => Ok(addr.to_string()),
+        ("unmapped", addr) => Ok(addr.to_canonical().to_string()),
+        ("exploded", addr) => Ok(format!("{:#}", AddrDisplay(addr))),
+        _ => Err(format!("unknown form {form:?}")),
+    }
+}
+
+fn parse_nat64_prefix(input: &str) -> Result<Nat64Prefix, String> {
+    let prefix: IpPrefix = input.parse().map_err(|err| format!("{input}: {err}"))?;
+    let IpAddr::V6(addr) = prefix.addr() else {
+        return Err(format!("{input}: NAT64 prefixes are IPv6"));
+    };
+    Nat64Prefix::new(addr, prefix.len()).map_err(|err| format!("{input}: {err}"))
+}
+
+/// Synthesizes from IPv4 and extracts from IPv6.
+fn nat64(prefix: Nat64Prefix, input: &str) -> Result<String, String> {
+    match parse_addr(input)? {
+        IpAddr::V4(addr) => Ok(prefix.embed(addr).to_string()),
+        IpAddr::V6(addr) => prefix
+            .extract(addr)
+            .map(|addr| addr.to_string())
+            .ok_or_else(|| format!("{input}: not under {prefix}")),
+    }
+}
+
+/// Names ending in `.arpa` are parsed back into addresses.
+fn ptr(input: &str) -> Result<String, String> {
+    let name = input
+        .strip_suffix('.')
+        .unwrap_or(input)
+        .to_ascii_lowercase();
+    let addr = if name.ends_with(".in-addr.arpa") {
+        Ipv4Addr::from_ptr_name(input).map(IpAddr::from)
+    } else if name.ends_with(".ip6.arpa") {
+        Ipv6Addr::from_ptr_name(input).map(IpAddr::from)
+    } else {
+        let addr = parse_addr(input)?;
+        return Ok(match addr {
+            IpAddr::V4(addr) => addr.ptr_name(),
+            IpAddr::V6(addr) => addr.ptr_name(),
+        });
+    };
+    addr.map(|addr| addr.to_string())
+        .map_err(|err| format!("{input}: {err}"))
+}
+
+/// The fewest networks covering the inputs, IPv4 first.
+fn aggregate(inputs: &[String]) -> Result<Vec<String>, String> {
+    let nets = inputs
+        .iter()
+        .map(|input| parse_net(input))
+        .collect::<Result<Vec<_>, _>>()?;
+    let v4 = Network::<IpFamilyV4>::aggregate(nets.iter().filter_map(AnyIpNet::to_network));
+    let v6 = Network::<IpFamilyV6>::aggregate(nets.iter().filter_map(AnyIpNet::to_network));
+    Ok(v4
+        .iter()
+        .map(ToString::to_string)
+        .chain(v6.iter().map(ToString::to_string))
+        .collect())
+}
+
+/// `2^bits` in decimal, which for 128 bits does not fit a `u128`.
+fn power_of_two(bits: u32) -> String {
+    match 1_u128.checked_shl(bits) {
+        Some(count) if bits < 128 => count.to_string(),
+        _ => "340282366920938463463374607431768211456".to_owned(),
+    }
+}
+
+fn subnet_line<F>(net: Network<F>, extra: &[(&str, String)]) -> String
+where
+    F: ip_family::AnyIpFamily,
+{
+    let host_bits = u32::from(F::Addr::MAX_PREFIX - net.len());
+    let first = net.hosts().next().expect("networks have hosts");
+    // `hosts` skips the network and broadcast addresses together.
+    let last = if first == net.network() {
+        net.last()
+    } else {
+        net.last().saturating_sub(1)
+    };
+    let mut fields = vec![("network", net.network().to_string())];
+    fields.extend(extra.iter().cloned());
+    fields.extend([
+        ("first", first.to_string()),
+        ("last", last.to_string()),
+        ("addresses", power_of_two(host_bits)),
+    ]);
+    let fields: Vec<_> = fields
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect();
+    format!("{net} {}", fields.join(" "))
+}
+
+/// The bounds and size of a network. `first` and `last` are the usable
+/// hosts, which excludes the network and broadcast addresses of IPv4
+/// networks up to /30.
+fn subnet(input: &str) -> Result<String, String> {
+    let net = parse_net(input)?;
+    if let Some(net) = net.to_network::<IpFamilyV4>() {
+        let netmask = Ipv4Addr::netmask_from_prefix(net.len()).expect("valid prefix length");
+        let extra = [
+            ("netmask", netmask.to_string()),
+            ("broadcast", net.broadcast().to_string()),
+        ];
+        return Ok(subnet_line(net, &extra));
+    }
+    let net = net.to_network::<IpFamilyV6>().expect("IPv6 network");
+    Ok(subnet_line(net, &[]))
+}
+
+fn read_inputs(args: Vec<String>) -> io::Result<Vec<String>> {
+    let lines = if args.is_empty() {
+        io::stdin().lock().lines().collect::<io::Result<_>>()?
+    } else {
+        args
+    };
+    Ok(lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+fn report(result: Result<Vec<String>, String>) -> ExitCode {
+    match result {
+        Ok(lines) => {
+            for line in lines {
+                println!("{line}");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>, op: impl Fn(&str) -> Result<String, String>) -> ExitCode {
+    let inputs = match read_inputs(args) {
+        Ok(inputs) => inputs,
+        Err(err) => return report(Err(err.to_string())),
+    };
+    let mut status = ExitCode::SUCCESS;
+    for input in inputs {
+        if report(op(&input).map(|line| vec![line])) != ExitCode::SUCCESS {
+            status = ExitCode::FAILURE;
+        }
+    }
+    status
+}
+
+fn usage() -> ExitCode {
+    eprintln!("{USAGE}");
+    ExitCode::from(2)
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        return usage();
+    }
+    let command = args.remove(0);
+    match command.as_str() {
+        "classify" => run(args, classify),
+        "convert" if !args.is_empty() => {
+            let form = args.remove(0);
+            run(args, |input| convert(&form, input))
+        }
+        "nat64" => {
+            let mut prefix = Nat64Prefix::WELL_KNOWN;
+            if args.first().map(String::as_str) == Some("--prefix") {
+                if args.len() < 2 {
+                    return usage();
+                }
+                prefix = match parse_nat64_prefix(&args[1]) {
+                    Ok(prefix) => prefix,
+                    Err(err) => return report(Err(err)),
+                };
+                args.drain(..2);
+            }
+            run(args, |input| nat64(prefix, input))
+        }
+        "ptr" => run(args, ptr),
+        "aggregate" => report(
+            read_inputs(args)
+                .map_err(|err| err.to_string())
+                .and_then(|inputs| aggregate(&inputs)),
+        ),
+        "subnet" => run(args, subnet),
+        _ => usage(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn classify_and_convert() {
+        assert_eq!(
+            classify("192.0.2.1").unwrap(),
+            "192.0.2.1 IPv4 documentation"
+        );
+        assert_eq!(
+            classify("::ffff:10.0.0.1").unwrap(),
+            "::ffff:10.0.0.1 IPv6 ipv4-mapped"
+        );
+        assert_eq!(convert("mapped", "192.0.2.1").unwrap(), "::ffff:192.0.2.1");
+        assert_eq!(
+            convert("unmapped", "::ffff:192.0.2.1").unwrap(),
+            "192.0.2.1"
+        );
+        assert!(convert("sideways", "192.0.2.1").is_err());
+        assert!(classify("nope").is_err());
+    }
+
+    #[test]
+    fn nat64_synthesizes_and_extracts() {
+        let well_known = Nat64Prefix::WELL_KNOWN;
+        assert_eq!(
+            nat64(well_known, "192.0.2.33").unwrap(),
+            "64:ff9b::c000:221"
+        );
+        assert_eq!(
+            nat64(well_known, "64:ff9b::192.0.2.33").unwrap(),
+            "192.0.2.33"
+        );
+        assert!(nat64(well_known, "2001:db8::1").is_err());
+
+        let prefix = parse_nat64_prefix("2001:db8:100::/40").unwrap();
+        assert_eq!(nat64(prefix, "192.0.2.33").unwrap(), "2001:db8:1c0:2:21::");
+        assert_eq!(nat64(prefix, "2001:db8:1c0:2:21::").unwrap(), "192.0.2.33");
+    }
+
+    #[test]
+    fn nat64_prefix_errors() {
+        assert!(parse_nat64_prefix("10.0.0.0/8").is_err());
+        assert!(parse_nat64_prefix("2001:db8::/33").is_err());
+        assert!(parse_nat64_prefix("2001:db8::1/32").is_err());
+    }
+
+    #[test]
+    fn ptr_names_both_ways() {
+        assert_eq!(ptr("192.0.2.1").unwrap(), "1.2.0.192.in-addr.arpa");
+        assert_eq!(ptr("1.2.0.192.in-addr.arpa.").unwrap(), "192.0.2.1");
+        let name = ptr("2001:db8::1").unwrap();
+        assert!(name.starts_with("1.0.0.0.") && name.ends_with(".8.b.d.0.1.0.0.2.ip6.arpa"));
+        assert_eq!(ptr(&name.to_uppercase()).unwrap(), "2001:db8::1");
+        assert!(ptr("1.2.0.in-addr.arpa").is_err());
+        assert!(ptr("example.com").is_err());
+    }
+
+    #[test]
+    fn aggregates_per_family() {
+        let inputs = strings(&[
+            "2001:db8:1::/48",
+            "192.0.2.128/25",
+            "2001:db8::/48",
+            "192.0.2.0/25",
+            "198.51.100.7",
+        ]);
+        assert_eq!(
+            aggregate(&inputs).unwrap(),
+            strings(&["192.0.2.0/24", "198.51.100.7/32", "2001:db8::/47"])
+        );
+        assert_eq!(aggregate(&[]).unwrap(), Vec::<String>::new());
+        assert!(aggregate(&strings(&["10.0.0.1/8"])).is_err());
+    }
+
+    #[test]
+    fn subnet_math() {
+        assert_eq!(
+            subnet("192.0.2.0/24").unwrap(),
+            "192.0.2.0/24 network=192.0.2.0 netmask=255.255.255.0 broadcast=192.0.2.255 \
+             first=192.0.2.1 last=192.0.2.254 addresses=256"
+        );
+        assert_eq!(
+            subnet("192.0.2.7").unwrap(),
+            "192.0.2.7/32 network=192.0.2.7 netmask=255.255.255.255 broadcast=192.0.2.7 \
+             first=192.0.2.7 last=192.0.2.7 addresses=1"
+        );
+        assert_eq!(
+            subnet("2001:db8::/126").unwrap(),
+            "2001:db8::/126 network=2001:db8:: first=2001:db8:: last=2001:db8::3 addresses=4"
+        );
+        assert!(subnet("10.0.0.1/8").is_err());
+    }
+
+    #[test]
+    fn subnet_of_whole_spaces() {
+        assert!(subnet("0.0.0.0/0")
+            .unwrap()
+            .ends_with(" addresses=4294967296"));
+        assert_eq!(
+            subnet("::/0").unwrap(),
+            "::/0 network=:: first=:: last=ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff \
+             addresses=340282366920938463463374607431768211456"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Type states for [`DualStackBuilder`].
+
+use crate::PerFamily;
+
+/// The family has not been configured yet.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Missing;
+
+/// The family has been configured with a value.
+#[derive(Clone, Copy, Debug)]
+pub struct Configured<T>(pub T);
+
+/// The family has been explicitly left out.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Disabled;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A family state that may be passed to
+/// [`DualStackBuilder::build_partial`].
+pub trait Slot<T>: sealed::Sealed {
+    fn into_option(self) -> Option<T>;
+}
+
+impl<T> sealed::Sealed for Configured<T> {}
+impl<T> Slot<T> for Configured<T> {
+    fn into_option(self) -> Option<T> {
+        Some(self.0)
+    }
+}
+
+impl sealed::Sealed for Disabled {}
+impl<T> Slot<T> for Disabled {
+    fn into_option(self) -> Option<T> {
+        None
+    }
+}
+
+/// Collects one value per family, where `build` only exists once both
+/// families are configured and `build_partial` once every family is either
+/// configured or explicitly disabled.
+#[derive(Clone, Copy, Debug)]
+pub struct DualStackBuilder<S4 = Missing, S6 = Missing> {
+    v4: S4,
+    v6: S6,
+}
+
+impl DualStackBuilder {
+    pub const fn new() -> Self {
+        Self {
+            v4: Missing,
+            v6: Missing,
+        }
+    }
+}
+
+impl Default for DualStackBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S6> DualStackBuilder<Missing, S6> {
+    pub fn v4<T>(self, value: T) -> DualStackBuilder<Configured<T>, S6> {
+        DualStackBuilder {
+            v4: Configured(value),
+            v6: self.v6,
+        }
+    }
+
+    pub fn without_v4(self) -> DualStackBuilder<Disabled, S6> {
+        DualStackBuilder {
+            v4: Disabled,
+            v6: self.v6,
+        }
+    }
+}
+
+impl<S4> DualStackBuilder<S4, Missing> {
+    pub fn v6<T>(self, value: T) -> DualStackBuilder<S4, Configured<T>> {
+        DualStackBuilder {
+            v4: self.v4,
+            v6: Configured(value),
+        }
+    }
+
+    pub fn without_v6(self) -> DualStackBuilder<S4, Disabled> {
+        DualStackBuilder {
+            v4: self.v4,
+            v6: Disabled,
+        }
+    }
+}
+
+impl<T> DualStackBuilder<Configured<T>, Configured<T>> {
+    pub fn build(self) -> PerFamily<T> {
+        PerFamily::new(self.v4.0, self.v6.0)
+    }
+}
+
+impl<S4, S6> DualStackBuilder<S4, S6> {
+    pub fn build_partial<T>(self) -> PerFamily<Option<T>>
+    where
+        S4: Slot<T>,
+        S6: Slot<T>,
+    {
+        PerFamily::new(self.v4.into_option(), self.v6.into_option())
+    }
+}
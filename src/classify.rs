@@ -0,0 +1,63 @@
+//! Classifiers shared by the `IpFamilyAddr` impls and `DynIpAddr for
+//! IpAddr`, written out where the std versions are unstable.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+fn v4_in(addr: Ipv4Addr, base: u32, len: u32) -> bool {
+    addr.to_bits() >> (32 - len) == base >> (32 - len)
+}
+
+fn v6_in(addr: Ipv6Addr, base: u128, len: u32) -> bool {
+    addr.to_bits() >> (128 - len) == base >> (128 - len)
+}
+
+pub(crate) fn v4_is_benchmarking(addr: Ipv4Addr) -> bool {
+    v4_in(addr, 0xc612_0000, 15)
+}
+
+pub(crate) fn v6_is_documentation(addr: Ipv6Addr) -> bool {
+    v6_in(addr, 0x2001_0db8 << 96, 32) || v6_in(addr, 0x3fff << 112, 20)
+}
+
+pub(crate) fn v6_is_benchmarking(addr: Ipv6Addr) -> bool {
+    v6_in(addr, 0x2001_0002 << 96, 48)
+}
+
+/// Follows the unstable `Ipv4Addr::is_global`.
+#[cfg(feature = "global")]
+pub(crate) fn v4_is_global(addr: Ipv4Addr) -> bool {
+    let octets = addr.octets();
+    !(octets[0] == 0
+        || addr.is_private()
+        || v4_in(addr, 0x6440_0000, 10)
+        || addr.is_loopback()
+        || addr.is_link_local()
+        // 192.0.0.0/24, except the PCP and TURN anycast addresses.
+        || (v4_in(addr, 0xc000_0000, 24) && octets[3] != 9 && octets[3] != 10)
+        || addr.is_documentation()
+        || v4_is_benchmarking(addr)
+        || v4_in(addr, 0xf000_0000, 4)
+        || addr.is_broadcast())
+}
+
+/// Follows the unstable `Ipv6Addr::is_global`.
+#[cfg(feature = "global")]
+pub(crate) fn v6_is_global(addr: Ipv6Addr) -> bool {
+    !(addr.is_unspecified()
+        || addr.is_loopback()
+        || v6_in(addr, 0xffff << 32, 96)
+        || v6_in(addr, 0x0064_ff9b_0001 << 80, 48)
+        || v6_in(addr, 0x0100 << 112, 64)
+        // 2001::/23, except the globally reachable assignments in it.
+        || (v6_in(addr, 0x2001 << 112, 23)
+            && !(addr.to_bits() == 0x2001_0001 << 96 | 1
+                || addr.to_bits() == 0x2001_0001 << 96 | 2
+                || v6_in(addr, 0x2001_0003 << 96, 32)
+                || v6_in(addr, 0x2001_0004_0112 << 80, 48)
+                || v6_in(addr, 0x2001_0020 << 96, 28)
+                || v6_in(addr, 0x2001_0030 << 96, 28)))
+        || v6_is_documentation(addr)
+        || v6_in(addr, 0x5f00 << 112, 16)
+        || addr.is_unique_local()
+        || addr.is_unicast_link_local())
+}
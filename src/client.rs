@@ -0,0 +1,102 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    net::IpAddr,
+};
+
+use crate::IpPrefix;
+
+/// The canonical identity of a client, see [`client_key`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct ClientKey(IpPrefix);
+
+impl ClientKey {
+    pub const fn prefix(&self) -> IpPrefix {
+        self.0
+    }
+
+    /// A fixed-size encoding: a family tag (`4` or `6`), the prefix length
+    /// and the 16 address bytes, IPv4 left-aligned and zero-padded.
+    pub fn to_bytes(&self) -> [u8; 18] {
+        let mut bytes = [0; 18];
+        bytes[1] = self.0.len();
+        match self.0.addr() {
+            IpAddr::V4(addr) => {
+                bytes[0] = 4;
+                bytes[2..6].copy_from_slice(&addr.octets());
+            }
+            IpAddr::V6(addr) => {
+                bytes[0] = 6;
+                bytes[2..].copy_from_slice(&addr.octets());
+            }
+        }
+        bytes
+    }
+}
+
+/// IPv4 keys render as the bare address, IPv6 keys as the prefix, such as
+/// `2001:db8:1:2::/64`.
+impl Display for ClientKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0.addr() {
+            IpAddr::V4(addr) => Display::fmt(&addr, f),
+            IpAddr::V6(_) => Display::fmt(&self.0, f),
+        }
+    }
+}
+
+/// Derives the key under which a peer is logged, rate limited and
+/// deduplicated.
+///
+/// IPv4-mapped addresses are unmapped, so a client reaching a dual-stack
+/// socket gets the same key as over IPv4. IPv4 addresses are kept whole,
+/// IPv6 addresses are truncated to `v6_prefix_len` bits because a single
+/// host usually controls a whole /64 or more.
+pub fn client_key(addr: IpAddr, v6_prefix_len: u8) -> ClientKey {
+    match addr.to_canonical() {
+        addr @ IpAddr::V4(_) => ClientKey(IpPrefix::host(addr)),
+        addr @ IpAddr::V6(_) => ClientKey(IpPrefix::new(addr, v6_prefix_len)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn mapped_addresses_share_the_ipv4_key() {
+        let mapped = client_key(ip("::ffff:192.0.2.1"), 64);
+        assert_eq!(mapped, client_key(ip("192.0.2.1"), 64));
+        assert_eq!(mapped.prefix(), "192.0.2.1/32".parse().unwrap());
+        assert_eq!(mapped.to_string(), "192.0.2.1");
+    }
+
+    #[test]
+    fn ipv6_addresses_are_aggregated() {
+        let key = client_key(ip("2001:db8:1:2:a:b:c:d"), 64);
+        assert_eq!(key, client_key(ip("2001:db8:1:2::1"), 64));
+        assert_ne!(key, client_key(ip("2001:db8:1:3::1"), 64));
+        assert_eq!(key.to_string(), "2001:db8:1:2::/64");
+        assert_eq!(
+            client_key(ip("2001:db8:1:2::1"), 48).to_string(),
+            "2001:db8:1::/48"
+        );
+        assert_eq!(
+            client_key(ip("2001:db8::1"), 128).to_string(),
+            "2001:db8::1/128"
+        );
+    }
+
+    #[test]
+    fn bytes_are_tagged_and_padded() {
+        let v4 = client_key(ip("192.0.2.1"), 64).to_bytes();
+        assert_eq!(v4[..6], [4, 32, 192, 0, 2, 1]);
+        assert!(v4[6..].iter().all(|&byte| byte == 0));
+        let v6 = client_key(ip("2001:db8::1"), 32).to_bytes();
+        assert_eq!(v6[..6], [6, 32, 0x20, 0x01, 0x0d, 0xb8]);
+        assert!(v6[6..].iter().all(|&byte| byte == 0));
+    }
+}
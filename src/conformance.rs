@@ -0,0 +1,325 @@
+//! Reusable assertions for the trait laws and for family-generic wrappers,
+//! also used by [`ip_family_conformance_tests!`](crate::ip_family_conformance_tests).
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use crate::{AnyIpFamily, IpFamilyAddr, IpFamilyExt, IpFamilySocketAddr};
+
+/// Loopback, unspecified, all-ones, documentation and multicast addresses.
+pub fn addr_samples<F: AnyIpFamily>() -> Vec<F::Addr> {
+    let prefix: &[u8] = if F::Addr::BYTES == 4 {
+        &[192, 0, 2]
+    } else {
+        &[0x20, 0x01, 0x0d, 0xb8]
+    };
+    let multicast: &[u8] = if F::Addr::BYTES == 4 {
+        &[224]
+    } else {
+        &[0xff, 0x02]
+    };
+    let with_prefix = |prefix: &[u8], last: u8| {
+        let mut bytes = vec![0; F::Addr::BYTES];
+        bytes[..prefix.len()].copy_from_slice(prefix);
+        bytes[F::Addr::BYTES - 1] = last;
+        F::Addr::from_byte_slice(&bytes).expect("sample has the family's length")
+    };
+    vec![
+        F::Addr::LOCALHOST,
+        F::Addr::UNSPECIFIED,
+        F::Addr::from_byte_slice(&vec![0xff; F::Addr::BYTES])
+            .expect("sample has the family's length"),
+        with_prefix(prefix, 1),
+        with_prefix(prefix, 254),
+        with_prefix(multicast, 251),
+    ]
+}
+
+/// [`addr_samples`] combined with the ports 0, 80 and 65535.
+pub fn socket_addr_samples<F: AnyIpFamily>() -> Vec<F::SocketAddr> {
+    addr_samples::<F>()
+        .into_iter()
+        .flat_map(|addr| [0, 80, u16::MAX].map(|port| F::SocketAddr::new(addr, port)))
+        .collect()
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Checks that `Eq`, `Ord` and `Hash` agree and that `Ord` is a total order
+/// over `values`.
+pub fn check_ordering<T: Ord + Hash + Debug>(values: &[T]) {
+    for a in values {
+        assert_eq!(
+            a.cmp(a),
+            std::cmp::Ordering::Equal,
+            "{a:?} is not equal to itself"
+        );
+        for b in values {
+            assert_eq!(
+                a.cmp(b),
+                b.cmp(a).reverse(),
+                "{a:?} and {b:?} are not antisymmetric"
+            );
+            assert_eq!(
+                a == b,
+                a.cmp(b).is_eq(),
+                "Eq and Ord disagree on {a:?} and {b:?}"
+            );
+            if a == b {
+                assert_eq!(
+                    hash_of(a),
+                    hash_of(b),
+                    "equal values {a:?} hash differently"
+                );
+            }
+            for c in values {
+                if a <= b && b <= c {
+                    assert!(a <= c, "{a:?} <= {b:?} <= {c:?} is not transitive");
+                }
+            }
+        }
+    }
+}
+
+/// Checks that a wrapper around `I` displays like `I` and parses back from
+/// its own output.
+pub fn check_wrapper<T, I>(samples: &[I])
+where
+    T: From<I> + Display + FromStr + Ord + Hash + Debug,
+    T::Err: Debug,
+    I: Copy + Display,
+{
+    let values: Vec<T> = samples.iter().copied().map(T::from).collect();
+    for (value, inner) in values.iter().zip(samples) {
+        let text = value.to_string();
+        assert_eq!(
+            text,
+            inner.to_string(),
+            "{value:?} displays differently from its inner value"
+        );
+        assert_eq!(
+            &text.parse::<T>().unwrap(),
+            value,
+            "{value:?} does not round-trip through {text:?}"
+        );
+    }
+    check_ordering(&values);
+}
+
+/// Checks the `Raw`, `Bytes`, `Display`/`FromStr` and `IpAddr` round trips
+/// and that classification agrees with the erased `IpAddr`.
+pub fn check_addr_laws<F: AnyIpFamily>(addr: F::Addr) {
+    let raw = <F::Addr as IpFamilyAddr>::Raw::from(addr);
+    assert_eq!(
+        F::Addr::from(raw),
+        addr,
+        "{addr:?} does not round-trip through Raw"
+    );
+    assert_eq!(
+        F::Addr::from_bits(addr.to_bits()),
+        addr,
+        "{addr:?} does not round-trip through to_bits"
+    );
+
+    let octets = addr.octets();
+    assert_eq!(
+        octets.as_ref().len(),
+        F::Addr::BYTES,
+        "{addr:?} has the wrong octet count"
+    );
+    assert_eq!(
+        F::Addr::from_byte_slice(octets.as_ref()),
+        Ok(addr),
+        "{addr:?} does not round-trip through from_byte_slice"
+    );
+    assert_eq!(
+        F::Addr::from(octets),
+        addr,
+        "{addr:?} does not round-trip through Bytes"
+    );
+    assert_eq!(
+        F::Addr::from_segments(addr.segments()),
+        addr,
+        "{addr:?} does not round-trip through Segments"
+    );
+
+    let text = addr.to_string();
+    assert!(
+        text.len() <= F::Addr::MAX_DISPLAY_LEN,
+        "{text:?} exceeds MAX_DISPLAY_LEN"
+    );
+    let mut buf = vec![0; F::Addr::MAX_DISPLAY_LEN];
+    assert_eq!(
+        addr.write_to(&mut buf),
+        Ok(text.as_str()),
+        "{addr:?} does not match its Display output through write_to"
+    );
+    assert_eq!(
+        text.parse::<F::Addr>().ok(),
+        Some(addr),
+        "{addr:?} does not round-trip through {text:?}"
+    );
+
+    let erased: IpAddr = addr.into();
+    assert!(
+        erased.family() == F::FAMILY,
+        "{addr:?} erases to the wrong family"
+    );
+    assert_eq!(
+        erased.to_string(),
+        text,
+        "{addr:?} displays differently once erased"
+    );
+    assert_eq!(
+        addr.is_unspecified(),
+        erased.is_unspecified(),
+        "is_unspecified disagrees for {addr:?}"
+    );
+    assert_eq!(
+        addr.is_loopback(),
+        erased.is_loopback(),
+        "is_loopback disagrees for {addr:?}"
+    );
+    assert_eq!(
+        addr.is_multicast(),
+        erased.is_multicast(),
+        "is_multicast disagrees for {addr:?}"
+    );
+}
+
+/// Checks the accessors, `Display`/`FromStr` and `SocketAddr` round trips.
+pub fn check_socket_addr_laws<F: AnyIpFamily>(socket_addr: F::SocketAddr) {
+    let (ip, port) = (socket_addr.ip(), socket_addr.port());
+    let rebuilt = F::SocketAddr::new(ip, port);
+    assert_eq!(
+        (rebuilt.ip(), rebuilt.port()),
+        (ip, port),
+        "{socket_addr:?} does not round-trip through new"
+    );
+
+    // Starts from `socket_addr` so the IPv6 flowinfo and scope id carry over.
+    let mut copy = socket_addr.with_ip(F::Addr::UNSPECIFIED).with_port(0);
+    copy.set_ip(ip);
+    copy.set_port(port);
+    assert_eq!(
+        copy, socket_addr,
+        "{socket_addr:?} does not round-trip through the setters"
+    );
+
+    let text = socket_addr.to_string();
+    assert!(
+        text.len() <= F::SocketAddr::MAX_DISPLAY_LEN,
+        "{text:?} exceeds MAX_DISPLAY_LEN"
+    );
+    let mut buf = vec![0; F::SocketAddr::MAX_DISPLAY_LEN];
+    assert_eq!(
+        socket_addr.write_to(&mut buf),
+        Ok(text.as_str()),
+        "{socket_addr:?} does not match its Display output through write_to"
+    );
+    // The text form has no IPv6 flowinfo, so compare what it can express.
+    let parsed = text.parse::<F::SocketAddr>().ok();
+    assert_eq!(
+        parsed.map(|parsed| (parsed.ip(), parsed.port(), parsed.to_string())),
+        Some((ip, port, text.clone())),
+        "{socket_addr:?} does not round-trip through {text:?}"
+    );
+
+    let erased: SocketAddr = socket_addr.into();
+    assert!(
+        erased.family() == F::FAMILY,
+        "{socket_addr:?} erases to the wrong family"
+    );
+    assert_eq!(
+        erased.ip(),
+        ip.into(),
+        "{socket_addr:?} erases to a different address"
+    );
+    assert_eq!(
+        erased.port(),
+        port,
+        "{socket_addr:?} erases to a different port"
+    );
+}
+
+/// Runs [`check_addr_laws`], [`check_socket_addr_laws`] and
+/// [`check_ordering`] over the family's samples.
+pub fn check_family_laws<F: AnyIpFamily>() {
+    let addrs = addr_samples::<F>();
+    addrs.iter().copied().for_each(check_addr_laws::<F>);
+    check_ordering(&addrs);
+
+    let socket_addrs = socket_addr_samples::<F>();
+    socket_addrs
+        .iter()
+        .copied()
+        .for_each(check_socket_addr_laws::<F>);
+    check_ordering(&socket_addrs);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "v4")]
+    #[test]
+    fn v4_laws() {
+        check_family_laws::<crate::IpFamilyV4>();
+    }
+
+    #[cfg(feature = "v6")]
+    #[test]
+    fn v6_laws() {
+        check_family_laws::<crate::IpFamilyV6>();
+    }
+
+    #[cfg(feature = "v6")]
+    #[test]
+    fn v6_laws_hold_for_scoped_and_mapped_addresses() {
+        use std::net::{Ipv6Addr, SocketAddrV6};
+
+        let mapped = Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0xc000, 0x0201);
+        check_addr_laws::<crate::IpFamilyV6>(mapped);
+        check_socket_addr_laws::<crate::IpFamilyV6>(SocketAddrV6::new(mapped, 80, 0, 0));
+        check_socket_addr_laws::<crate::IpFamilyV6>(SocketAddrV6::new(
+            Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+            443,
+            7,
+            3,
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "antisymmetric")]
+    fn check_ordering_rejects_inconsistent_orders() {
+        #[derive(PartialEq, Eq, Hash, Debug)]
+        struct Inconsistent(u8);
+
+        impl PartialOrd for Inconsistent {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Inconsistent {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                if self == other {
+                    std::cmp::Ordering::Equal
+                } else {
+                    std::cmp::Ordering::Less
+                }
+            }
+        }
+
+        check_ordering(&[Inconsistent(1), Inconsistent(2)]);
+    }
+}
@@ -0,0 +1,49 @@
+use std::net::SocketAddr;
+
+use crate::{AnyIpFamily, IpFamily, IpFamilyV4, IpFamilyV6};
+
+/// Generic code run for the marker type of a runtime [`IpFamily`], see
+/// [`IpFamily::dispatch`].
+pub trait FamilyVisitor {
+    type Output;
+
+    fn visit<F: AnyIpFamily>(self) -> Self::Output;
+}
+
+impl IpFamily {
+    /// Calls `visitor.visit::<IpFamilyV4>()` or
+    /// `visitor.visit::<IpFamilyV6>()`.
+    pub fn dispatch<V: FamilyVisitor>(self, visitor: V) -> V::Output {
+        match self {
+            Self::V4 => visitor.visit::<IpFamilyV4>(),
+            Self::V6 => visitor.visit::<IpFamilyV6>(),
+        }
+    }
+}
+
+/// Generic code handling socket addresses of either family, such as a
+/// per-peer callback. Store it as a `Box<dyn DynFamilyHandler<Output = _>>`.
+pub trait FamilyHandler {
+    type Output;
+
+    fn handle<F: AnyIpFamily>(&self, addr: F::SocketAddr) -> Self::Output;
+}
+
+/// Object-safe form of [`FamilyHandler`], implemented for every handler.
+pub trait DynFamilyHandler {
+    type Output;
+
+    /// Calls [`FamilyHandler::handle`] for the family of `addr`.
+    fn handle_dyn(&self, addr: SocketAddr) -> Self::Output;
+}
+
+impl<H: FamilyHandler> DynFamilyHandler for H {
+    type Output = H::Output;
+
+    fn handle_dyn(&self, addr: SocketAddr) -> H::Output {
+        match addr {
+            SocketAddr::V4(addr) => self.handle::<IpFamilyV4>(addr),
+            SocketAddr::V6(addr) => self.handle::<IpFamilyV6>(addr),
+        }
+    }
+}
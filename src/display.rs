@@ -0,0 +1,399 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+};
+
+use crate::{sealed, BufferTooSmall};
+
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Formats `value` into the start of `buf`.
+pub(crate) fn write_to<'a>(
+    value: &impl Display,
+    buf: &'a mut [u8],
+) -> Result<&'a str, BufferTooSmall> {
+    let mut writer = SliceWriter { buf, len: 0 };
+    fmt::write(&mut writer, format_args!("{value}")).map_err(|_| BufferTooSmall(()))?;
+    let SliceWriter { buf, len } = writer;
+    Ok(std::str::from_utf8(&buf[..len]).expect("only `str`s were written"))
+}
+
+/// Switches for [`FormattedAddr`]. The default matches the standard
+/// `Display` output.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DisplayOptions {
+    expand_v6: bool,
+    uppercase: bool,
+    pad_v4: bool,
+    brackets: Option<bool>,
+}
+
+impl DisplayOptions {
+    pub const STANDARD: Self = Self {
+        expand_v6: false,
+        uppercase: false,
+        pad_v4: false,
+        brackets: None,
+    };
+    /// The form [`AddrDisplay`] renders with `{:#}`.
+    pub const EXPLODED: Self = Self::STANDARD.expand_v6(true).pad_v4(true);
+
+    /// Writes all eight IPv6 segments as four hex digits, without `::`
+    /// compression or embedded IPv4.
+    pub const fn expand_v6(mut self, expand: bool) -> Self {
+        self.expand_v6 = expand;
+        self
+    }
+
+    /// Writes IPv6 hex digits in uppercase.
+    pub const fn uppercase(mut self, uppercase: bool) -> Self {
+        self.uppercase = uppercase;
+        self
+    }
+
+    /// Zero-pads every IPv4 octet to three digits.
+    pub const fn pad_v4(mut self, pad: bool) -> Self {
+        self.pad_v4 = pad;
+        self
+    }
+
+    /// Whether IPv6 addresses are put in brackets. Without this call only
+    /// socket addresses are; unbracketed IPv6 socket addresses can't be
+    /// parsed back.
+    pub const fn brackets(mut self, brackets: bool) -> Self {
+        self.brackets = Some(brackets);
+        self
+    }
+}
+
+/// Types that can be rendered through [`AddrDisplay`] and [`FormattedAddr`].
+pub trait ExplodedDisplay: sealed::Sealed + Display {
+    fn write_with(&self, options: DisplayOptions, w: &mut dyn fmt::Write) -> fmt::Result;
+
+    fn fmt_exploded(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_with(DisplayOptions::EXPLODED, f)
+    }
+}
+
+/// Renders the standard form with `{}` and the exploded form with `{:#}`.
+///
+/// The exploded form zero-pads every IPv4 octet to three digits and every
+/// IPv6 segment to four hex digits without `::` compression.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct AddrDisplay<T>(pub T);
+
+impl<T: ExplodedDisplay> AddrDisplay<T> {
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+
+    pub fn with_options(self, options: DisplayOptions) -> FormattedAddr<T> {
+        FormattedAddr::new(self.0, options)
+    }
+}
+
+impl<T: ExplodedDisplay> Display for AddrDisplay<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            self.0.fmt_exploded(f)
+        } else {
+            Display::fmt(&self.0, f)
+        }
+    }
+}
+
+/// Renders an address with [`DisplayOptions`]. Width, fill and alignment
+/// flags apply to the whole output, so `{:>47}` right-aligns any address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct FormattedAddr<T> {
+    value: T,
+    options: DisplayOptions,
+}
+
+impl<T: ExplodedDisplay> FormattedAddr<T> {
+    pub const fn new(value: T, options: DisplayOptions) -> Self {
+        Self { value, options }
+    }
+
+    pub const fn options(&self) -> DisplayOptions {
+        self.options
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: ExplodedDisplay> Display for FormattedAddr<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Fits `[ffff:ffff:ffff:ffff:ffff:ffff:255.255.255.255%4294967295]:65535`.
+        let mut buf = [0; 64];
+        let mut writer = SliceWriter {
+            buf: &mut buf,
+            len: 0,
+        };
+        self.value.write_with(self.options, &mut writer)?;
+        let len = writer.len;
+        f.pad(std::str::from_utf8(&buf[..len]).expect("only `str`s were written"))
+    }
+}
+
+impl ExplodedDisplay for Ipv4Addr {
+    fn write_with(&self, options: DisplayOptions, w: &mut dyn fmt::Write) -> fmt::Result {
+        if options.pad_v4 {
+            let [a, b, c, d] = self.octets();
+            write!(w, "{a:03}.{b:03}.{c:03}.{d:03}")
+        } else {
+            write!(w, "{self}")
+        }
+    }
+}
+
+impl ExplodedDisplay for Ipv6Addr {
+    fn write_with(&self, options: DisplayOptions, w: &mut dyn fmt::Write) -> fmt::Result {
+        let brackets = options.brackets == Some(true);
+        if brackets {
+            w.write_str("[")?;
+        }
+        if options.expand_v6 {
+            for (i, segment) in self.segments().iter().enumerate() {
+                if i != 0 {
+                    w.write_str(":")?;
+                }
+                if options.uppercase {
+                    write!(w, "{segment:04X}")?;
+                } else {
+                    write!(w, "{segment:04x}")?;
+                }
+            }
+        } else if options.uppercase {
+            let mut buf = [0; 45];
+            let len = write_to(self, &mut buf).map_err(|_| fmt::Error)?.len();
+            buf[..len].make_ascii_uppercase();
+            w.write_str(std::str::from_utf8(&buf[..len]).expect("uppercasing keeps ASCII"))?;
+        } else {
+            write!(w, "{self}")?;
+        }
+        if brackets {
+            w.write_str("]")?;
+        }
+        Ok(())
+    }
+}
+
+impl ExplodedDisplay for IpAddr {
+    fn write_with(&self, options: DisplayOptions, w: &mut dyn fmt::Write) -> fmt::Result {
+        match self {
+            IpAddr::V4(addr) => addr.write_with(options, w),
+            IpAddr::V6(addr) => addr.write_with(options, w),
+        }
+    }
+}
+
+impl ExplodedDisplay for SocketAddrV4 {
+    fn write_with(&self, options: DisplayOptions, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.ip().write_with(options, w)?;
+        write!(w, ":{}", self.port())
+    }
+}
+
+impl ExplodedDisplay for SocketAddrV6 {
+    fn write_with(&self, options: DisplayOptions, w: &mut dyn fmt::Write) -> fmt::Result {
+        let brackets = options.brackets != Some(false);
+        if brackets {
+            w.write_str("[")?;
+        }
+        self.ip().write_with(options.brackets(false), w)?;
+        if self.scope_id() != 0 {
+            write!(w, "%{}", self.scope_id())?;
+        }
+        if brackets {
+            w.write_str("]")?;
+        }
+        write!(w, ":{}", self.port())
+    }
+}
+
+impl ExplodedDisplay for SocketAddr {
+    fn write_with(&self, options: DisplayOptions, w: &mut dyn fmt::Write) -> fmt::Result {
+        match self {
+            SocketAddr::V4(addr) => addr.write_with(options, w),
+            SocketAddr::V6(addr) => addr.write_with(options, w),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V4: Ipv4Addr = Ipv4Addr::new(192, 0, 2, 1);
+    const V6: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0xab, 1);
+
+    #[test]
+    fn alternate_form_is_exploded() {
+        assert_eq!(format!("{}", AddrDisplay(V4)), "192.0.2.1");
+        assert_eq!(format!("{:#}", AddrDisplay(V4)), "192.000.002.001");
+        assert_eq!(format!("{}", AddrDisplay(V6)), "2001:db8::ab:1");
+        assert_eq!(
+            format!("{:#}", AddrDisplay(V6)),
+            "2001:0db8:0000:0000:0000:0000:00ab:0001"
+        );
+        assert_eq!(
+            format!("{:#}", AddrDisplay(V4.to_ipv6_mapped())),
+            "0000:0000:0000:0000:0000:ffff:c000:0201"
+        );
+    }
+
+    #[test]
+    fn alternate_form_of_socket_addresses() {
+        let v4 = SocketAddr::from((V4, 53));
+        assert_eq!(format!("{}", AddrDisplay(v4)), "192.0.2.1:53");
+        assert_eq!(format!("{:#}", AddrDisplay(v4)), "192.000.002.001:53");
+        let v6 = SocketAddrV6::new(V6, 53, 0, 3);
+        assert_eq!(format!("{}", AddrDisplay(v6)), "[2001:db8::ab:1%3]:53");
+        assert_eq!(
+            format!("{:#}", AddrDisplay(SocketAddr::V6(v6))),
+            "[2001:0db8:0000:0000:0000:0000:00ab:0001%3]:53"
+        );
+    }
+
+    #[cfg(feature = "v4")]
+    #[test]
+    fn write_to_fits_the_longest_ipv4_addresses() {
+        use crate::{IpFamilyAddr, IpFamilySocketAddr};
+
+        let mut buf = [0; Ipv4Addr::MAX_DISPLAY_LEN];
+        let v4 = Ipv4Addr::BROADCAST;
+        assert_eq!(v4.write_to(&mut buf), Ok("255.255.255.255"));
+        let mut buf = [0; SocketAddrV4::MAX_DISPLAY_LEN];
+        let v4 = SocketAddrV4::new(v4, 65535);
+        assert_eq!(v4.write_to(&mut buf), Ok("255.255.255.255:65535"));
+    }
+
+    #[cfg(feature = "v6")]
+    #[test]
+    fn write_to_fits_the_longest_ipv6_addresses() {
+        use crate::{IpFamilyAddr, IpFamilySocketAddr};
+
+        let mut buf = [0; Ipv6Addr::MAX_DISPLAY_LEN];
+        let v6 = Ipv6Addr::from_bits(u128::MAX);
+        assert_eq!(
+            v6.write_to(&mut buf),
+            Ok("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")
+        );
+        let mut buf = [0; SocketAddrV6::MAX_DISPLAY_LEN];
+        let v6 = SocketAddrV6::new(v6, 65535, 0, u32::MAX);
+        assert_eq!(
+            v6.write_to(&mut buf),
+            Ok("[ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff%4294967295]:65535")
+        );
+    }
+
+    #[test]
+    fn write_to_rejects_short_buffers() {
+        let mut buf = [0; 9];
+        assert_eq!(write_to(&V4, &mut buf), Ok("192.0.2.1"));
+        assert_eq!(write_to(&V4, &mut buf[..8]), Err(BufferTooSmall(())));
+        assert_eq!(write_to(&V6, &mut []), Err(BufferTooSmall(())));
+    }
+
+    fn formatted<T: ExplodedDisplay>(value: T, options: DisplayOptions) -> String {
+        FormattedAddr::new(value, options).to_string()
+    }
+
+    #[test]
+    fn standard_options_match_display() {
+        let v6 = SocketAddrV6::new(V6, 443, 0, 2);
+        assert_eq!(formatted(V4, DisplayOptions::default()), V4.to_string());
+        assert_eq!(formatted(V6, DisplayOptions::STANDARD), V6.to_string());
+        assert_eq!(formatted(v6, DisplayOptions::STANDARD), v6.to_string());
+        let mapped = V4.to_ipv6_mapped();
+        assert_eq!(
+            formatted(mapped, DisplayOptions::STANDARD),
+            "::ffff:192.0.2.1"
+        );
+    }
+
+    #[test]
+    fn expanded_and_uppercase_ipv6() {
+        let expanded = DisplayOptions::STANDARD.expand_v6(true);
+        assert_eq!(
+            formatted(V6, expanded),
+            "2001:0db8:0000:0000:0000:0000:00ab:0001"
+        );
+        assert_eq!(
+            formatted(V6, expanded.uppercase(true)),
+            "2001:0DB8:0000:0000:0000:0000:00AB:0001"
+        );
+        let uppercase = DisplayOptions::STANDARD.uppercase(true);
+        assert_eq!(formatted(V6, uppercase), "2001:DB8::AB:1");
+        assert_eq!(formatted(V4, uppercase), "192.0.2.1");
+        assert_eq!(formatted(V4, expanded), "192.0.2.1");
+    }
+
+    #[test]
+    fn padded_ipv4() {
+        let padded = DisplayOptions::STANDARD.pad_v4(true);
+        assert_eq!(formatted(V4, padded), "192.000.002.001");
+        assert_eq!(
+            formatted(SocketAddrV4::new(V4, 8080), padded),
+            "192.000.002.001:8080"
+        );
+        assert_eq!(formatted(V6, padded), "2001:db8::ab:1");
+    }
+
+    #[test]
+    fn brackets_and_zones() {
+        let bracketed = DisplayOptions::STANDARD.brackets(true);
+        let bare = DisplayOptions::STANDARD.brackets(false);
+        assert_eq!(formatted(V6, bracketed), "[2001:db8::ab:1]");
+        assert_eq!(formatted(IpAddr::V6(V6), bare), "2001:db8::ab:1");
+        assert_eq!(formatted(V4, bracketed), "192.0.2.1");
+
+        let zoned = SocketAddrV6::new(V6, 443, 0, 7);
+        assert_eq!(formatted(zoned, bracketed), "[2001:db8::ab:1%7]:443");
+        assert_eq!(formatted(zoned, bare), "2001:db8::ab:1%7:443");
+        assert_eq!(
+            formatted(SocketAddr::V6(zoned), DisplayOptions::EXPLODED),
+            "[2001:0db8:0000:0000:0000:0000:00ab:0001%7]:443"
+        );
+        assert_eq!(
+            formatted(SocketAddr::V4(SocketAddrV4::new(V4, 443)), bracketed),
+            "192.0.2.1:443"
+        );
+    }
+
+    #[test]
+    fn padding_applies_to_the_whole_address() {
+        let value = AddrDisplay(V4).with_options(DisplayOptions::EXPLODED);
+        assert_eq!(format!("{value:>17}"), "  192.000.002.001");
+        assert_eq!(format!("{value:-<16}"), "192.000.002.001-");
+        assert_eq!(value.options(), DisplayOptions::EXPLODED);
+        assert_eq!(value.into_inner(), V4);
+        let longest = SocketAddrV6::new(Ipv6Addr::from_bits(u128::MAX), 65535, 0, u32::MAX);
+        assert_eq!(
+            formatted(longest, DisplayOptions::EXPLODED.uppercase(true)),
+            "[FFFF:FFFF:FFFF:FFFF:FFFF:FFFF:FFFF:FFFF%4294967295]:65535"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    io,
+    net::{Ipv6Addr, SocketAddr, TcpListener, TcpStream},
+};
+
+#[cfg(unix)]
+use crate::PerFamily;
+use crate::{IpFamily, IpFamilyExt, SocketBuilder};
+
+const BACKLOG: i32 = 128;
+
+#[derive(Debug)]
+enum Sockets {
+    /// An IPv6 socket with `IPV6_V6ONLY` off, IPv4 peers show up as mapped
+    /// addresses.
+    Single(TcpListener),
+    /// One socket per family, for systems that cannot turn `IPV6_V6ONLY`
+    /// off.
+    #[cfg(unix)]
+    Split(PerFamily<TcpListener>),
+}
+
+/// Accepts connections of both families on one port.
+#[derive(Debug)]
+pub struct DualStackListener {
+    sockets: Sockets,
+}
+
+fn listen(builder: SocketBuilder, addr: SocketAddr) -> io::Result<TcpListener> {
+    let socket = builder.reuse_address(true).build()?;
+    socket.bind(&addr.into())?;
+    socket.listen(BACKLOG)?;
+    Ok(socket.into())
+}
+
+impl DualStackListener {
+    /// Binds the unspecified addresses on `port`, using a single IPv6
+    /// socket where the system allows it and one socket per family
+    /// otherwise. With port `0` both families get the same ephemeral port.
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let single = listen(
+            SocketBuilder::tcp(IpFamily::V6).only_v6(false),
+            SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), port),
+        );
+        match single {
+            Ok(listener) => Ok(Self {
+                sockets: Sockets::Single(listener),
+            }),
+            #[cfg(unix)]
+            Err(_) => Self::bind_split(port),
+            #[cfg(not(unix))]
+            Err(err) => Err(err),
+        }
+    }
+
+    #[cfg(unix)]
+    fn bind_split(port: u16) -> io::Result<Self> {
+        let v4 = listen(
+            SocketBuilder::tcp(IpFamily::V4),
+            SocketAddr::new(std::net::Ipv4Addr::UNSPECIFIED.into(), port),
+        )?;
+        let port = v4.local_addr()?.port();
+        let v6 = listen(
+            SocketBuilder::tcp(IpFamily::V6).only_v6(true),
+            SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), port),
+        )?;
+        v4.set_nonblocking(true)?;
+        v6.set_nonblocking(true)?;
+        Ok(Self {
+            sockets: Sockets::Split(PerFamily::new(v4, v6)),
+        })
+    }
+
+    pub fn port(&self) -> io::Result<u16> {
+        match &self.sockets {
+            Sockets::Single(listener) => Ok(listener.local_addr()?.port()),
+            #[cfg(unix)]
+            Sockets::Split(listeners) => Ok(listeners.v4.local_addr()?.port()),
+        }
+    }
+
+    /// Whether one IPv6 socket serves both families.
+    pub fn is_single_socket(&self) -> bool {
+        matches!(self.sockets, Sockets::Single(_))
+    }
+
+    /// Blocks until a peer of either family connects. The peer address is
+    /// returned with mapped IPv4 addresses unmapped.
+    pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr, IpFamily)> {
+        let (stream, peer) = match &self.sockets {
+            Sockets::Single(listener) => listener.accept()?,
+            #[cfg(unix)]
+            Sockets::Split(listeners) => accept_either(listeners)?,
+        };
+        let peer = SocketAddr::new(peer.ip().to_canonical(), peer.port());
+        Ok((stream, peer, peer.family()))
+    }
+}
+
+#[cfg(unix)]
+fn accept_either(listeners: &PerFamily<TcpListener>) -> io::Result<(TcpStream, SocketAddr)> {
+    use std::os::fd::AsRawFd;
+
+    loop {
+        let mut fds = [&listeners.v4, &listeners.v6].map(|listener| libc::pollfd {
+            fd: listener.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        });
+        // SAFETY: `fds` is a valid array of two `pollfd`s for the whole call.
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), 2, -1) };
+        if ready < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        for (fd, listener) in fds.iter().zip([&listeners.v4, &listeners.v6]) {
+            if fd.revents == 0 {
+                continue;
+            }
+            // Another thread may have taken the connection since the poll.
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    stream.set_nonblocking(false)?;
+                    return Ok((stream, peer));
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    fmt::{Debug, Display},
+    net::{IpAddr, SocketAddr},
+    ops::Deref,
+};
+
+use crate::{classify, HasFamily, IpFamily, IpFamilyAddr, IpFamilySocketAddr};
+
+/// Octets of an address of either family, borrowed as `&[u8]`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Octets {
+    buf: [u8; 16],
+    len: usize,
+}
+
+impl Octets {
+    fn new(bytes: &[u8]) -> Self {
+        let mut buf = [0; 16];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Self {
+            buf,
+            len: bytes.len(),
+        }
+    }
+}
+
+impl Deref for Octets {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl AsRef<[u8]> for Octets {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Object-safe view of an address, for code that can't be generic over
+/// `AnyIpFamily`.
+pub trait DynIpAddr: HasFamily<Output = IpFamily> + Debug + Display + Send + Sync {
+    fn octets(&self) -> Octets;
+    fn to_ip_addr(&self) -> IpAddr;
+    fn is_unspecified(&self) -> bool;
+    fn is_loopback(&self) -> bool;
+    fn is_multicast(&self) -> bool;
+    fn is_private(&self) -> bool;
+    fn is_link_local(&self) -> bool;
+    fn is_documentation(&self) -> bool;
+    fn is_benchmarking(&self) -> bool;
+    #[cfg(feature = "global")]
+    fn is_global(&self) -> bool;
+}
+
+impl<T> DynIpAddr for T
+where
+    T: IpFamilyAddr + HasFamily<Output = IpFamily>,
+{
+    fn octets(&self) -> Octets {
+        Octets::new(IpFamilyAddr::octets(self).as_ref())
+    }
+
+    fn to_ip_addr(&self) -> IpAddr {
+        (*self).into()
+    }
+
+    fn is_unspecified(&self) -> bool {
+        IpFamilyAddr::is_unspecified(self)
+    }
+
+    fn is_loopback(&self) -> bool {
+        IpFamilyAddr::is_loopback(self)
+    }
+
+    fn is_multicast(&self) -> bool {
+        IpFamilyAddr::is_multicast(self)
+    }
+
+    fn is_private(&self) -> bool {
+        IpFamilyAddr::is_private(self)
+    }
+
+    fn is_link_local(&self) -> bool {
+        IpFamilyAddr::is_link_local(self)
+    }
+
+    fn is_documentation(&self) -> bool {
+        IpFamilyAddr::is_documentation(self)
+    }
+
+    fn is_benchmarking(&self) -> bool {
+        IpFamilyAddr::is_benchmarking(self)
+    }
+
+    #[cfg(feature = "global")]
+    fn is_global(&self) -> bool {
+        IpFamilyAddr::is_global(self)
+    }
+}
+
+impl DynIpAddr for IpAddr {
+    fn octets(&self) -> Octets {
+        match self {
+            IpAddr::V4(addr) => Octets::new(&addr.octets()),
+            IpAddr::V6(addr) => Octets::new(&addr.octets()),
+        }
+    }
+
+    fn to_ip_addr(&self) -> IpAddr {
+        *self
+    }
+
+    fn is_unspecified(&self) -> bool {
+        IpAddr::is_unspecified(self)
+    }
+
+    fn is_loopback(&self) -> bool {
+        IpAddr::is_loopback(self)
+    }
+
+    fn is_multicast(&self) -> bool {
+        IpAddr::is_multicast(self)
+    }
+
+    fn is_private(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => addr.is_private(),
+            IpAddr::V6(addr) => addr.is_unique_local(),
+        }
+    }
+
+    fn is_link_local(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => addr.is_link_local(),
+            IpAddr::V6(addr) => addr.is_unicast_link_local(),
+        }
+    }
+
+    fn is_documentation(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => addr.is_documentation(),
+            IpAddr::V6(addr) => classify::v6_is_documentation(*addr),
+        }
+    }
+
+    fn is_benchmarking(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => classify::v4_is_benchmarking(*addr),
+            IpAddr::V6(addr) => classify::v6_is_benchmarking(*addr),
+        }
+    }
+
+    #[cfg(feature = "global")]
+    fn is_global(&self) -> bool {
+        match self {
+            IpAddr::V4(addr) => classify::v4_is_global(*addr),
+            IpAddr::V6(addr) => classify::v6_is_global(*addr),
+        }
+    }
+}
+
+/// Object-safe view of a socket address, the counterpart of [`DynIpAddr`].
+pub trait DynSocketAddr: HasFamily<Output = IpFamily> + Debug + Display + Send + Sync {
+    fn ip(&self) -> IpAddr;
+    fn port(&self) -> u16;
+    fn to_socket_addr(&self) -> SocketAddr;
+}
+
+impl<T> DynSocketAddr for T
+where
+    T: IpFamilySocketAddr + HasFamily<Output = IpFamily>,
+{
+    fn ip(&self) -> IpAddr {
+        IpFamilySocketAddr::ip(self).into()
+    }
+
+    fn port(&self) -> u16 {
+        IpFamilySocketAddr::port(self)
+    }
+
+    fn to_socket_addr(&self) -> SocketAddr {
+        (*self).into()
+    }
+}
+
+impl DynSocketAddr for SocketAddr {
+    fn ip(&self) -> IpAddr {
+        SocketAddr::ip(self)
+    }
+
+    fn port(&self) -> u16 {
+        SocketAddr::port(self)
+    }
+
+    fn to_socket_addr(&self) -> SocketAddr {
+        *self
+    }
+}
@@ -0,0 +1,513 @@
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+    io,
+    net::{AddrParseError, Ipv6Addr, SocketAddr},
+};
+
+use crate::{IpFamily, IpFamilyExt, IpPrefix};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct WrongLength {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl Display for WrongLength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected {} address bytes, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl StdError for WrongLength {}
+
+/// An address or socket address of the other family, see
+/// [`IpFamilyAddr::try_from_ip_addr`](crate::IpFamilyAddr::try_from_ip_addr).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FamilyMismatchError {
+    pub expected: IpFamily,
+    pub actual: IpFamily,
+}
+
+impl Display for FamilyMismatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "expected an {} address, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl StdError for FamilyMismatchError {}
+
+/// See [`parse_socket_addr_with_family`](crate::parse_socket_addr_with_family).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ParseSocketAddrError {
+    Empty,
+    InvalidAddr,
+    /// The port is not a number in `0..=65535`.
+    InvalidPort,
+    /// The IPv6 scope id is not a number or the name of an interface.
+    InvalidScope,
+    MissingPort,
+    /// `[` without a matching `]`.
+    UnclosedBracket,
+    /// Something other than `:port` follows `]`.
+    TrailingCharacters,
+}
+
+impl Display for ParseSocketAddrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Empty => "empty socket address",
+            Self::InvalidAddr => "invalid IP address",
+            Self::InvalidPort => "invalid port, expected a number from 0 to 65535",
+            Self::InvalidScope => "invalid IPv6 scope id, expected a number or interface name",
+            Self::MissingPort => "missing port, expected e.g. `1.2.3.4:80` or `[::1]:80`",
+            Self::UnclosedBracket => "missing `]` after IPv6 address",
+            Self::TrailingCharacters => "expected `:port` after `]`",
+        })
+    }
+}
+
+impl StdError for ParseSocketAddrError {}
+
+/// Text that is not an address, or an address of the other family, see
+/// [`IpFamily::parse_addr`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseOrMismatchError<E> {
+    Parse(E),
+    Mismatch(FamilyMismatchError),
+}
+
+impl<E: Display> Display for ParseOrMismatchError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => Display::fmt(err, f),
+            Self::Mismatch(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for ParseOrMismatchError<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Parse(err) => Some(err),
+            Self::Mismatch(err) => Some(err),
+        }
+    }
+}
+
+/// A prefix length longer than the address, see
+/// [`IpFamilyAddr::netmask_from_prefix`](crate::IpFamilyAddr::netmask_from_prefix).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InvalidPrefix {
+    pub len: u8,
+    pub max: u8,
+}
+
+impl Display for InvalidPrefix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "prefix length {} exceeds {}", self.len, self.max)
+    }
+}
+
+impl StdError for InvalidPrefix {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ParseIpFamilyError(pub(crate) ());
+
+impl Display for ParseIpFamilyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid IP family, expected e.g. `v4`, `ipv6` or `inet6`")
+    }
+}
+
+impl StdError for ParseIpFamilyError {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ParseFamilyPreferenceError(pub(crate) ());
+
+impl Display for ParseFamilyPreferenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid family preference, expected e.g. `v4-only` or `prefer-v6`")
+    }
+}
+
+impl StdError for ParseFamilyPreferenceError {}
+
+/// A buffer of `MAX_DISPLAY_LEN` bytes always suffices.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BufferTooSmall(pub(crate) ());
+
+impl Display for BufferTooSmall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("buffer too small for the formatted address")
+    }
+}
+
+impl StdError for BufferTooSmall {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ParsePtrError(pub(crate) ());
+
+impl Display for ParsePtrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid reverse DNS name")
+    }
+}
+
+impl StdError for ParsePtrError {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MappingError {
+    /// The policy does not allow converting between families.
+    Refused,
+    /// The IPv6 address does not carry an IPv4 address under the policy.
+    NotEmbedded(Ipv6Addr),
+}
+
+impl Display for MappingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Refused => f.write_str("address family conversion refused by policy"),
+            Self::NotEmbedded(addr) => write!(f, "{addr} does not embed an IPv4 address"),
+        }
+    }
+}
+
+impl StdError for MappingError {}
+
+#[derive(Debug)]
+pub enum ConnectError {
+    /// The source and destination addresses belong to different families.
+    FamilyMismatch {
+        source: SocketAddr,
+        destination: SocketAddr,
+    },
+    Io(io::Error),
+}
+
+impl Display for ConnectError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FamilyMismatch {
+                source,
+                destination,
+            } => write!(
+                f,
+                "cannot connect from {source} to {destination} of a different family"
+            ),
+            Self::Io(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl StdError for ConnectError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::FamilyMismatch { .. } => None,
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for ConnectError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ParsePrefixError {
+    Addr,
+    /// The prefix length is not a number or exceeds the address width.
+    Len,
+    /// Bits after the prefix length are set.
+    HostBits,
+    /// The prefix belongs to the other family.
+    Family,
+}
+
+impl Display for ParsePrefixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Addr => "invalid prefix address",
+            Self::Len => "invalid prefix length",
+            Self::HostBits => "prefix has host bits set",
+            Self::Family => "prefix belongs to the wrong address family",
+        })
+    }
+}
+
+impl StdError for ParsePrefixError {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Nat64PrefixError {
+    /// RFC 6052 only allows /32, /40, /48, /56, /64 and /96.
+    Len(u8),
+    /// Bits after the prefix length are set.
+    HostBits,
+    /// Bits 64 to 71 of a /96 prefix are set.
+    ReservedOctet,
+}
+
+impl Display for Nat64PrefixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Len(len) => write!(f, "invalid NAT64 prefix length {len}"),
+            Self::HostBits => f.write_str("NAT64 prefix has host bits set"),
+            Self::ReservedOctet => f.write_str("NAT64 prefix sets bits 64 to 71"),
+        }
+    }
+}
+
+impl StdError for Nat64PrefixError {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MulticastError {
+    NotMulticast,
+    ReservedScope(u8),
+    ReservedFlags(u8),
+    GroupIdTooLarge,
+    /// The unicast prefix is not IPv6 or longer than 64 bits.
+    InvalidPrefix,
+}
+
+impl Display for MulticastError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotMulticast => f.write_str("not an IPv6 multicast address"),
+            Self::ReservedScope(scope) => write!(f, "reserved multicast scope {scope:x}"),
+            Self::ReservedFlags(flags) => write!(f, "invalid multicast flags {flags:x}"),
+            Self::GroupIdTooLarge => f.write_str("multicast group id does not fit"),
+            Self::InvalidPrefix => f.write_str("invalid unicast prefix for multicast"),
+        }
+    }
+}
+
+impl StdError for MulticastError {}
+
+/// An error at a 1-based line of [`AllowedIps`](crate::AllowedIps) text.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TextError {
+    pub line: usize,
+    pub kind: TextErrorKind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TextErrorKind {
+    Prefix(ParsePrefixError),
+    /// The prefix does not use its canonical spelling.
+    NotCanonical,
+    /// A family header is missing, repeated, out of order or empty.
+    Header,
+    Unsorted,
+    /// The prefix is covered by the one on the previous line.
+    Covered(IpPrefix),
+    MissingNewline,
+    /// A CSV line or header lacks the requested column.
+    MissingColumn,
+}
+
+impl Display for TextError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            TextErrorKind::Prefix(err) => Display::fmt(&err, f),
+            TextErrorKind::NotCanonical => f.write_str("prefix is not in canonical form"),
+            TextErrorKind::Header => f.write_str("misplaced family header"),
+            TextErrorKind::Unsorted => f.write_str("prefixes are not sorted"),
+            TextErrorKind::Covered(by) => write!(f, "prefix is covered by {by}"),
+            TextErrorKind::MissingNewline => f.write_str("missing final newline"),
+            TextErrorKind::MissingColumn => f.write_str("missing column"),
+        }
+    }
+}
+
+impl StdError for TextError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match &self.kind {
+            TextErrorKind::Prefix(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WireError {
+    UnsupportedVersion(u8),
+    ReservedBits(u8),
+    /// The tag names no family or combines flags that never occur.
+    InvalidTag(u8),
+    /// The frame is valid but encodes a different type.
+    UnexpectedFrame,
+    /// The prefix length is too long or host bits are set.
+    InvalidPrefix,
+    /// The slice is too short.
+    Truncated,
+    Io(io::Error),
+}
+
+impl Display for WireError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(f, "unsupported wire version {version}"),
+            Self::ReservedBits(tag) => write!(f, "reserved bits set in wire tag {tag:#04x}"),
+            Self::InvalidTag(tag) => write!(f, "invalid wire tag {tag:#04x}"),
+            Self::UnexpectedFrame => f.write_str("wire frame encodes a different type"),
+            Self::InvalidPrefix => f.write_str("invalid prefix in wire frame"),
+            Self::Truncated => f.write_str("truncated wire frame"),
+            Self::Io(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl StdError for WireError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Running out of input is reported as [`WireError::Truncated`].
+impl From<io::Error> for WireError {
+    fn from(value: io::Error) -> Self {
+        match value.kind() {
+            io::ErrorKind::UnexpectedEof | io::ErrorKind::WriteZero => Self::Truncated,
+            _ => Self::Io(value),
+        }
+    }
+}
+
+/// Any error of this crate, for callers that propagate several of them
+/// with `?`. Every specific error converts into it.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    FamilyMismatch(FamilyMismatchError),
+    InvalidPrefix(InvalidPrefix),
+    /// Text or bytes that do not form the expected value.
+    Parse(Box<dyn StdError + Send + Sync>),
+    /// The input or the output buffer is too short.
+    Truncated,
+    /// A valid value this crate refuses or cannot handle, such as an
+    /// unknown wire version or a mapping the policy does not allow.
+    Unsupported(Box<dyn StdError + Send + Sync>),
+    Io(io::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FamilyMismatch(err) => Display::fmt(err, f),
+            Self::InvalidPrefix(err) => Display::fmt(err, f),
+            Self::Parse(err) | Self::Unsupported(err) => Display::fmt(err, f),
+            Self::Truncated => f.write_str("input or buffer too short"),
+            Self::Io(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::FamilyMismatch(err) => Some(err),
+            Self::InvalidPrefix(err) => Some(err),
+            Self::Parse(err) | Self::Unsupported(err) => Some(&**err),
+            Self::Truncated => None,
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<FamilyMismatchError> for Error {
+    fn from(value: FamilyMismatchError) -> Self {
+        Self::FamilyMismatch(value)
+    }
+}
+
+impl From<InvalidPrefix> for Error {
+    fn from(value: InvalidPrefix) -> Self {
+        Self::InvalidPrefix(value)
+    }
+}
+
+impl From<BufferTooSmall> for Error {
+    fn from(_: BufferTooSmall) -> Self {
+        Self::Truncated
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl<E: StdError + Send + Sync + 'static> From<ParseOrMismatchError<E>> for Error {
+    fn from(value: ParseOrMismatchError<E>) -> Self {
+        match value {
+            ParseOrMismatchError::Parse(err) => Self::Parse(Box::new(err)),
+            ParseOrMismatchError::Mismatch(err) => Self::FamilyMismatch(err),
+        }
+    }
+}
+
+/// The source is expected to be the family of the destination.
+impl From<ConnectError> for Error {
+    fn from(value: ConnectError) -> Self {
+        match value {
+            ConnectError::FamilyMismatch {
+                source,
+                destination,
+            } => Self::FamilyMismatch(FamilyMismatchError {
+                expected: destination.family(),
+                actual: source.family(),
+            }),
+            ConnectError::Io(err) => Self::Io(err),
+        }
+    }
+}
+
+impl From<WireError> for Error {
+    fn from(value: WireError) -> Self {
+        match value {
+            WireError::Truncated => Self::Truncated,
+            WireError::Io(err) => Self::Io(err),
+            WireError::UnsupportedVersion(_) => Self::Unsupported(Box::new(value)),
+            _ => Self::Parse(Box::new(value)),
+        }
+    }
+}
+
+macro_rules! impl_from_boxed {
+    ($variant:ident => $($ty:ty),*) => {
+        $(
+            impl From<$ty> for Error {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(Box::new(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_boxed!(Parse =>
+    AddrParseError,
+    ParseFamilyPreferenceError,
+    ParseIpFamilyError,
+    ParsePrefixError,
+    ParsePtrError,
+    ParseSocketAddrError,
+    TextError,
+    WrongLength
+);
+impl_from_boxed!(Unsupported => MappingError, MulticastError, Nat64PrefixError);
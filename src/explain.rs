@@ -0,0 +1,359 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::{
+    multicast, ptr, sixto4_v4, IpFamily, IpFamilyExt, IpPrefix, MulticastScope, Nat64Prefix, Teredo,
+};
+
+/// An entry of the IANA IPv4 or IPv6 special-purpose address registry.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SpecialPurpose {
+    pub prefix: IpPrefix,
+    pub name: &'static str,
+    pub rfc: &'static str,
+}
+
+/// An IPv4 address carried inside an IPv6 address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EmbeddedV4 {
+    /// `ipv4-mapped`, `ipv4-compatible`, `nat64`, `6to4`, `teredo-server`
+    /// or `teredo-client`.
+    pub kind: &'static str,
+    pub addr: Ipv4Addr,
+}
+
+/// Everything the crate knows about an address, see [`explain`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Explanation {
+    pub addr: IpAddr,
+    /// The standard text form, with IPv4-mapped addresses unmapped.
+    pub canonical: String,
+    /// Every matching class, such as `loopback`, `private` or `global`.
+    pub classes: Vec<&'static str>,
+    /// The most specific special-purpose registry entry.
+    pub special_purpose: Option<SpecialPurpose>,
+    /// `host`, `link`, `private` or `global`, or the multicast scope.
+    pub scope: &'static str,
+    /// The reverse DNS name under `in-addr.arpa` or `ip6.arpa`.
+    pub ptr: String,
+    pub embedded_v4: Vec<EmbeddedV4>,
+}
+
+const V4_SPECIAL_PURPOSE: &[(&str, &str, &str)] = &[
+    ("0.0.0.0/8", "This network", "RFC 791"),
+    ("0.0.0.0/32", "This host on this network", "RFC 1122"),
+    ("10.0.0.0/8", "Private-Use", "RFC 1918"),
+    ("100.64.0.0/10", "Shared Address Space", "RFC 6598"),
+    ("127.0.0.0/8", "Loopback", "RFC 1122"),
+    ("169.254.0.0/16", "Link Local", "RFC 3927"),
+    ("172.16.0.0/12", "Private-Use", "RFC 1918"),
+    ("192.0.0.0/24", "IETF Protocol Assignments", "RFC 6890"),
+    ("192.0.0.0/29", "IPv4 Service Continuity Prefix", "RFC 7335"),
+    ("192.0.0.8/32", "IPv4 dummy address", "RFC 7600"),
+    ("192.0.0.9/32", "Port Control Protocol Anycast", "RFC 7723"),
+    (
+        "192.0.0.10/32",
+        "Traversal Using Relays around NAT Anycast",
+        "RFC 8155",
+    ),
+    ("192.0.0.170/32", "NAT64/DNS64 Discovery", "RFC 8880"),
+    ("192.0.0.171/32", "NAT64/DNS64 Discovery", "RFC 8880"),
+    ("192.0.2.0/24", "Documentation (TEST-NET-1)", "RFC 5737"),
+    ("192.31.196.0/24", "AS112-v4", "RFC 7535"),
+    ("192.52.193.0/24", "AMT", "RFC 7450"),
+    (
+        "192.88.99.0/24",
+        "Deprecated (6to4 Relay Anycast)",
+        "RFC 7526",
+    ),
+    ("192.168.0.0/16", "Private-Use", "RFC 1918"),
+    (
+        "192.175.48.0/24",
+        "Direct Delegation AS112 Service",
+        "RFC 7534",
+    ),
+    ("198.18.0.0/15", "Benchmarking", "RFC 2544"),
+    ("198.51.100.0/24", "Documentation (TEST-NET-2)", "RFC 5737"),
+    ("203.0.113.0/24", "Documentation (TEST-NET-3)", "RFC 5737"),
+    ("240.0.0.0/4", "Reserved", "RFC 1112"),
+    ("255.255.255.255/32", "Limited Broadcast", "RFC 919"),
+];
+
+const V6_SPECIAL_PURPOSE: &[(&str, &str, &str)] = &[
+    ("::1/128", "Loopback Address", "RFC 4291"),
+    ("::/128", "Unspecified Address", "RFC 4291"),
+    ("::ffff:0:0/96", "IPv4-mapped Address", "RFC 4291"),
+    ("64:ff9b::/96", "IPv4-IPv6 Translation", "RFC 6052"),
+    ("64:ff9b:1::/48", "IPv4-IPv6 Translation", "RFC 8215"),
+    ("100::/64", "Discard-Only Address Block", "RFC 6666"),
+    ("2001::/23", "IETF Protocol Assignments", "RFC 2928"),
+    ("2001::/32", "TEREDO", "RFC 4380"),
+    ("2001:1::1/128", "Port Control Protocol Anycast", "RFC 7723"),
+    (
+        "2001:1::2/128",
+        "Traversal Using Relays around NAT Anycast",
+        "RFC 8155",
+    ),
+    ("2001:2::/48", "Benchmarking", "RFC 5180"),
+    ("2001:3::/32", "AMT", "RFC 7450"),
+    ("2001:4:112::/48", "AS112-v6", "RFC 7535"),
+    ("2001:20::/28", "ORCHIDv2", "RFC 7343"),
+    ("2001:db8::/32", "Documentation", "RFC 3849"),
+    ("2002::/16", "6to4", "RFC 3056"),
+    (
+        "2620:4f:8000::/48",
+        "Direct Delegation AS112 Service",
+        "RFC 7534",
+    ),
+    ("3fff::/20", "Documentation", "RFC 9637"),
+    ("5f00::/16", "Segment Routing (SRv6) SIDs", "RFC 9602"),
+    ("fc00::/7", "Unique-Local", "RFC 4193"),
+    ("fe80::/10", "Link-Local Unicast", "RFC 4291"),
+];
+
+fn special_purpose(addr: IpAddr) -> Option<SpecialPurpose> {
+    let table = match addr.family() {
+        IpFamily::V4 => V4_SPECIAL_PURPOSE,
+        IpFamily::V6 => V6_SPECIAL_PURPOSE,
+    };
+    table
+        .iter()
+        .map(|&(prefix, name, rfc)| SpecialPurpose {
+            prefix: prefix.parse().expect("registry prefixes are valid"),
+            name,
+            rfc,
+        })
+        .filter(|entry| entry.prefix.contains(addr))
+        .max_by_key(|entry| entry.prefix.len())
+}
+
+fn in_prefix(addr: IpAddr, prefix: &str) -> bool {
+    prefix
+        .parse::<IpPrefix>()
+        .is_ok_and(|prefix| prefix.contains(addr))
+}
+
+fn v4_classes(addr: Ipv4Addr, classes: &mut Vec<&'static str>) {
+    let ip = IpAddr::V4(addr);
+    let checks = [
+        (addr.is_unspecified(), "unspecified"),
+        (addr.is_loopback(), "loopback"),
+        (addr.is_private(), "private"),
+        (in_prefix(ip, "100.64.0.0/10"), "shared"),
+        (addr.is_link_local(), "link_local"),
+        (addr.is_multicast(), "multicast"),
+        (addr.is_broadcast(), "broadcast"),
+        (addr.is_documentation(), "documentation"),
+        (in_prefix(ip, "198.18.0.0/15"), "benchmarking"),
+        (
+            in_prefix(ip, "240.0.0.0/4") && !addr.is_broadcast(),
+            "reserved",
+        ),
+    ];
+    classes.extend(checks.into_iter().filter(|(m, _)| *m).map(|(_, c)| c));
+}
+
+fn v6_classes(addr: Ipv6Addr, classes: &mut Vec<&'static str>) {
+    let ip = IpAddr::V6(addr);
+    let checks = [
+        (addr.is_unspecified(), "unspecified"),
+        (addr.is_loopback(), "loopback"),
+        (addr.is_unique_local(), "unique_local"),
+        (addr.is_unicast_link_local(), "link_local"),
+        (addr.is_multicast(), "multicast"),
+        (
+            in_prefix(ip, "2001:db8::/32") || in_prefix(ip, "3fff::/20"),
+            "documentation",
+        ),
+        (in_prefix(ip, "2001:2::/48"), "benchmarking"),
+        (addr.to_ipv4_mapped().is_some(), "ipv4_mapped"),
+    ];
+    classes.extend(checks.into_iter().filter(|(m, _)| *m).map(|(_, c)| c));
+}
+
+fn embedded_v4(addr: Ipv6Addr) -> Vec<EmbeddedV4> {
+    let bits = addr.to_bits();
+    let low = Ipv4Addr::from_bits(bits as u32);
+    let mut found = Vec::new();
+    let mut push = |kind, addr| found.push(EmbeddedV4 { kind, addr });
+    if addr.to_ipv4_mapped().is_some() {
+        push("ipv4-mapped", low);
+    } else if bits >> 32 == 0 && bits > 1 {
+        push("ipv4-compatible", low);
+    }
+    if let Some(v4) = Nat64Prefix::WELL_KNOWN.extract(addr) {
+        push("nat64", v4);
+    }
+    if let Some(v4) = sixto4_v4(addr) {
+        push("6to4", v4);
+    }
+    if let Some(teredo) = Teredo::from_addr(addr) {
+        push("teredo-server", teredo.server);
+        push("teredo-client", *teredo.client.ip());
+    }
+    found
+}
+
+fn scope(addr: IpAddr, classes: &[&str]) -> &'static str {
+    let multicast = match addr {
+        IpAddr::V4(v4) => multicast::v4_scope(v4),
+        IpAddr::V6(v6) => multicast::v6_scope(v6),
+    };
+    if let Some(scope) = multicast {
+        return match scope {
+            MulticastScope::InterfaceLocal => "interface",
+            MulticastScope::LinkLocal => "link",
+            MulticastScope::RealmLocal => "realm",
+            MulticastScope::AdminLocal => "admin",
+            MulticastScope::SiteLocal => "site",
+            MulticastScope::OrganizationLocal => "organization",
+            MulticastScope::Global | MulticastScope::Unassigned(_) => "global",
+        };
+    }
+    let has = |class| classes.contains(&class);
+    if has("loopback") || has("unspecified") {
+        "host"
+    } else if has("link_local") || has("broadcast") {
+        "link"
+    } else if has("private") || has("shared") || has("unique_local") {
+        "private"
+    } else {
+        "global"
+    }
+}
+
+fn ptr(addr: IpAddr) -> String {
+    match addr {
+        IpAddr::V4(addr) => ptr::ptr_name(&addr.octets()),
+        IpAddr::V6(addr) => ptr::ptr_name(&addr.octets()),
+    }
+}
+
+/// Classifies `addr` in every way the crate knows, for debugging tools and
+/// support dumps.
+pub fn explain(addr: IpAddr) -> Explanation {
+    let mut classes = Vec::new();
+    let embedded = match addr {
+        IpAddr::V4(v4) => {
+            v4_classes(v4, &mut classes);
+            Vec::new()
+        }
+        IpAddr::V6(v6) => {
+            v6_classes(v6, &mut classes);
+            embedded_v4(v6)
+        }
+    };
+    let special_purpose = special_purpose(addr);
+    if classes.is_empty() && special_purpose.is_none() {
+        classes.push("global");
+    }
+    // Mapped addresses reach the IPv4 host, so they take its scope.
+    let canonical = addr.to_canonical();
+    let scope = match canonical {
+        IpAddr::V4(v4) if canonical != addr => {
+            let mut v4_classes_of_mapped = Vec::new();
+            v4_classes(v4, &mut v4_classes_of_mapped);
+            scope(canonical, &v4_classes_of_mapped)
+        }
+        _ => scope(addr, &classes),
+    };
+    Explanation {
+        addr,
+        canonical: canonical.to_string(),
+        scope,
+        classes,
+        special_purpose,
+        ptr: ptr(addr),
+        embedded_v4: embedded,
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+    use super::{EmbeddedV4, Explanation, SpecialPurpose};
+    use crate::{IpFamily, IpFamilyExt};
+
+    impl Serialize for SpecialPurpose {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut s = serializer.serialize_struct("SpecialPurpose", 3)?;
+            s.serialize_field("prefix", &self.prefix)?;
+            s.serialize_field("name", self.name)?;
+            s.serialize_field("rfc", self.rfc)?;
+            s.end()
+        }
+    }
+
+    impl Serialize for EmbeddedV4 {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut s = serializer.serialize_struct("EmbeddedV4", 2)?;
+            s.serialize_field("kind", self.kind)?;
+            s.serialize_field("addr", &self.addr)?;
+            s.end()
+        }
+    }
+
+    impl Serialize for Explanation {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let family = match self.addr.family() {
+                IpFamily::V4 => "ipv4",
+                IpFamily::V6 => "ipv6",
+            };
+            let mut s = serializer.serialize_struct("Explanation", 8)?;
+            s.serialize_field("addr", &self.addr)?;
+            s.serialize_field("family", family)?;
+            s.serialize_field("canonical", &self.canonical)?;
+            s.serialize_field("classes", &self.classes)?;
+            s.serialize_field("special_purpose", &self.special_purpose)?;
+            s.serialize_field("scope", self.scope)?;
+            s.serialize_field("ptr", &self.ptr)?;
+            s.serialize_field("embedded_v4", &self.embedded_v4)?;
+            s.end()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope_of(addr: &str) -> &'static str {
+        explain(addr.parse().unwrap()).scope
+    }
+
+    #[test]
+    fn v4_multicast_scope() {
+        assert_eq!(scope_of("224.0.0.1"), "link");
+        assert_eq!(scope_of("239.255.255.250"), "site");
+        assert_eq!(scope_of("239.192.0.1"), "organization");
+        assert_eq!(scope_of("239.1.2.3"), "admin");
+        assert_eq!(scope_of("233.252.0.1"), "global");
+    }
+
+    #[test]
+    fn v6_multicast_scope() {
+        assert_eq!(scope_of("ff01::1"), "interface");
+        assert_eq!(scope_of("ff02::1"), "link");
+        assert_eq!(scope_of("ff05::2"), "site");
+        assert_eq!(scope_of("ff0e::1"), "global");
+    }
+
+    #[test]
+    fn unicast_scope() {
+        assert_eq!(scope_of("127.0.0.1"), "host");
+        assert_eq!(scope_of("::"), "host");
+        assert_eq!(scope_of("169.254.1.1"), "link");
+        assert_eq!(scope_of("fe80::1"), "link");
+        assert_eq!(scope_of("10.0.0.1"), "private");
+        assert_eq!(scope_of("fd00::1"), "private");
+        assert_eq!(scope_of("8.8.8.8"), "global");
+    }
+
+    #[test]
+    fn mapped_addresses_take_the_v4_scope() {
+        let explanation = explain("::ffff:224.0.0.1".parse().unwrap());
+        assert_eq!(explanation.scope, "link");
+        assert_eq!(explanation.canonical, "224.0.0.1");
+        assert_eq!(scope_of("::ffff:127.0.0.1"), "host");
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign},
+    str::FromStr,
+};
+
+use crate::{IpFamily, ParseIpFamilyError};
+
+const fn bit(family: IpFamily) -> u8 {
+    match family {
+        IpFamily::V4 => 0b01,
+        IpFamily::V6 => 0b10,
+    }
+}
+
+/// A set of families, such as the families a service listens on.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FamilySet {
+    bits: u8,
+}
+
+impl FamilySet {
+    pub const NONE: Self = Self { bits: 0 };
+    pub const V4: Self = Self::only(IpFamily::V4);
+    pub const V6: Self = Self::only(IpFamily::V6);
+    pub const BOTH: Self = Self::V4.union(Self::V6);
+
+    pub const fn only(family: IpFamily) -> Self {
+        Self { bits: bit(family) }
+    }
+
+    pub const fn contains(self, family: IpFamily) -> bool {
+        self.bits & bit(family) != 0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    pub const fn len(self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    pub const fn intersection(self, other: Self) -> Self {
+        Self {
+            bits: self.bits & other.bits,
+        }
+    }
+
+    pub fn insert(&mut self, family: IpFamily) {
+        self.bits |= bit(family);
+    }
+
+    pub fn remove(&mut self, family: IpFamily) {
+        self.bits &= !bit(family);
+    }
+
+    /// IPv4 first.
+    pub fn iter(self) -> impl Iterator<Item = IpFamily> {
+        IpFamily::iter().filter(move |&family| self.contains(family))
+    }
+}
+
+impl From<IpFamily> for FamilySet {
+    fn from(value: IpFamily) -> Self {
+        Self::only(value)
+    }
+}
+
+impl BitOr for FamilySet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+impl BitOrAssign for FamilySet {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = self.union(rhs);
+    }
+}
+
+impl BitAnd for FamilySet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        self.intersection(rhs)
+    }
+}
+
+impl BitAndAssign for FamilySet {
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = self.intersection(rhs);
+    }
+}
+
+impl FromIterator<IpFamily> for FamilySet {
+    fn from_iter<I: IntoIterator<Item = IpFamily>>(iter: I) -> Self {
+        let mut set = Self::NONE;
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<IpFamily> for FamilySet {
+    fn extend<I: IntoIterator<Item = IpFamily>>(&mut self, iter: I) {
+        for family in iter {
+            self.insert(family);
+        }
+    }
+}
+
+impl Debug for FamilySet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// `v4`, `v6`, `v4,v6` or `none`.
+impl Display for FamilySet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("none");
+        }
+        for (i, family) in self.iter().enumerate() {
+            if i != 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(match family {
+                IpFamily::V4 => "v4",
+                IpFamily::V6 => "v6",
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Accepts `none`, the empty string, or a comma-separated list of the
+/// spellings [`IpFamily`] accepts, such as `v4,ipv6`.
+impl FromStr for FamilySet {
+    type Err = ParseIpFamilyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() || s.eq_ignore_ascii_case("none") {
+            return Ok(Self::NONE);
+        }
+        s.split(',').map(|family| family.trim().parse()).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FamilySet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FamilySet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_round_trips() {
+        for set in [
+            FamilySet::NONE,
+            FamilySet::V4,
+            FamilySet::V6,
+            FamilySet::BOTH,
+        ] {
+            assert_eq!(set.to_string().parse::<FamilySet>(), Ok(set), "{set}");
+        }
+        assert_eq!(FamilySet::NONE.to_string(), "none");
+        assert_eq!(FamilySet::V4.to_string(), "v4");
+        assert_eq!(FamilySet::V6.to_string(), "v6");
+        assert_eq!(FamilySet::BOTH.to_string(), "v4,v6");
+    }
+
+    #[test]
+    fn parses_lists() {
+        for (s, set) in [
+            ("", FamilySet::NONE),
+            ("  ", FamilySet::NONE),
+            ("None", FamilySet::NONE),
+            ("v4", FamilySet::V4),
+            ("v6", FamilySet::V6),
+            ("v4,v6", FamilySet::BOTH),
+            ("ipv6, inet", FamilySet::BOTH),
+            ("v6,v4", FamilySet::BOTH),
+            ("v4,v4", FamilySet::V4),
+            ("6,ipv6,v6", FamilySet::V6),
+        ] {
+            assert_eq!(s.parse(), Ok(set), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_tokens() {
+        for s in ["v5", "v4,", ",v6", "v4,,v6", "v4;v6", "none,v4", "all"] {
+            assert!(s.parse::<FamilySet>().is_err(), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn debug_lists_the_families() {
+        assert_eq!(format!("{:?}", FamilySet::NONE), "{}");
+        assert_eq!(format!("{:?}", FamilySet::V6), "{IPv6}");
+        assert_eq!(format!("{:?}", FamilySet::BOTH), "{IPv4, IPv6}");
+    }
+}
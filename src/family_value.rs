@@ -0,0 +1,114 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+use crate::{HasFamily, IpFamily};
+
+/// A value whose type depends on the family, e.g. one socket or route of
+/// either family.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum FamilyValue<T4, T6> {
+    V4(T4),
+    V6(T6),
+}
+
+impl<T4, T6> FamilyValue<T4, T6> {
+    pub const fn family(&self) -> IpFamily {
+        match self {
+            Self::V4(_) => IpFamily::V4,
+            Self::V6(_) => IpFamily::V6,
+        }
+    }
+
+    pub fn v4(self) -> Option<T4> {
+        match self {
+            Self::V4(value) => Some(value),
+            Self::V6(_) => None,
+        }
+    }
+
+    pub fn v6(self) -> Option<T6> {
+        match self {
+            Self::V4(_) => None,
+            Self::V6(value) => Some(value),
+        }
+    }
+
+    pub fn map_v4<U>(self, f: impl FnOnce(T4) -> U) -> FamilyValue<U, T6> {
+        match self {
+            Self::V4(value) => FamilyValue::V4(f(value)),
+            Self::V6(value) => FamilyValue::V6(value),
+        }
+    }
+
+    pub fn map_v6<U>(self, f: impl FnOnce(T6) -> U) -> FamilyValue<T4, U> {
+        match self {
+            Self::V4(value) => FamilyValue::V4(value),
+            Self::V6(value) => FamilyValue::V6(f(value)),
+        }
+    }
+
+    pub const fn as_ref(&self) -> FamilyValue<&T4, &T6> {
+        match self {
+            Self::V4(value) => FamilyValue::V4(value),
+            Self::V6(value) => FamilyValue::V6(value),
+        }
+    }
+
+    pub fn as_mut(&mut self) -> FamilyValue<&mut T4, &mut T6> {
+        match self {
+            Self::V4(value) => FamilyValue::V4(value),
+            Self::V6(value) => FamilyValue::V6(value),
+        }
+    }
+}
+
+impl<T> FamilyValue<T, T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::V4(value) | Self::V6(value) => value,
+        }
+    }
+}
+
+impl<T4, T6> HasFamily for FamilyValue<T4, T6> {
+    type Output = IpFamily;
+
+    fn ip_family(&self) -> IpFamily {
+        self.family()
+    }
+}
+
+impl From<IpAddr> for FamilyValue<Ipv4Addr, Ipv6Addr> {
+    fn from(value: IpAddr) -> Self {
+        match value {
+            IpAddr::V4(addr) => Self::V4(addr),
+            IpAddr::V6(addr) => Self::V6(addr),
+        }
+    }
+}
+
+impl From<FamilyValue<Ipv4Addr, Ipv6Addr>> for IpAddr {
+    fn from(value: FamilyValue<Ipv4Addr, Ipv6Addr>) -> Self {
+        match value {
+            FamilyValue::V4(addr) => IpAddr::V4(addr),
+            FamilyValue::V6(addr) => IpAddr::V6(addr),
+        }
+    }
+}
+
+impl From<SocketAddr> for FamilyValue<SocketAddrV4, SocketAddrV6> {
+    fn from(value: SocketAddr) -> Self {
+        match value {
+            SocketAddr::V4(addr) => Self::V4(addr),
+            SocketAddr::V6(addr) => Self::V6(addr),
+        }
+    }
+}
+
+impl From<FamilyValue<SocketAddrV4, SocketAddrV6>> for SocketAddr {
+    fn from(value: FamilyValue<SocketAddrV4, SocketAddrV6>) -> Self {
+        match value {
+            FamilyValue::V4(addr) => SocketAddr::V4(addr),
+            FamilyValue::V6(addr) => SocketAddr::V6(addr),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+};
+
+use crate::{IpFamily, IpFamilyExt, PerFamily};
+
+/// Sends datagrams to targets of both families, using one UDP socket per
+/// family bound to the unspecified address.
+///
+/// Sockets are created on the first send to their family. Failing to create
+/// one is reported for that target and retried on the next send.
+#[derive(Debug, Default)]
+pub struct FanoutSender {
+    sockets: PerFamily<Option<UdpSocket>>,
+}
+
+impl FanoutSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The socket of `family`, if one has been created.
+    pub fn socket(&self, family: IpFamily) -> Option<&UdpSocket> {
+        self.sockets[family].as_ref()
+    }
+
+    pub fn send_to(&mut self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        let family = target.family();
+        let slot = &mut self.sockets[family];
+        let socket = match slot {
+            Some(socket) => socket,
+            None => slot.insert(UdpSocket::bind(family.unspecified_socket(0))?),
+        };
+        socket.send_to(buf, target)
+    }
+
+    /// Sends `buf` to every target and returns the failures, grouped by
+    /// family. A failing target does not stop the remaining sends.
+    pub fn send_all<I>(&mut self, buf: &[u8], targets: I) -> PerFamily<Vec<(SocketAddr, io::Error)>>
+    where
+        I: IntoIterator<Item = SocketAddr>,
+    {
+        let mut errors = PerFamily::<Vec<_>>::default();
+        for target in targets {
+            if let Err(err) = self.send_to(buf, target) {
+                errors[target.family()].push((target, err));
+            }
+        }
+        errors
+    }
+}
@@ -0,0 +1,231 @@
+use std::net::IpAddr;
+
+use crate::{AllowedIps, IpPrefix, ParsePrefixError, TextError, TextErrorKind};
+
+/// The result of importing a feed: every entry that parsed, plus an error
+/// for each one that did not.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct FeedImport {
+    pub set: AllowedIps,
+    pub errors: Vec<TextError>,
+}
+
+// Collects prefixes first so the set is normalized only once.
+#[derive(Default)]
+struct Importer {
+    prefixes: Vec<IpPrefix>,
+    errors: Vec<TextError>,
+}
+
+impl Importer {
+    fn push(&mut self, line: usize, entry: &str) {
+        match parse_entry(entry) {
+            Ok(prefix) => self.prefixes.push(prefix),
+            Err(err) => self.error(line, TextErrorKind::Prefix(err)),
+        }
+    }
+
+    fn error(&mut self, line: usize, kind: TextErrorKind) {
+        self.errors.push(TextError { line, kind });
+    }
+
+    fn finish(self) -> FeedImport {
+        FeedImport {
+            set: self.prefixes.into_iter().collect(),
+            errors: self.errors,
+        }
+    }
+}
+
+/// Like `IpPrefix::from_str`, clearing host bits instead of rejecting them,
+/// as feeds often list `192.0.2.1/24` for the network of a host.
+fn parse_entry(entry: &str) -> Result<IpPrefix, ParsePrefixError> {
+    match entry.parse() {
+        Err(ParsePrefixError::HostBits) => {
+            let (addr, len) = entry.split_once('/').expect("only prefixes have host bits");
+            let addr: IpAddr = addr.parse().expect("address was parsed");
+            Ok(IpPrefix::new(addr, len.parse().expect("length was parsed")))
+        }
+        result => result,
+    }
+}
+
+/// Which field of a CSV feed holds the address or prefix.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum CsvColumn<'a> {
+    /// A 0-based field index. Every line is data.
+    Index(usize),
+    /// The field named in the first line, matched case-insensitively.
+    Header(&'a str),
+}
+
+/// Imports one address or prefix per line. Blank lines and everything
+/// after `#` or `;` are ignored, host bits are cleared.
+pub fn import_lines(text: &str) -> FeedImport {
+    let mut import = Importer::default();
+    for (i, line) in text.lines().enumerate() {
+        let entry = line.split(['#', ';']).next().unwrap_or_default().trim();
+        if !entry.is_empty() {
+            import.push(i + 1, entry);
+        }
+    }
+    import.finish()
+}
+
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().expect("at least one field");
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    fields
+}
+
+/// Imports the addresses or prefixes of one column of a CSV feed. Fields
+/// may be double-quoted, blank lines and lines starting with `#` after
+/// optional whitespace are ignored, host bits are cleared.
+pub fn import_csv(text: &str, column: CsvColumn<'_>) -> FeedImport {
+    let mut import = Importer::default();
+    let mut lines = text.lines().enumerate().filter(|(_, line)| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    });
+    let index = match column {
+        CsvColumn::Index(index) => index,
+        CsvColumn::Header(name) => {
+            let header = lines.next().map(|(i, line)| (i, csv_fields(line)));
+            let found = header.as_ref().and_then(|(_, fields)| {
+                fields
+                    .iter()
+                    .position(|field| field.trim().eq_ignore_ascii_case(name))
+            });
+            match (found, header) {
+                (Some(index), _) => index,
+                (None, header) => {
+                    let line = header.map_or(1, |(i, _)| i + 1);
+                    import.error(line, TextErrorKind::MissingColumn);
+                    return import.finish();
+                }
+            }
+        }
+    };
+    for (i, line) in lines {
+        match csv_fields(line).get(index) {
+            Some(entry) => import.push(i + 1, entry.trim()),
+            None => import.error(i + 1, TextErrorKind::MissingColumn),
+        }
+    }
+    import.finish()
+}
+
+/// Imports a JSON array of address or prefix strings from any `serde`
+/// deserializer, such as `serde_json::Deserializer`. Errors report the
+/// 1-based array position as their line. Host bits are cleared.
+#[cfg(feature = "serde")]
+pub fn import_json<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<FeedImport, D::Error> {
+    use serde::Deserialize;
+
+    let entries = <Vec<std::borrow::Cow<'de, str>>>::deserialize(deserializer)?;
+    let mut import = Importer::default();
+    for (i, entry) in entries.iter().enumerate() {
+        import.push(i + 1, entry.trim());
+    }
+    Ok(import.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefixes(import: &FeedImport) -> Vec<String> {
+        import
+            .set
+            .prefixes()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn error_lines(import: &FeedImport) -> Vec<usize> {
+        import.errors.iter().map(|err| err.line).collect()
+    }
+
+    #[test]
+    fn lines_skip_comments_and_blank_lines() {
+        let import = import_lines(
+            "# blocklist\r\n\r\n192.0.2.0/24 ; documentation\r\n  2001:db8::/32  # docs\r\n\t\n198.51.100.7\r\n",
+        );
+        assert_eq!(
+            prefixes(&import),
+            ["192.0.2.0/24", "198.51.100.7/32", "2001:db8::/32"]
+        );
+        assert!(import.errors.is_empty());
+    }
+
+    #[test]
+    fn lines_clear_host_bits() {
+        let import = import_lines("10.0.0.1/8\n2001:db8::1/32\n");
+        assert_eq!(prefixes(&import), ["10.0.0.0/8", "2001:db8::/32"]);
+        assert!(import.errors.is_empty());
+    }
+
+    #[test]
+    fn lines_report_bad_entries_and_keep_the_rest() {
+        let import = import_lines("192.0.2.0/24\nexample.com\n10.0.0.0/33\n::1\n");
+        assert_eq!(prefixes(&import), ["192.0.2.0/24", "::1/128"]);
+        assert_eq!(error_lines(&import), [2, 3]);
+        assert_eq!(
+            import.errors[1].kind,
+            TextErrorKind::Prefix(ParsePrefixError::Len)
+        );
+    }
+
+    #[test]
+    fn csv_by_header_with_quotes_and_comments() {
+        let text = "# exported\r\nname,\"Network\"\r\n  # indented comment\r\n\"office, main\",10.1.0.0/16\r\n\r\nlab,\"2001:db8:1::1/48\"\r\n";
+        let import = import_csv(text, CsvColumn::Header("network"));
+        assert_eq!(prefixes(&import), ["10.1.0.0/16", "2001:db8:1::/48"]);
+        assert!(import.errors.is_empty());
+    }
+
+    #[test]
+    fn csv_by_index_reports_short_lines() {
+        let import = import_csv("a,192.0.2.1\nb\nc,fd00::/8\n", CsvColumn::Index(1));
+        assert_eq!(prefixes(&import), ["192.0.2.1/32", "fd00::/8"]);
+        assert_eq!(error_lines(&import), [2]);
+        assert_eq!(import.errors[0].kind, TextErrorKind::MissingColumn);
+    }
+
+    #[test]
+    fn csv_without_the_named_column() {
+        let import = import_csv("name,addr\nx,192.0.2.1\n", CsvColumn::Header("prefix"));
+        assert!(import.set.is_empty());
+        assert_eq!(error_lines(&import), [1]);
+        let import = import_csv("", CsvColumn::Header("prefix"));
+        assert_eq!(error_lines(&import), [1]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_array() {
+        let mut deserializer =
+            serde_json::Deserializer::from_str(r#"[" 192.0.2.1/24", "fd00::/8", "nope"]"#);
+        let import = import_json(&mut deserializer).unwrap();
+        assert_eq!(prefixes(&import), ["192.0.2.0/24", "fd00::/8"]);
+        assert_eq!(error_lines(&import), [3]);
+        let mut deserializer = serde_json::Deserializer::from_str("{}");
+        assert!(import_json(&mut deserializer).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Well-known example addresses for doctests and test suites.
+//!
+//! Unicast samples come from the documentation ranges (RFC 5737, RFC 3849),
+//! which are guaranteed never to be routed.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+
+use crate::{AnyIpFamily, IpFamily, IpFamilyAddr, IpFamilySocketAddr, IpPrefix};
+
+/// `192.0.2.0/24`, TEST-NET-1.
+pub const V4_TEST_NET_1: Ipv4Addr = Ipv4Addr::new(192, 0, 2, 0);
+/// `198.51.100.0/24`, TEST-NET-2.
+pub const V4_TEST_NET_2: Ipv4Addr = Ipv4Addr::new(198, 51, 100, 0);
+/// `203.0.113.0/24`, TEST-NET-3.
+pub const V4_TEST_NET_3: Ipv4Addr = Ipv4Addr::new(203, 0, 113, 0);
+/// `2001:db8::/32`.
+pub const V6_DOCUMENTATION: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0);
+
+pub const V4_ADDR: Ipv4Addr = Ipv4Addr::new(192, 0, 2, 1);
+pub const V4_ADDR_2: Ipv4Addr = Ipv4Addr::new(198, 51, 100, 1);
+pub const V4_ADDR_3: Ipv4Addr = Ipv4Addr::new(203, 0, 113, 1);
+pub const V6_ADDR: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+pub const V6_ADDR_2: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2);
+pub const V6_ADDR_3: Ipv6Addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 3);
+
+pub const V4_SOCKET_ADDR: SocketAddrV4 = SocketAddrV4::new(V4_ADDR, 8080);
+pub const V6_SOCKET_ADDR: SocketAddrV6 = SocketAddrV6::new(V6_ADDR, 8080, 0, 0);
+
+/// `224.0.0.1`, all hosts on this subnet.
+pub const V4_MULTICAST: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 1);
+/// `ff02::1`, all nodes on the link.
+pub const V6_MULTICAST: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+
+/// `169.254.0.1`.
+pub const V4_LINK_LOCAL: Ipv4Addr = Ipv4Addr::new(169, 254, 0, 1);
+/// `fe80::1`.
+pub const V6_LINK_LOCAL: Ipv6Addr = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+
+fn pick<F: AnyIpFamily>(v4: Ipv4Addr, v6: Ipv6Addr) -> F::Addr {
+    match F::FAMILY {
+        IpFamily::V4 => F::Addr::from_byte_slice(&v4.octets()),
+        IpFamily::V6 => F::Addr::from_byte_slice(&v6.octets()),
+    }
+    .expect("address matches the family")
+}
+
+/// `192.0.2.host` or `2001:db8::host`.
+pub fn documentation<F: AnyIpFamily>(host: u8) -> F::Addr {
+    pick::<F>(
+        Ipv4Addr::new(192, 0, 2, host),
+        Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, host.into()),
+    )
+}
+
+pub fn socket_addr<F: AnyIpFamily>(host: u8, port: u16) -> F::SocketAddr {
+    F::SocketAddr::new(documentation::<F>(host), port)
+}
+
+pub fn multicast<F: AnyIpFamily>() -> F::Addr {
+    pick::<F>(V4_MULTICAST, V6_MULTICAST)
+}
+
+pub fn link_local<F: AnyIpFamily>() -> F::Addr {
+    pick::<F>(V4_LINK_LOCAL, V6_LINK_LOCAL)
+}
+
+const V4_TEST_NETS: [Ipv4Addr; 3] = [V4_TEST_NET_1, V4_TEST_NET_2, V4_TEST_NET_3];
+const EXAMPLE_PORTS: [u16; 9] = [443, 80, 53, 22, 8080, 25, 123, 993, 5432];
+const V6_EXAMPLE_SUBNETS: u32 = 16;
+
+fn example<F: AnyIpFamily>(i: u32) -> Option<F::Addr> {
+    match F::FAMILY {
+        IpFamily::V4 => {
+            let host = i / 3 + 1;
+            (host < 255).then(|| {
+                let net = V4_TEST_NETS[(i % 3) as usize].to_bits();
+                pick::<F>(Ipv4Addr::from_bits(net | host), Ipv6Addr::UNSPECIFIED)
+            })
+        }
+        IpFamily::V6 => {
+            let host = i / V6_EXAMPLE_SUBNETS + 1;
+            (host <= 0xffff).then(|| {
+                let subnet = (i % V6_EXAMPLE_SUBNETS) as u16;
+                let addr = Ipv6Addr::new(0x2001, 0xdb8, subnet, 0, 0, 0, 0, host as u16);
+                pick::<F>(Ipv4Addr::UNSPECIFIED, addr)
+            })
+        }
+    }
+}
+
+/// Distinct documentation addresses, alternating between networks:
+/// `192.0.2.1`, `198.51.100.1`, `203.0.113.1`, `192.0.2.2`, ... or
+/// `2001:db8::1`, `2001:db8:1::1`, ... `2001:db8:f::1`, `2001:db8::2`, ...
+pub fn examples<F: AnyIpFamily>() -> impl Iterator<Item = F::Addr> {
+    (0..).map_while(example::<F>)
+}
+
+/// [`examples`] paired with common service ports.
+pub fn example_socket_addrs<F: AnyIpFamily>() -> impl Iterator<Item = F::SocketAddr> {
+    examples::<F>()
+        .zip(EXAMPLE_PORTS.into_iter().cycle())
+        .map(|(addr, port)| F::SocketAddr::new(addr, port))
+}
+
+/// The three IPv4 documentation /24s followed by their /26s, or the /48s of
+/// `2001:db8::/32`.
+pub fn example_subnets<F: AnyIpFamily>() -> impl Iterator<Item = IpPrefix> {
+    let v4 =
+        V4_TEST_NETS
+            .into_iter()
+            .map(|net| (net, 24))
+            .chain(V4_TEST_NETS.into_iter().flat_map(|net| {
+                (0..4).map(move |i| (Ipv4Addr::from_bits(net.to_bits() | i << 6), 26))
+            }))
+            .map(|(net, len)| IpPrefix::new(IpAddr::V4(net), len));
+    let v6 = (0..=0xffff).map(|subnet| {
+        let net = Ipv6Addr::new(0x2001, 0xdb8, subnet, 0, 0, 0, 0, 0);
+        IpPrefix::new(IpAddr::V6(net), 48)
+    });
+    let (v4, v6) = match F::FAMILY {
+        IpFamily::V4 => (Some(v4), None),
+        IpFamily::V6 => (None, Some(v6)),
+    };
+    v4.into_iter().flatten().chain(v6.into_iter().flatten())
+}
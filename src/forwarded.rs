@@ -0,0 +1,147 @@
+use std::net::IpAddr;
+
+use crate::AllowedIps;
+
+/// Coarse classification of a resolved client address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ClientClass {
+    Public,
+    /// Carrier-grade NAT shared address space, `100.64.0.0/10`.
+    Cgnat,
+    /// RFC 1918 IPv4 or unique local IPv6 addresses.
+    Private,
+    /// Loopback, link-local or unspecified addresses.
+    Local,
+}
+
+impl ClientClass {
+    pub fn of(addr: IpAddr) -> Self {
+        match addr.to_canonical() {
+            IpAddr::V4(addr) => {
+                let [a, b, ..] = addr.octets();
+                if addr.is_loopback() || addr.is_link_local() || addr.is_unspecified() {
+                    Self::Local
+                } else if a == 100 && b & 0xc0 == 64 {
+                    Self::Cgnat
+                } else if addr.is_private() {
+                    Self::Private
+                } else {
+                    Self::Public
+                }
+            }
+            IpAddr::V6(addr) => {
+                if addr.is_loopback() || addr.is_unicast_link_local() || addr.is_unspecified() {
+                    Self::Local
+                } else if addr.is_unique_local() {
+                    Self::Private
+                } else {
+                    Self::Public
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ResolvedClient {
+    /// Canonical address, IPv4-mapped addresses are unmapped.
+    pub addr: IpAddr,
+    pub class: ClientClass,
+}
+
+/// Finds the real client behind a chain of trusted proxies.
+///
+/// `hops` are the addresses from `X-Forwarded-For` or `Forwarded`, client
+/// first, and `peer` is the address of the socket. The chain is walked from
+/// the peer towards the client and the first address not in `trusted` is
+/// returned. If every hop is trusted the leftmost one is the client.
+pub fn resolve_client<I>(peer: IpAddr, hops: I, trusted: &AllowedIps) -> ResolvedClient
+where
+    I: IntoIterator<Item = IpAddr>,
+    I::IntoIter: DoubleEndedIterator,
+{
+    let mut client = peer.to_canonical();
+    if trusted.contains(client) {
+        for hop in hops.into_iter().rev() {
+            client = hop.to_canonical();
+            if !trusted.contains(client) {
+                break;
+            }
+        }
+    }
+    ResolvedClient {
+        addr: client,
+        class: ClientClass::of(client),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn ips(list: &[&str]) -> Vec<IpAddr> {
+        list.iter().copied().map(ip).collect()
+    }
+
+    fn trusted() -> AllowedIps {
+        "10.0.0.0/8, fd00::/8".parse().unwrap()
+    }
+
+    #[test]
+    fn untrusted_peer_is_the_client() {
+        // A direct client can put anything in the header.
+        let client = resolve_client(ip("198.51.100.7"), ips(&["192.0.2.1"]), &trusted());
+        assert_eq!(client.addr, ip("198.51.100.7"));
+        assert_eq!(client.class, ClientClass::Public);
+    }
+
+    #[test]
+    fn walks_trusted_hops_from_the_peer() {
+        let hops = ips(&["192.0.2.1", "203.0.113.9", "10.0.0.2", "fd00::1"]);
+        let client = resolve_client(ip("10.0.0.1"), hops, &trusted());
+        assert_eq!(client.addr, ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn all_trusted_chain_yields_the_leftmost_hop() {
+        let hops = ips(&["10.1.1.1", "fd00::2", "10.0.0.2"]);
+        let client = resolve_client(ip("10.0.0.1"), hops, &trusted());
+        assert_eq!(client.addr, ip("10.1.1.1"));
+        assert_eq!(client.class, ClientClass::Private);
+        // Without hops the trusted peer itself is the client.
+        let client = resolve_client(ip("10.0.0.1"), [], &trusted());
+        assert_eq!(client.addr, ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn mapped_addresses_are_unmapped() {
+        let hops = ips(&["::ffff:192.0.2.1", "::ffff:10.0.0.2"]);
+        let client = resolve_client(ip("::ffff:10.0.0.1"), hops, &trusted());
+        assert_eq!(client.addr, ip("192.0.2.1"));
+    }
+
+    #[test]
+    fn classifies_addresses() {
+        for (addr, class) in [
+            ("192.0.2.1", ClientClass::Public),
+            ("2001:db8::1", ClientClass::Public),
+            ("100.64.0.1", ClientClass::Cgnat),
+            ("100.127.255.255", ClientClass::Cgnat),
+            ("100.128.0.1", ClientClass::Public),
+            ("172.16.0.1", ClientClass::Private),
+            ("fd12::1", ClientClass::Private),
+            ("::ffff:192.168.1.1", ClientClass::Private),
+            ("127.0.0.1", ClientClass::Local),
+            ("169.254.1.1", ClientClass::Local),
+            ("fe80::1", ClientClass::Local),
+            ("::", ClientClass::Local),
+            ("::1", ClientClass::Local),
+        ] {
+            assert_eq!(ClientClass::of(ip(addr)), class, "{addr}");
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{arith, AnyIpFamily, IpFamily, IpFamilyAddr, IpFamilySocketAddr, Network};
+
+/// Address classes [`AddrGenerator`] can draw from.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AddrBucket {
+    Any,
+    Global,
+    Private,
+    Loopback,
+    LinkLocal,
+    Multicast,
+    Documentation,
+}
+
+type Prefix = (u128, u8);
+
+const V4_PRIVATE: &[Prefix] = &[(0x0a00_0000, 8), (0xac10_0000, 12), (0xc0a8_0000, 16)];
+const V4_DOCUMENTATION: &[Prefix] = &[(0xc000_0200, 24), (0xc633_6400, 24), (0xcb00_7100, 24)];
+const V4_NOT_GLOBAL: &[Prefix] = &[
+    (0x0000_0000, 8),
+    (0x0a00_0000, 8),
+    (0x6440_0000, 10),
+    (0x7f00_0000, 8),
+    (0xa9fe_0000, 16),
+    (0xac10_0000, 12),
+    (0xc000_0000, 24),
+    (0xc000_0200, 24),
+    (0xc058_6300, 24),
+    (0xc0a8_0000, 16),
+    (0xc612_0000, 15),
+    (0xc633_6400, 24),
+    (0xcb00_7100, 24),
+    (0xe000_0000, 3),
+];
+
+const V6_DOCUMENTATION: &[Prefix] = &[(0x2001_0db8 << 96, 32), (0x3fff << 112, 20)];
+const V6_NOT_GLOBAL: &[Prefix] = &[
+    (0x2001 << 112, 23),
+    (0x2001_0db8 << 96, 32),
+    (0x2002 << 112, 16),
+    (0x3fff << 112, 20),
+];
+
+fn prefixes(family: IpFamily, bucket: AddrBucket) -> &'static [Prefix] {
+    match (family, bucket) {
+        (_, AddrBucket::Any) => &[(0, 0)],
+        (IpFamily::V4, AddrBucket::Global) => &[(0, 0)],
+        (IpFamily::V4, AddrBucket::Private) => V4_PRIVATE,
+        (IpFamily::V4, AddrBucket::Loopback) => &[(0x7f00_0000, 8)],
+        (IpFamily::V4, AddrBucket::LinkLocal) => &[(0xa9fe_0000, 16)],
+        (IpFamily::V4, AddrBucket::Multicast) => &[(0xe000_0000, 4)],
+        (IpFamily::V4, AddrBucket::Documentation) => V4_DOCUMENTATION,
+        (IpFamily::V6, AddrBucket::Global) => &[(0x2000 << 112, 3)],
+        (IpFamily::V6, AddrBucket::Private) => &[(0xfc00 << 112, 7)],
+        (IpFamily::V6, AddrBucket::Loopback) => &[(1, 128)],
+        (IpFamily::V6, AddrBucket::LinkLocal) => &[(0xfe80 << 112, 64)],
+        (IpFamily::V6, AddrBucket::Multicast) => &[(0xff00 << 112, 8)],
+        (IpFamily::V6, AddrBucket::Documentation) => V6_DOCUMENTATION,
+    }
+}
+
+fn excluded(family: IpFamily, bucket: AddrBucket) -> &'static [Prefix] {
+    match (family, bucket) {
+        (IpFamily::V4, AddrBucket::Global) => V4_NOT_GLOBAL,
+        (IpFamily::V6, AddrBucket::Global) => V6_NOT_GLOBAL,
+        _ => &[],
+    }
+}
+
+fn host_mask(width: u32, len: u8) -> u128 {
+    let host_bits = width - u32::from(len);
+    u128::MAX.checked_shr(128 - host_bits).unwrap_or(0)
+}
+
+fn in_prefix(bits: u128, width: u32, (base, len): Prefix) -> bool {
+    let mask = host_mask(width, len);
+    bits & !mask == base & !mask
+}
+
+/// Deterministic, seeded generator of addresses per family and
+/// [`AddrBucket`], for reproducible benchmarks and golden tests.
+///
+/// The same seed always yields the same sequence on every platform.
+#[derive(Clone, Debug)]
+pub struct AddrGenerator {
+    state: u64,
+}
+
+impl AddrGenerator {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    // SplitMix64
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_u128(&mut self) -> u128 {
+        (u128::from(self.next_u64()) << 64) | u128::from(self.next_u64())
+    }
+
+    pub fn addr<F: AnyIpFamily>(&mut self) -> F::Addr {
+        self.addr_in::<F>(AddrBucket::Any)
+    }
+
+    pub fn addr_in<F: AnyIpFamily>(&mut self, bucket: AddrBucket) -> F::Addr {
+        let width = F::Addr::BYTES as u32 * 8;
+        let candidates = prefixes(F::FAMILY, bucket);
+        let bits = loop {
+            let (base, len) = candidates[self.next_u64() as usize % candidates.len()];
+            let mask = host_mask(width, len);
+            let bits = (base & !mask) | (self.next_u128() & mask);
+            if !excluded(F::FAMILY, bucket)
+                .iter()
+                .any(|&prefix| in_prefix(bits, width, prefix))
+            {
+                break bits;
+            }
+        };
+        F::Addr::from_byte_slice(&bits.to_be_bytes()[16 - F::Addr::BYTES..])
+            .expect("generated address has the family's length")
+    }
+
+    /// An address of `network`, including its first and last address.
+    pub fn addr_in_network<F: AnyIpFamily>(&mut self, network: &Network<F>) -> F::Addr {
+        let mask = host_mask(F::Addr::BYTES as u32 * 8, network.len());
+        let bits = arith::to_u128(network.network()) | (self.next_u128() & mask);
+        arith::from_u128(bits).expect("generated address has the family's length")
+    }
+
+    pub fn socket_addr<F: AnyIpFamily>(&mut self) -> F::SocketAddr {
+        self.socket_addr_in::<F>(AddrBucket::Any)
+    }
+
+    /// Ports are drawn from `1..=65535`.
+    pub fn socket_addr_in<F: AnyIpFamily>(&mut self, bucket: AddrBucket) -> F::SocketAddr {
+        let addr = self.addr_in::<F>(bucket);
+        let port = (self.next_u64() % u64::from(u16::MAX)) as u16 + 1;
+        F::SocketAddr::new(addr, port)
+    }
+
+    pub fn addrs<F: AnyIpFamily>(
+        &mut self,
+        bucket: AddrBucket,
+    ) -> impl Iterator<Item = F::Addr> + '_ {
+        std::iter::repeat_with(move || self.addr_in::<F>(bucket))
+    }
+
+    pub fn socket_addrs<F: AnyIpFamily>(
+        &mut self,
+        bucket: AddrBucket,
+    ) -> impl Iterator<Item = F::SocketAddr> + '_ {
+        std::iter::repeat_with(move || self.socket_addr_in::<F>(bucket))
+    }
+}
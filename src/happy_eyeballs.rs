@@ -0,0 +1,580 @@
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{FamilyCounters, FamilyPreference, IpFamily, IpFamilyExt};
+
+/// Delay between starting connection attempts recommended by RFC 8305.
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Reorders `addrs` to alternate between the families, starting with the
+/// family of the first address and otherwise keeping the given order.
+pub fn interleave_families<I>(addrs: I) -> Vec<SocketAddr>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let mut addrs = addrs.into_iter().peekable();
+    let Some(first) = addrs.peek().map(|addr| addr.family()) else {
+        return Vec::new();
+    };
+    let (mut preferred, mut other): (Vec<_>, Vec<_>) =
+        addrs.partition(|addr| addr.family() == first);
+    preferred.reverse();
+    other.reverse();
+    let mut interleaved = Vec::with_capacity(preferred.len() + other.len());
+    while !preferred.is_empty() || !other.is_empty() {
+        interleaved.extend(preferred.pop());
+        interleaved.extend(other.pop());
+    }
+    interleaved
+}
+
+/// The address parameters of an SVCB or HTTPS record (RFC 9460).
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ServiceHints {
+    /// `SvcPriority`, lower first. Alias-mode records, priority 0, are
+    /// ignored.
+    pub priority: u16,
+    /// The `port` parameter, overriding the default port.
+    pub port: Option<u16>,
+    pub ipv4_hint: Vec<Ipv4Addr>,
+    pub ipv6_hint: Vec<Ipv6Addr>,
+}
+
+/// Builds a dial list from SVCB/HTTPS records and A/AAAA answers, ready for
+/// [`connect_happy_eyeballs`].
+///
+/// Each service record contributes the resolved addresses with its port.
+/// For a family without A or AAAA answers the record's hints stand in, as
+/// RFC 9460 §7.3 allows until the answers arrive. Without service records
+/// the resolved addresses get `default_port`. Families `preference` refuses
+/// are dropped and duplicates are removed.
+///
+/// Records are grouped by priority and each group is
+/// [interleaved](interleave_families) on its own, starting with the
+/// preferred family, so every endpoint of a group comes before those of
+/// lower priority groups.
+pub fn merge_service_hints(
+    records: &[ServiceHints],
+    resolved: &[IpAddr],
+    default_port: u16,
+    preference: FamilyPreference,
+) -> Vec<SocketAddr> {
+    let mut services: Vec<_> = records
+        .iter()
+        .filter(|record| record.priority != 0)
+        .collect();
+    // Stable, so records of equal priority keep their order.
+    services.sort_by_key(|record| record.priority);
+    let mut dial_list = Vec::new();
+    if services.is_empty() {
+        let mut group = Vec::new();
+        for family in preference.families() {
+            for &ip in resolved.iter().filter(|addr| family.matches(*addr)) {
+                push_new(&mut group, &dial_list, SocketAddr::new(ip, default_port));
+            }
+        }
+        dial_list.extend(interleave_families(group));
+        return dial_list;
+    }
+    for group_records in services.chunk_by(|a, b| a.priority == b.priority) {
+        let mut group = Vec::new();
+        for family in preference.families() {
+            let answers: Vec<IpAddr> = resolved
+                .iter()
+                .copied()
+                .filter(|addr| family.matches(addr))
+                .collect();
+            for record in group_records {
+                let port = record.port.unwrap_or(default_port);
+                let ips: Vec<IpAddr> = match (answers.is_empty(), family) {
+                    (false, _) => answers.clone(),
+                    (true, IpFamily::V4) => record.ipv4_hint.iter().map(|&ip| ip.into()).collect(),
+                    (true, IpFamily::V6) => record.ipv6_hint.iter().map(|&ip| ip.into()).collect(),
+                };
+                for ip in ips {
+                    push_new(&mut group, &dial_list, SocketAddr::new(ip, port));
+                }
+            }
+        }
+        dial_list.extend(interleave_families(group));
+    }
+    dial_list
+}
+
+fn push_new(group: &mut Vec<SocketAddr>, earlier: &[SocketAddr], addr: SocketAddr) {
+    if !group.contains(&addr) && !earlier.contains(&addr) {
+        group.push(addr);
+    }
+}
+
+fn no_addrs() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "no addresses to connect to")
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "connection attempts timed out")
+}
+
+/// Connects to the first address that answers, starting attempts
+/// [`CONNECTION_ATTEMPT_DELAY`] apart in [`interleave_families`] order, or
+/// immediately after the previous attempt failed.
+///
+/// Fails after `timeout` in total, or with the last error once every
+/// attempt failed. Attempts still in flight finish on their own threads and
+/// their streams are dropped.
+pub fn connect_happy_eyeballs<I>(addrs: I, timeout: Duration) -> io::Result<(TcpStream, IpFamily)>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    connect(addrs, timeout, None)
+}
+
+/// Like [`connect_happy_eyeballs`], recording every attempt in `counters`.
+///
+/// Attempts that are still in flight when another one wins or the timeout
+/// passes count as attempts only.
+pub fn connect_happy_eyeballs_with_metrics<I>(
+    addrs: I,
+    timeout: Duration,
+    counters: &FamilyCounters,
+) -> io::Result<(TcpStream, IpFamily)>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    connect(addrs, timeout, Some(counters))
+}
+
+fn connect<I>(
+    addrs: I,
+    timeout: Duration,
+    counters: Option<&FamilyCounters>,
+) -> io::Result<(TcpStream, IpFamily)>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let deadline = Instant::now() + timeout;
+    let mut pending = interleave_families(addrs).into_iter();
+    let (sender, receiver) = mpsc::channel();
+    let mut in_flight = 0;
+    let mut last_err = None;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(timed_out());
+        }
+        if let Some(addr) = pending.next() {
+            if let Some(counters) = counters {
+                counters.record_attempt(addr.family());
+            }
+            let sender = sender.clone();
+            let remaining = deadline - now;
+            thread::spawn(move || {
+                let _ = sender.send((addr, TcpStream::connect_timeout(&addr, remaining), now));
+            });
+            in_flight += 1;
+        }
+        if in_flight == 0 {
+            return Err(last_err.unwrap_or_else(no_addrs));
+        }
+        let wait_until = match pending.len() {
+            0 => deadline,
+            _ => deadline.min(now + CONNECTION_ATTEMPT_DELAY),
+        };
+        match receiver.recv_timeout(wait_until.saturating_duration_since(Instant::now())) {
+            Ok((addr, result, started)) => {
+                if let Some(counters) = counters {
+                    record(counters, addr, result.is_ok(), started.elapsed());
+                }
+                match result {
+                    Ok(stream) => return Ok((stream, addr.family())),
+                    Err(err) => {
+                        in_flight -= 1;
+                        last_err = Some(err);
+                    }
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => unreachable!("sender is still held"),
+        }
+    }
+}
+
+fn record(counters: &FamilyCounters, addr: SocketAddr, ok: bool, latency: Duration) {
+    if ok {
+        counters.record_success(addr.family(), latency);
+    } else {
+        counters.record_failure(addr.family(), latency);
+    }
+}
+
+/// Like [`connect_happy_eyeballs`], with `tokio` streams. Attempts still in
+/// flight are aborted once one succeeds.
+#[cfg(feature = "tokio")]
+pub async fn connect_happy_eyeballs_tokio<I>(
+    addrs: I,
+    timeout: Duration,
+) -> io::Result<(tokio::net::TcpStream, IpFamily)>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    connect_tokio(addrs, timeout, None).await
+}
+
+/// Like [`connect_happy_eyeballs_tokio`], recording every attempt in
+/// `counters` as [`connect_happy_eyeballs_with_metrics`] does.
+#[cfg(feature = "tokio")]
+pub async fn connect_happy_eyeballs_tokio_with_metrics<I>(
+    addrs: I,
+    timeout: Duration,
+    counters: &FamilyCounters,
+) -> io::Result<(tokio::net::TcpStream, IpFamily)>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    connect_tokio(addrs, timeout, Some(counters)).await
+}
+
+#[cfg(feature = "tokio")]
+async fn connect_tokio<I>(
+    addrs: I,
+    timeout: Duration,
+    counters: Option<&FamilyCounters>,
+) -> io::Result<(tokio::net::TcpStream, IpFamily)>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    use tokio::{net::TcpStream, task::JoinSet, time};
+
+    let deadline = time::Instant::now() + timeout;
+    let mut pending = interleave_families(addrs).into_iter();
+    let mut attempts = JoinSet::new();
+    let mut last_err = None;
+    loop {
+        let now = time::Instant::now();
+        if now >= deadline {
+            return Err(timed_out());
+        }
+        if let Some(addr) = pending.next() {
+            if let Some(counters) = counters {
+                counters.record_attempt(addr.family());
+            }
+            attempts.spawn(async move { (addr, TcpStream::connect(addr).await, now) });
+        }
+        let wait_until = match pending.len() {
+            0 => deadline,
+            _ => deadline.min(now + CONNECTION_ATTEMPT_DELAY),
+        };
+        match time::timeout_at(wait_until, attempts.join_next()).await {
+            Ok(None) => return Err(last_err.unwrap_or_else(no_addrs)),
+            Ok(Some(Ok((addr, result, started)))) => {
+                if let Some(counters) = counters {
+                    record(counters, addr, result.is_ok(), started.elapsed());
+                }
+                match result {
+                    Ok(stream) => return Ok((stream, addr.family())),
+                    Err(err) => last_err = Some(err),
+                }
+            }
+            Ok(Some(Err(err))) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            // Cancelled, e.g. by runtime shutdown.
+            Ok(Some(Err(err))) => last_err = Some(io::Error::other(err)),
+            Err(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, TcpListener};
+
+    use super::*;
+    use crate::{resolve_with_preference, FamilyPreference, MockResolver};
+
+    const HOST: &str = "service.test";
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn listener(ip: IpAddr) -> TcpListener {
+        TcpListener::bind((ip, 0)).unwrap()
+    }
+
+    /// An address nothing listens on.
+    fn closed(ip: IpAddr) -> SocketAddr {
+        listener(ip).local_addr().unwrap()
+    }
+
+    fn resolved(resolver: &MockResolver, port: u16) -> Vec<SocketAddr> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(resolve_with_preference(
+                resolver,
+                HOST,
+                FamilyPreference::Any,
+            ))
+            .unwrap()
+            .into_iter()
+            .map(|ip| SocketAddr::new(ip, port))
+            .collect()
+    }
+
+    fn v4(s: &str) -> SocketAddr {
+        SocketAddr::new(s.parse::<Ipv4Addr>().unwrap().into(), 1)
+    }
+
+    fn v6(s: &str) -> SocketAddr {
+        SocketAddr::new(s.parse::<Ipv6Addr>().unwrap().into(), 1)
+    }
+
+    #[test]
+    fn interleaves_starting_with_the_first_family() {
+        let addrs = [
+            v6("::1"),
+            v6("::2"),
+            v6("::3"),
+            v4("1.0.0.1"),
+            v4("1.0.0.2"),
+        ];
+        assert_eq!(
+            interleave_families(addrs),
+            [addrs[0], addrs[3], addrs[1], addrs[4], addrs[2]]
+        );
+        let addrs = [v4("1.0.0.1"), v6("::1"), v4("1.0.0.2")];
+        assert_eq!(interleave_families(addrs), [addrs[0], addrs[1], addrs[2]]);
+        assert!(interleave_families([]).is_empty());
+    }
+
+    fn ips(list: &[&str]) -> Vec<IpAddr> {
+        list.iter().map(|ip| ip.parse().unwrap()).collect()
+    }
+
+    fn sockets(list: &[&str]) -> Vec<SocketAddr> {
+        list.iter().map(|addr| addr.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn service_hints_without_records_use_the_default_port() {
+        let resolved = ips(&["192.0.2.1", "2001:db8::1", "2001:db8::2"]);
+        assert_eq!(
+            merge_service_hints(&[], &resolved, 443, FamilyPreference::Any),
+            sockets(&["[2001:db8::1]:443", "192.0.2.1:443", "[2001:db8::2]:443"])
+        );
+    }
+
+    #[test]
+    fn service_hints_stand_in_for_missing_answers() {
+        let record = ServiceHints {
+            priority: 1,
+            port: Some(8443),
+            ipv4_hint: vec![Ipv4Addr::new(192, 0, 2, 9)],
+            ipv6_hint: vec!["2001:db8::9".parse().unwrap()],
+        };
+        // AAAA answered, A did not: the IPv6 hint is replaced, the IPv4
+        // hint is used.
+        let resolved = ips(&["2001:db8::1"]);
+        assert_eq!(
+            merge_service_hints(&[record], &resolved, 443, FamilyPreference::Any),
+            sockets(&["[2001:db8::1]:8443", "192.0.2.9:8443"])
+        );
+    }
+
+    #[test]
+    fn service_hints_follow_priority_and_preference() {
+        let records = [
+            ServiceHints {
+                priority: 2,
+                port: None,
+                ..ServiceHints::default()
+            },
+            // Alias mode.
+            ServiceHints {
+                priority: 0,
+                port: Some(1),
+                ..ServiceHints::default()
+            },
+            ServiceHints {
+                priority: 1,
+                port: Some(8443),
+                ..ServiceHints::default()
+            },
+        ];
+        let resolved = ips(&["2001:db8::1", "192.0.2.1"]);
+        assert_eq!(
+            merge_service_hints(&records, &resolved, 443, FamilyPreference::PreferV4),
+            sockets(&[
+                "192.0.2.1:8443",
+                "[2001:db8::1]:8443",
+                "192.0.2.1:443",
+                "[2001:db8::1]:443",
+            ])
+        );
+        assert_eq!(
+            merge_service_hints(&records, &resolved, 443, FamilyPreference::V6Only),
+            sockets(&["[2001:db8::1]:8443", "[2001:db8::1]:443"])
+        );
+    }
+
+    #[test]
+    fn service_hints_interleave_within_each_priority() {
+        let records = [
+            ServiceHints {
+                priority: 1,
+                port: Some(8443),
+                ipv4_hint: vec!["192.0.2.1".parse().unwrap()],
+                ipv6_hint: vec![
+                    "2001:db8::1".parse().unwrap(),
+                    "2001:db8::2".parse().unwrap(),
+                    "2001:db8::3".parse().unwrap(),
+                ],
+            },
+            ServiceHints {
+                priority: 2,
+                port: Some(443),
+                ipv4_hint: vec![
+                    "198.51.100.1".parse().unwrap(),
+                    "198.51.100.2".parse().unwrap(),
+                ],
+                ipv6_hint: vec!["2001:db8:1::1".parse().unwrap()],
+            },
+        ];
+        // Priority 2 endpoints never come before the last priority 1 one,
+        // although that group has fewer A than AAAA addresses.
+        assert_eq!(
+            merge_service_hints(&records, &[], 443, FamilyPreference::Any),
+            sockets(&[
+                "[2001:db8::1]:8443",
+                "192.0.2.1:8443",
+                "[2001:db8::2]:8443",
+                "[2001:db8::3]:8443",
+                "[2001:db8:1::1]:443",
+                "198.51.100.1:443",
+                "198.51.100.2:443",
+            ])
+        );
+    }
+
+    #[test]
+    fn service_hints_drop_duplicates() {
+        let record = ServiceHints {
+            priority: 1,
+            ..ServiceHints::default()
+        };
+        let resolved = ips(&["192.0.2.1", "192.0.2.1"]);
+        assert_eq!(
+            merge_service_hints(
+                &[record.clone(), record],
+                &resolved,
+                80,
+                FamilyPreference::Any
+            ),
+            sockets(&["192.0.2.1:80"])
+        );
+    }
+
+    #[test]
+    fn fails_over_to_ipv4_when_ipv6_refuses() {
+        let v4 = listener(Ipv4Addr::LOCALHOST.into());
+        let port = v4.local_addr().unwrap().port();
+        // The IPv6 candidate is tried first and refused.
+        let refused = closed(Ipv6Addr::LOCALHOST.into());
+        let resolver = MockResolver::new()
+            .with_answer(HOST, IpFamily::V4, [Ipv4Addr::LOCALHOST.into()])
+            .with_answer(HOST, IpFamily::V6, [Ipv6Addr::LOCALHOST.into()]);
+        let mut addrs = resolved(&resolver, port);
+        addrs[0].set_port(refused.port());
+        let (stream, family) = connect_happy_eyeballs(addrs, TIMEOUT).unwrap();
+        assert_eq!(family, IpFamily::V4);
+        assert_eq!(stream.peer_addr().unwrap(), v4.local_addr().unwrap());
+    }
+
+    #[test]
+    fn uses_the_remaining_family_when_a_lookup_fails() {
+        let v6 = listener(Ipv6Addr::LOCALHOST.into());
+        let resolver = MockResolver::new()
+            .with_error(HOST, IpFamily::V4, io::ErrorKind::TimedOut)
+            .with_answer(HOST, IpFamily::V6, [Ipv6Addr::LOCALHOST.into()]);
+        let addrs = resolved(&resolver, v6.local_addr().unwrap().port());
+        let (_, family) = connect_happy_eyeballs(addrs, TIMEOUT).unwrap();
+        assert_eq!(family, IpFamily::V6);
+    }
+
+    #[test]
+    fn reports_the_last_error_when_every_attempt_fails() {
+        let addrs = [
+            closed(Ipv6Addr::LOCALHOST.into()),
+            closed(Ipv4Addr::LOCALHOST.into()),
+        ];
+        let err = connect_happy_eyeballs(addrs, TIMEOUT).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+    }
+
+    #[test]
+    fn rejects_an_empty_candidate_list() {
+        let err = connect_happy_eyeballs([], TIMEOUT).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn tokio_fails_over_to_ipv4_when_ipv6_refuses() {
+        let v4 = listener(Ipv4Addr::LOCALHOST.into());
+        let addrs = [closed(Ipv6Addr::LOCALHOST.into()), v4.local_addr().unwrap()];
+        let (stream, family) = connect_happy_eyeballs_tokio(addrs, TIMEOUT).await.unwrap();
+        assert_eq!(family, IpFamily::V4);
+        assert_eq!(stream.peer_addr().unwrap(), v4.local_addr().unwrap());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn tokio_reports_the_last_error_when_every_attempt_fails() {
+        let addrs = [
+            closed(Ipv6Addr::LOCALHOST.into()),
+            closed(Ipv4Addr::LOCALHOST.into()),
+        ];
+        let err = connect_happy_eyeballs_tokio(addrs, TIMEOUT)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+        let err = connect_happy_eyeballs_tokio([], TIMEOUT).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn records_attempts_in_counters() {
+        let v4 = listener(Ipv4Addr::LOCALHOST.into());
+        let addrs = [closed(Ipv6Addr::LOCALHOST.into()), v4.local_addr().unwrap()];
+        let counters = FamilyCounters::new();
+        let (_, family) = connect_happy_eyeballs_with_metrics(addrs, TIMEOUT, &counters).unwrap();
+        assert_eq!(family, IpFamily::V4);
+        let v6 = counters.snapshot(IpFamily::V6);
+        assert_eq!((v6.attempts, v6.successes, v6.failures), (1, 0, 1));
+        let v4 = counters.snapshot(IpFamily::V4);
+        assert_eq!((v4.attempts, v4.successes, v4.failures), (1, 1, 0));
+        assert_eq!(v4.latency_count() + v6.latency_count(), 2);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn tokio_records_attempts_in_counters() {
+        let addrs = [
+            closed(Ipv6Addr::LOCALHOST.into()),
+            closed(Ipv4Addr::LOCALHOST.into()),
+        ];
+        let counters = FamilyCounters::new();
+        connect_happy_eyeballs_tokio_with_metrics(addrs, TIMEOUT, &counters)
+            .await
+            .unwrap_err();
+        for family in [IpFamily::V4, IpFamily::V6] {
+            let snapshot = counters.snapshot(family);
+            assert_eq!(
+                (snapshot.attempts, snapshot.successes, snapshot.failures),
+                (1, 0, 1)
+            );
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+
+use crate::{sealed, AnyIpFamily, IpPrefix, Network};
+
+/// The `ipnet` network type of a family, [`AnyIpFamily::Net`].
+pub trait FamilyNet: sealed::Sealed + Copy + Into<IpNet> {
+    /// Returns `None` if `net` belongs to the other family.
+    fn from_ip_net(net: IpNet) -> Option<Self>;
+}
+
+impl sealed::Sealed for Ipv4Net {}
+impl FamilyNet for Ipv4Net {
+    fn from_ip_net(net: IpNet) -> Option<Self> {
+        match net {
+            IpNet::V4(net) => Some(net),
+            IpNet::V6(_) => None,
+        }
+    }
+}
+
+impl sealed::Sealed for Ipv6Net {}
+impl FamilyNet for Ipv6Net {
+    fn from_ip_net(net: IpNet) -> Option<Self> {
+        match net {
+            IpNet::V6(net) => Some(net),
+            IpNet::V4(_) => None,
+        }
+    }
+}
+
+impl<F: AnyIpFamily> Network<F> {
+    pub fn to_net(&self) -> F::Net {
+        F::Net::from_ip_net(IpPrefix::from(*self).into()).expect("network matches the family")
+    }
+
+    /// Host bits set in `net` are cleared.
+    pub fn from_net(net: F::Net) -> Self {
+        Self::try_from(IpPrefix::from(net.into())).expect("network matches the family")
+    }
+}
+
+impl From<IpPrefix> for IpNet {
+    fn from(value: IpPrefix) -> Self {
+        IpNet::new(value.addr(), value.len()).expect("prefix length is valid")
+    }
+}
+
+/// Host bits set in the `IpNet` are cleared.
+impl From<IpNet> for IpPrefix {
+    fn from(value: IpNet) -> Self {
+        IpPrefix::new(value.addr(), value.prefix_len())
+    }
+}
+
+impl<F: AnyIpFamily> From<Network<F>> for IpNet {
+    fn from(value: Network<F>) -> Self {
+        IpPrefix::from(value).into()
+    }
+}
+
+#[cfg(feature = "v4")]
+impl From<Network<crate::IpFamilyV4>> for Ipv4Net {
+    fn from(value: Network<crate::IpFamilyV4>) -> Self {
+        value.to_net()
+    }
+}
+
+#[cfg(feature = "v4")]
+impl From<Ipv4Net> for Network<crate::IpFamilyV4> {
+    fn from(value: Ipv4Net) -> Self {
+        Self::from_net(value)
+    }
+}
+
+#[cfg(feature = "v6")]
+impl From<Network<crate::IpFamilyV6>> for Ipv6Net {
+    fn from(value: Network<crate::IpFamilyV6>) -> Self {
+        value.to_net()
+    }
+}
+
+#[cfg(feature = "v6")]
+impl From<Ipv6Net> for Network<crate::IpFamilyV6> {
+    fn from(value: Ipv6Net) -> Self {
+        Self::from_net(value)
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt::{self, Debug, Formatter};
+
+use crate::{AnyIpFamily, IpFamilyV4, IpFamilyV6};
+
+/// Type-level mapping from a family marker to the type stored for it, e.g.
+/// `type Of<F> = HashMap<F::Addr, T>`.
+pub trait FamilyKeyed {
+    type Of<F: AnyIpFamily>;
+}
+
+/// Holds one `K::Of<F>` per family, accessed generically by marker type.
+pub struct FamilyMap<K: FamilyKeyed> {
+    pub v4: K::Of<IpFamilyV4>,
+    pub v6: K::Of<IpFamilyV6>,
+}
+
+impl<K: FamilyKeyed> FamilyMap<K> {
+    pub fn new(v4: K::Of<IpFamilyV4>, v6: K::Of<IpFamilyV6>) -> Self {
+        Self { v4, v6 }
+    }
+
+    pub fn get<F: AnyIpFamily>(&self) -> &K::Of<F> {
+        F::select(self)
+    }
+
+    pub fn get_mut<F: AnyIpFamily>(&mut self) -> &mut K::Of<F> {
+        F::select_mut(self)
+    }
+}
+
+impl<K: FamilyKeyed> Default for FamilyMap<K>
+where
+    K::Of<IpFamilyV4>: Default,
+    K::Of<IpFamilyV6>: Default,
+{
+    fn default() -> Self {
+        Self::new(Default::default(), Default::default())
+    }
+}
+
+impl<K: FamilyKeyed> Clone for FamilyMap<K>
+where
+    K::Of<IpFamilyV4>: Clone,
+    K::Of<IpFamilyV6>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self::new(self.v4.clone(), self.v6.clone())
+    }
+}
+
+impl<K: FamilyKeyed> Debug for FamilyMap<K>
+where
+    K::Of<IpFamilyV4>: Debug,
+    K::Of<IpFamilyV6>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FamilyMap")
+            .field("v4", &self.v4)
+            .field("v6", &self.v6)
+            .finish()
+    }
+}
@@ -1,95 +1,583 @@
 use std::{
-    fmt::{Debug, Display},
+    fmt::{self, Debug, Display},
     hash::Hash,
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs},
+    ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr},
     str::FromStr,
 };
 
+mod accounting;
+#[cfg(unix)]
+mod af;
+mod allowed;
+mod arith;
+pub mod builder;
+mod classify;
+mod client;
+pub mod conformance;
+#[cfg(all(feature = "v4", feature = "v6"))]
+mod dispatch;
+mod display;
+#[cfg(feature = "socket2")]
+mod dual_stack;
+mod dynamic;
+mod error;
+mod explain;
+mod family_set;
+mod family_value;
+mod fanout;
+mod feed;
+pub mod fixtures;
+mod forwarded;
+mod generator;
+mod happy_eyeballs;
+#[cfg(feature = "ipnet")]
+mod ipnet_interop;
+#[cfg(all(feature = "v4", feature = "v6"))]
+mod keyed;
+mod link_local;
+#[cfg(all(unix, feature = "local-addrs"))]
+mod local_addrs;
+mod lookup;
+mod macros;
+mod mapping;
+mod metrics;
+#[cfg(any(test, feature = "test-util"))]
+mod mock_resolver;
+mod multicast;
+mod nat64;
+mod network;
+mod order;
+mod parse;
+mod per_family;
+mod pool;
+mod preference;
+mod prefix;
+mod prefix_map;
+mod profile;
+mod ptr;
+mod range;
+mod redact;
+mod resolve;
+mod rfc6724;
+mod socket;
+#[cfg(feature = "socket2")]
+mod socket2_interop;
+#[cfg(feature = "socket2")]
+mod sockopt;
+mod sources;
+#[cfg(feature = "tokio")]
+pub mod tokio_net;
+mod tunnel;
+mod typed_socket;
+mod watch;
+pub mod wire;
+
+pub use accounting::{PrefixAccounting, PrefixTraffic, Traffic};
+pub use allowed::AllowedIps;
+pub use builder::DualStackBuilder;
+pub use client::{client_key, ClientKey};
+#[cfg(all(feature = "v4", feature = "v6"))]
+pub use dispatch::{DynFamilyHandler, FamilyHandler, FamilyVisitor};
+pub use display::{AddrDisplay, DisplayOptions, ExplodedDisplay, FormattedAddr};
+#[cfg(feature = "socket2")]
+pub use dual_stack::DualStackListener;
+pub use dynamic::{DynIpAddr, DynSocketAddr, Octets};
+pub use error::{
+    BufferTooSmall, ConnectError, Error, FamilyMismatchError, InvalidPrefix, MappingError,
+    MulticastError, Nat64PrefixError, ParseFamilyPreferenceError, ParseIpFamilyError,
+    ParseOrMismatchError, ParsePrefixError, ParsePtrError, ParseSocketAddrError, TextError,
+    TextErrorKind, WireError, WrongLength,
+};
+pub use explain::{explain, EmbeddedV4, Explanation, SpecialPurpose};
+pub use family_set::FamilySet;
+pub use family_value::FamilyValue;
+pub use fanout::FanoutSender;
+#[cfg(feature = "serde")]
+pub use feed::import_json;
+pub use feed::{import_csv, import_lines, CsvColumn, FeedImport};
+pub use forwarded::{resolve_client, ClientClass, ResolvedClient};
+pub use generator::{AddrBucket, AddrGenerator};
+pub use happy_eyeballs::{
+    connect_happy_eyeballs, connect_happy_eyeballs_with_metrics, interleave_families,
+    merge_service_hints, ServiceHints, CONNECTION_ATTEMPT_DELAY,
+};
+#[cfg(feature = "tokio")]
+pub use happy_eyeballs::{connect_happy_eyeballs_tokio, connect_happy_eyeballs_tokio_with_metrics};
+#[cfg(feature = "ipnet")]
+pub use ipnet_interop::FamilyNet;
+#[cfg(all(feature = "v4", feature = "v6"))]
+pub use keyed::{FamilyKeyed, FamilyMap};
+pub use link_local::{
+    eui64_interface_id, link_local, link_local_v4, link_local_v6, mac_from_interface_id, Eui64Addr,
+};
+#[cfg(all(unix, feature = "local-addrs"))]
+pub use local_addrs::{
+    interface_index, interface_name, local_addrs, local_addrs_of, LocalAddr, ZoneDisplay,
+};
+pub use lookup::ToSocketAddrsFamilyExt;
+pub use mapping::MappingPolicy;
+pub use metrics::{CountersSnapshot, FamilyCounters, LATENCY_BUCKETS};
+#[cfg(any(test, feature = "test-util"))]
+pub use mock_resolver::MockResolver;
+pub use multicast::{MulticastFlags, MulticastGroup, MulticastScope, MulticastV6};
+pub use nat64::Nat64Prefix;
+pub use network::{AnyIpNet, Hosts, Network};
+pub use order::{eq_ignore_meta, AddrPort, Canonical, IgnoreMeta, MappedOrd};
+pub use parse::{parse_socket_addr_or_default, parse_socket_addr_with_family};
+pub use per_family::PerFamily;
+pub use pool::{FamilyPool, PoolConfig};
+pub use preference::FamilyPreference;
+pub use prefix::IpPrefix;
+pub use prefix_map::{PrefixMap, PrefixMapIter};
+pub use profile::{
+    detect_supported_families, refresh_supported_families, HostNetworkProfile, NetworkProfile,
+};
+pub use range::AddrRange;
+pub use redact::{Redact, Redacted, RedactionPolicy};
+pub use resolve::{
+    resolve, resolve_family, resolve_stream, resolve_with_preference, InstrumentedResolver,
+    ResolveStream, Resolver,
+};
+pub use rfc6724::{sort_addrs_rfc6724, sort_addrs_rfc6724_with, sorted_rfc6724};
+#[cfg(feature = "tokio")]
+pub use socket::listen_any_tokio;
+#[cfg(feature = "socket2")]
+pub use socket::tcp_connect_from;
+pub use socket::{listen_any, udp_connect_from, udp_ephemeral, BindAddr};
+#[cfg(feature = "socket2")]
+pub use socket2_interop::SocketBuilder;
+pub use sources::ExpectedSources;
+pub use tunnel::{is_6to4, is_teredo, sixto4_v4, Teredo};
+pub use typed_socket::{FamilyTcpListener, FamilyTcpStream, FamilyUdpSocket};
+pub use watch::{SetChange, WatchedSet};
+pub use wire::{WireRead, WireWrite};
+
+#[cfg(feature = "macros")]
+pub use ip_family_macros::{ip_family_generic, HasFamily, PerIpFamily};
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "serde")]
+    pub use serde;
+}
+
+#[cfg(feature = "serde")]
+pub mod serde_with;
+
 mod sealed {
     pub trait Sealed {}
 }
 
 pub trait AnyIpFamily
 where
-    Self: sealed::Sealed,
-    Self::Addr: IpFamilyAddr<Family = Self>,
-    Self::SocketAddr: IpFamilySocketAddr<Family = Self>,
-    IpAddr: From<Self::Addr>,
-    SocketAddr: From<Self::SocketAddr>,
+    Self: sealed::Sealed + Send + Sync + Sized + 'static,
+    Self::Addr: IpFamilyAddr<Family = Self> + Into<IpAddr>,
+    Self::SocketAddr: IpFamilySocketAddr<Family = Self> + Into<SocketAddr>,
 {
     type Addr;
     type SocketAddr;
+    /// `ipnet::Ipv4Net` or `ipnet::Ipv6Net`.
+    #[cfg(feature = "ipnet")]
+    type Net: FamilyNet;
 
     const FAMILY: IpFamily;
+    /// Whether `Self::Addr` implements [`BroadcastAddr`].
+    const HAS_BROADCAST: bool;
+    /// `AF_INET` or `AF_INET6`.
+    #[cfg(unix)]
+    const AF: libc::sa_family_t;
+
+    #[cfg(all(feature = "v4", feature = "v6"))]
+    fn select<K: FamilyKeyed>(map: &FamilyMap<K>) -> &K::Of<Self>;
+    #[cfg(all(feature = "v4", feature = "v6"))]
+    fn select_mut<K: FamilyKeyed>(map: &mut FamilyMap<K>) -> &mut K::Of<Self>;
 }
 
+/// A zeroed `AddrBuf::<F>::default()` is a stack buffer of exactly
+/// `F::Addr::BYTES` bytes.
+pub type AddrBuf<F> = <<F as AnyIpFamily>::Addr as IpFamilyAddr>::Bytes;
+
 pub trait IpFamilyAddr
 where
     Self: sealed::Sealed
+        + AsRef<IpFamily>
         + Copy
         + Debug
         + Display
         + Eq
+        + ExplodedDisplay
         + From<Self::Raw>
         + From<Self::Bytes>
         + FromStr
         + Hash
-        + Ord,
+        + Into<IpAddr>
+        + Ord
+        + Send
+        + Sync
+        + 'static,
     Self::Family: AnyIpFamily<Addr = Self>,
-    Self::Raw: From<Self>,
-    IpAddr: From<Self>,
-    SocketAddr: From<<Self::Family as AnyIpFamily>::SocketAddr>,
+    Self::Raw: From<Self>
+        + From<u8>
+        + Copy
+        + Debug
+        + Default
+        + Eq
+        + Hash
+        + Ord
+        + BitAnd<Output = Self::Raw>
+        + BitOr<Output = Self::Raw>
+        + BitXor<Output = Self::Raw>
+        + Not<Output = Self::Raw>
+        + Shl<u32, Output = Self::Raw>
+        + Shr<u32, Output = Self::Raw>,
+    Self::Bytes: AsRef<[u8]> + AsMut<[u8]> + Copy + Default + for<'a> TryFrom<&'a [u8]>,
+    Self::Segments: AsRef<[u16]> + AsMut<[u16]> + Copy + Debug + Default + Eq,
 {
     type Family;
     type Raw;
     type Bytes;
+    /// The address in big-endian 16-bit groups, `[u16; 2]` for IPv4.
+    type Segments;
 
+    /// Same as [`AnyIpFamily::FAMILY`].
+    const FAMILY: IpFamily = <Self::Family as AnyIpFamily>::FAMILY;
     const BYTES: usize;
+    /// Upper bound on the length of the `Display` output.
+    const MAX_DISPLAY_LEN: usize;
+    /// Number of bits in the address, 32 or 128.
+    const MAX_PREFIX: u8;
     const LOCALHOST: Self;
     const UNSPECIFIED: Self;
 
     fn octets(&self) -> Self::Bytes;
+    fn segments(&self) -> Self::Segments;
+    fn from_segments(segments: Self::Segments) -> Self;
     fn is_unspecified(&self) -> bool;
     fn is_loopback(&self) -> bool;
     fn is_multicast(&self) -> bool;
+    /// RFC 1918 for IPv4, unique local (`fc00::/7`) for IPv6.
+    fn is_private(&self) -> bool;
+    /// `169.254.0.0/16` or `fe80::/10`.
+    fn is_link_local(&self) -> bool;
+    /// RFC 5737 for IPv4, `2001:db8::/32` and `3fff::/20` for IPv6.
+    fn is_documentation(&self) -> bool;
+    /// `198.18.0.0/15` or `2001:2::/48`.
+    fn is_benchmarking(&self) -> bool;
+    /// Whether the address is globally reachable according to the IANA
+    /// special-purpose registries, like the unstable std `is_global`.
+    #[cfg(feature = "global")]
+    fn is_global(&self) -> bool;
+
+    /// Fails if `addr` belongs to the other family. Stands in for
+    /// `TryFrom<IpAddr>`, which cannot be implemented for the std address
+    /// types outside of std.
+    fn try_from_ip_addr(addr: IpAddr) -> Result<Self, FamilyMismatchError>;
+
+    /// `::ffff:a.b.c.d` for IPv4, IPv6 addresses are returned as is.
+    fn to_ipv6_mapped(&self) -> Ipv6Addr;
+    /// The IPv4 address itself, or the one in an IPv4-mapped IPv6 address.
+    fn to_ipv4_mapped(&self) -> Option<Ipv4Addr>;
+    /// Unmaps IPv4-mapped IPv6 addresses.
+    fn to_canonical(&self) -> IpAddr;
+
+    /// Compares the [`to_canonical`](Self::to_canonical) forms, so
+    /// `1.2.3.4` equals `::ffff:1.2.3.4`.
+    fn eq_canonical(&self, other: &IpAddr) -> bool {
+        self.to_canonical() == other.to_canonical()
+    }
+
+    fn to_bits(&self) -> Self::Raw {
+        Self::Raw::from(*self)
+    }
+
+    fn from_bits(bits: Self::Raw) -> Self {
+        Self::from(bits)
+    }
+
+    /// The mask with the leading `prefix` bits set, e.g. `255.255.255.0`
+    /// for `/24`.
+    fn netmask_from_prefix(prefix: u8) -> Result<Self, InvalidPrefix> {
+        if prefix > Self::MAX_PREFIX {
+            return Err(InvalidPrefix {
+                len: prefix,
+                max: Self::MAX_PREFIX,
+            });
+        }
+        let zero = Self::Raw::default();
+        if prefix == 0 {
+            return Ok(Self::from_bits(zero));
+        }
+        Ok(Self::from_bits(
+            !zero << u32::from(Self::MAX_PREFIX - prefix),
+        ))
+    }
+
+    /// Returns `None` unless the set bits of `self` are contiguous from the
+    /// most significant bit.
+    fn prefix_from_netmask(&self) -> Option<u8> {
+        (0..=Self::MAX_PREFIX).find(|&prefix| Self::netmask_from_prefix(prefix) == Ok(*self))
+    }
+
+    /// Adds `n` as if the address were an integer, `None` on overflow,
+    /// including `n` larger than the address width.
+    fn checked_add(&self, n: u128) -> Option<Self> {
+        arith::checked_add(*self, n)
+    }
+
+    fn checked_sub(&self, n: u128) -> Option<Self> {
+        arith::checked_sub(*self, n)
+    }
+
+    /// Stops at the all-ones address.
+    fn saturating_add(&self, n: u128) -> Self {
+        arith::saturating_add(*self, n)
+    }
+
+    /// Stops at the unspecified address.
+    fn saturating_sub(&self, n: u128) -> Self {
+        arith::saturating_sub(*self, n)
+    }
+
+    /// The following address, `None` after the all-ones address.
+    fn next(&self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    /// The preceding address, `None` before the unspecified address.
+    fn prev(&self) -> Option<Self> {
+        self.checked_sub(1)
+    }
+
+    /// `d.c.b.a.in-addr.arpa` for IPv4, the nibble-reversed `ip6.arpa`
+    /// name for IPv6.
+    fn ptr_name(&self) -> String {
+        ptr::ptr_name(self.octets().as_ref())
+    }
+
+    /// Parses the output of [`ptr_name`](Self::ptr_name), with or without
+    /// a trailing dot.
+    fn from_ptr_name(name: &str) -> Result<Self, ParsePtrError> {
+        let octets = ptr::parse_ptr_name(name, Self::BYTES)?;
+        Self::from_byte_slice(&octets).map_err(|_| ParsePtrError(()))
+    }
+
+    fn from_byte_slice(bytes: &[u8]) -> Result<Self, WrongLength> {
+        Self::Bytes::try_from(bytes)
+            .map(Self::from)
+            .map_err(|_| WrongLength {
+                expected: Self::BYTES,
+                actual: bytes.len(),
+            })
+    }
+
+    /// Reads an address from the start of `bytes`, such as a field of a
+    /// packet header, and returns the bytes after it. Fails if `bytes` is
+    /// shorter than [`BYTES`](Self::BYTES).
+    fn from_byte_prefix(bytes: &[u8]) -> Result<(Self, &[u8]), WrongLength> {
+        if bytes.len() < Self::BYTES {
+            return Err(WrongLength {
+                expected: Self::BYTES,
+                actual: bytes.len(),
+            });
+        }
+        let (addr, rest) = bytes.split_at(Self::BYTES);
+        Ok((Self::from_byte_slice(addr)?, rest))
+    }
+
+    fn display(&self) -> AddrDisplay<Self> {
+        AddrDisplay(*self)
+    }
+
+    /// Formats the address into `buf` without allocating. Succeeds for any
+    /// buffer of [`MAX_DISPLAY_LEN`](Self::MAX_DISPLAY_LEN) bytes.
+    fn write_to<'a>(&self, buf: &'a mut [u8]) -> Result<&'a str, BufferTooSmall> {
+        display::write_to(self, buf)
+    }
 }
 
 pub trait IpFamilySocketAddr
 where
-    Self: sealed::Sealed + Copy + Debug + Display + Eq + FromStr + Hash + Ord + ToSocketAddrs,
-
+    Self: sealed::Sealed
+        + AsRef<IpFamily>
+        + Copy
+        + Debug
+        + Display
+        + Eq
+        + ExplodedDisplay
+        + FromStr
+        + Hash
+        + Into<SocketAddr>
+        + Ord
+        + Send
+        + Sync
+        + ToSocketAddrs
+        + 'static,
     Self::Family: AnyIpFamily<SocketAddr = Self>,
-    IpAddr: From<<Self::Family as AnyIpFamily>::Addr>,
-    SocketAddr: From<Self>,
 {
     type Family;
 
+    /// Same as [`AnyIpFamily::FAMILY`].
+    const FAMILY: IpFamily = <Self::Family as AnyIpFamily>::FAMILY;
+    /// Upper bound on the length of the `Display` output, including the
+    /// brackets and scope id of IPv6 socket addresses.
+    const MAX_DISPLAY_LEN: usize;
+    /// The loopback address with port 0.
+    const LOCALHOST: Self;
+    /// The unspecified address with port 0.
+    const UNSPECIFIED: Self;
+
+    /// IPv6 socket addresses get a zero flowinfo and scope id.
     fn new(ip: <Self::Family as AnyIpFamily>::Addr, port: u16) -> Self;
+    /// Like [`new`](Self::new), with the IPv6-only fields. They are
+    /// ignored for IPv4.
+    fn new_with_scope(
+        ip: <Self::Family as AnyIpFamily>::Addr,
+        port: u16,
+        flowinfo: u32,
+        scope_id: u32,
+    ) -> Self;
     fn ip(&self) -> <Self::Family as AnyIpFamily>::Addr;
     fn set_ip(&mut self, new_ip: <Self::Family as AnyIpFamily>::Addr);
     fn port(&self) -> u16;
     fn set_port(&mut self, new_port: u16);
+
+    /// Fails if `addr` belongs to the other family.
+    fn try_from_socket_addr(addr: SocketAddr) -> Result<Self, FamilyMismatchError>;
+
+    /// Like [`IpFamilyAddr::to_ipv6_mapped`], keeping the port and, for
+    /// IPv6, flowinfo and scope id.
+    fn to_ipv6_mapped(&self) -> SocketAddrV6;
+    /// Like [`IpFamilyAddr::to_ipv4_mapped`], keeping the port.
+    fn to_ipv4_mapped(&self) -> Option<SocketAddrV4>;
+    /// Like [`IpFamilyAddr::to_canonical`], keeping the port. Unmapped
+    /// addresses lose the flowinfo and scope id.
+    fn to_canonical(&self) -> SocketAddr;
+
+    fn localhost_with_port(port: u16) -> Self {
+        Self::new(<Self::Family as AnyIpFamily>::Addr::LOCALHOST, port)
+    }
+
+    fn unspecified_with_port(port: u16) -> Self {
+        Self::new(<Self::Family as AnyIpFamily>::Addr::UNSPECIFIED, port)
+    }
+
+    /// Replaces the address, keeping the port and, for IPv6, flowinfo and
+    /// scope id.
+    fn with_ip(mut self, ip: <Self::Family as AnyIpFamily>::Addr) -> Self {
+        self.set_ip(ip);
+        self
+    }
+
+    /// Replaces the port, keeping everything else.
+    fn with_port(mut self, port: u16) -> Self {
+        self.set_port(port);
+        self
+    }
+
+    /// Like [`new`](Self::new), but takes the IPv6 flowinfo and scope id
+    /// from `template` instead of zeroing them.
+    fn rebuild_from(template: &Self, ip: <Self::Family as AnyIpFamily>::Addr, port: u16) -> Self {
+        template.with_ip(ip).with_port(port)
+    }
+
+    fn display(&self) -> AddrDisplay<Self> {
+        AddrDisplay(*self)
+    }
+
+    /// Formats the address into `buf` without allocating. Succeeds for any
+    /// buffer of [`MAX_DISPLAY_LEN`](Self::MAX_DISPLAY_LEN) bytes.
+    fn write_to<'a>(&self, buf: &'a mut [u8]) -> Result<&'a str, BufferTooSmall> {
+        display::write_to(self, buf)
+    }
+}
+
+/// Limited broadcast, implemented by [`Ipv4Addr`] alone.
+pub trait BroadcastAddr: IpFamilyAddr {
+    /// `255.255.255.255`.
+    const BROADCAST: Self;
+
+    fn is_broadcast(&self) -> bool;
 }
 
+/// The IPv6-only fields of a socket address, implemented by
+/// [`SocketAddrV6`] alone.
+pub trait ScopedSocketAddr: IpFamilySocketAddr {
+    fn flowinfo(&self) -> u32;
+    fn set_flowinfo(&mut self, new_flowinfo: u32);
+    fn scope_id(&self) -> u32;
+    fn set_scope_id(&mut self, new_scope_id: u32);
+}
+
+#[cfg(feature = "v4")]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
 pub struct IpFamilyV4;
 
+#[cfg(feature = "v4")]
+impl IpFamilyV4 {
+    pub const fn from_octets(octets: [u8; IPV4_ADDR_BYTES]) -> Ipv4Addr {
+        let [a, b, c, d] = octets;
+        Ipv4Addr::new(a, b, c, d)
+    }
+
+    /// `const` version of [`IpFamilyAddr::from_bits`].
+    pub const fn from_bits(bits: u32) -> Ipv4Addr {
+        Ipv4Addr::from_bits(bits)
+    }
+
+    /// `const` version of [`IpFamilyAddr::netmask_from_prefix`].
+    pub const fn netmask(prefix: u8) -> Result<Ipv4Addr, InvalidPrefix> {
+        match prefix {
+            0 => Ok(Ipv4Addr::UNSPECIFIED),
+            1..=32 => Ok(Ipv4Addr::from_bits(u32::MAX << (32 - prefix))),
+            _ => Err(InvalidPrefix {
+                len: prefix,
+                max: 32,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "v4")]
 impl sealed::Sealed for IpFamilyV4 {}
+#[cfg(feature = "v4")]
 impl AnyIpFamily for IpFamilyV4 {
     type Addr = Ipv4Addr;
 
     type SocketAddr = SocketAddrV4;
 
+    #[cfg(feature = "ipnet")]
+    type Net = ipnet::Ipv4Net;
+
     const FAMILY: IpFamily = IpFamily::V4;
+    const HAS_BROADCAST: bool = true;
+    #[cfg(unix)]
+    const AF: libc::sa_family_t = IpFamily::V4.to_af();
+
+    #[cfg(all(feature = "v4", feature = "v6"))]
+    fn select<K: FamilyKeyed>(map: &FamilyMap<K>) -> &K::Of<Self> {
+        &map.v4
+    }
+
+    #[cfg(all(feature = "v4", feature = "v6"))]
+    fn select_mut<K: FamilyKeyed>(map: &mut FamilyMap<K>) -> &mut K::Of<Self> {
+        &mut map.v4
+    }
 }
 
+#[cfg(feature = "v4")]
 const IPV4_ADDR_BYTES: usize = 4;
 impl sealed::Sealed for Ipv4Addr {}
+#[cfg(feature = "v4")]
 impl IpFamilyAddr for Ipv4Addr {
     type Family = IpFamilyV4;
     type Raw = u32;
     type Bytes = [u8; IPV4_ADDR_BYTES];
+    type Segments = [u16; 2];
 
     const BYTES: usize = IPV4_ADDR_BYTES;
+    const MAX_DISPLAY_LEN: usize = "255.255.255.255".len();
+    const MAX_PREFIX: u8 = 32;
     const LOCALHOST: Self = Self::LOCALHOST;
     const UNSPECIFIED: Self = Self::UNSPECIFIED;
 
@@ -97,6 +585,15 @@ impl IpFamilyAddr for Ipv4Addr {
         self.octets()
     }
 
+    fn segments(&self) -> Self::Segments {
+        let bits = self.to_bits();
+        [(bits >> 16) as u16, bits as u16]
+    }
+
+    fn from_segments([high, low]: Self::Segments) -> Self {
+        Self::from_bits((u32::from(high) << 16) | u32::from(low))
+    }
+
     fn is_unspecified(&self) -> bool {
         self.is_unspecified()
     }
@@ -108,16 +605,85 @@ impl IpFamilyAddr for Ipv4Addr {
     fn is_multicast(&self) -> bool {
         self.is_multicast()
     }
+
+    fn is_private(&self) -> bool {
+        self.is_private()
+    }
+
+    fn is_link_local(&self) -> bool {
+        self.is_link_local()
+    }
+
+    fn is_documentation(&self) -> bool {
+        self.is_documentation()
+    }
+
+    fn is_benchmarking(&self) -> bool {
+        classify::v4_is_benchmarking(*self)
+    }
+
+    #[cfg(feature = "global")]
+    fn is_global(&self) -> bool {
+        classify::v4_is_global(*self)
+    }
+
+    fn try_from_ip_addr(addr: IpAddr) -> Result<Self, FamilyMismatchError> {
+        match addr {
+            IpAddr::V4(addr) => Ok(addr),
+            IpAddr::V6(_) => Err(FamilyMismatchError {
+                expected: IpFamily::V4,
+                actual: IpFamily::V6,
+            }),
+        }
+    }
+
+    fn to_ipv6_mapped(&self) -> Ipv6Addr {
+        Ipv4Addr::to_ipv6_mapped(self)
+    }
+
+    fn to_ipv4_mapped(&self) -> Option<Ipv4Addr> {
+        Some(*self)
+    }
+
+    fn to_canonical(&self) -> IpAddr {
+        IpAddr::V4(*self)
+    }
+}
+
+#[cfg(feature = "v4")]
+impl BroadcastAddr for Ipv4Addr {
+    const BROADCAST: Self = Self::BROADCAST;
+
+    fn is_broadcast(&self) -> bool {
+        self.is_broadcast()
+    }
 }
 
 impl sealed::Sealed for SocketAddrV4 {}
+#[cfg(feature = "v4")]
+const _: () = assert!(size_of::<AddrBuf<IpFamilyV4>>() == Ipv4Addr::BYTES);
+
+#[cfg(feature = "v4")]
 impl IpFamilySocketAddr for SocketAddrV4 {
     type Family = IpFamilyV4;
 
+    const MAX_DISPLAY_LEN: usize = Ipv4Addr::MAX_DISPLAY_LEN + ":65535".len();
+    const LOCALHOST: Self = Self::new(Ipv4Addr::LOCALHOST, 0);
+    const UNSPECIFIED: Self = Self::new(Ipv4Addr::UNSPECIFIED, 0);
+
     fn new(ip: <Self::Family as AnyIpFamily>::Addr, port: u16) -> Self {
         Self::new(ip, port)
     }
 
+    fn new_with_scope(
+        ip: <Self::Family as AnyIpFamily>::Addr,
+        port: u16,
+        _flowinfo: u32,
+        _scope_id: u32,
+    ) -> Self {
+        Self::new(ip, port)
+    }
+
     fn ip(&self) -> <Self::Family as AnyIpFamily>::Addr {
         *self.ip()
     }
@@ -133,27 +699,98 @@ impl IpFamilySocketAddr for SocketAddrV4 {
     fn set_port(&mut self, new_port: u16) {
         self.set_port(new_port)
     }
+
+    fn try_from_socket_addr(addr: SocketAddr) -> Result<Self, FamilyMismatchError> {
+        match addr {
+            SocketAddr::V4(addr) => Ok(addr),
+            SocketAddr::V6(_) => Err(FamilyMismatchError {
+                expected: IpFamily::V4,
+                actual: IpFamily::V6,
+            }),
+        }
+    }
+
+    fn to_ipv6_mapped(&self) -> SocketAddrV6 {
+        SocketAddrV6::new(self.ip().to_ipv6_mapped(), self.port(), 0, 0)
+    }
+
+    fn to_ipv4_mapped(&self) -> Option<SocketAddrV4> {
+        Some(*self)
+    }
+
+    fn to_canonical(&self) -> SocketAddr {
+        SocketAddr::V4(*self)
+    }
 }
 
+#[cfg(feature = "v6")]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
 pub struct IpFamilyV6;
 
+#[cfg(feature = "v6")]
+impl IpFamilyV6 {
+    pub const fn from_octets(octets: [u8; IPV6_ADDR_BYTES]) -> Ipv6Addr {
+        Ipv6Addr::from_bits(u128::from_be_bytes(octets))
+    }
+
+    /// `const` version of [`IpFamilyAddr::from_bits`].
+    pub const fn from_bits(bits: u128) -> Ipv6Addr {
+        Ipv6Addr::from_bits(bits)
+    }
+
+    /// `const` version of [`IpFamilyAddr::netmask_from_prefix`].
+    pub const fn netmask(prefix: u8) -> Result<Ipv6Addr, InvalidPrefix> {
+        match prefix {
+            0 => Ok(Ipv6Addr::UNSPECIFIED),
+            1..=128 => Ok(Ipv6Addr::from_bits(u128::MAX << (128 - prefix))),
+            _ => Err(InvalidPrefix {
+                len: prefix,
+                max: 128,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "v6")]
 impl sealed::Sealed for IpFamilyV6 {}
+#[cfg(feature = "v6")]
 impl AnyIpFamily for IpFamilyV6 {
     type Addr = Ipv6Addr;
 
     type SocketAddr = SocketAddrV6;
 
+    #[cfg(feature = "ipnet")]
+    type Net = ipnet::Ipv6Net;
+
     const FAMILY: IpFamily = IpFamily::V6;
+    const HAS_BROADCAST: bool = false;
+    #[cfg(unix)]
+    const AF: libc::sa_family_t = IpFamily::V6.to_af();
+
+    #[cfg(all(feature = "v4", feature = "v6"))]
+    fn select<K: FamilyKeyed>(map: &FamilyMap<K>) -> &K::Of<Self> {
+        &map.v6
+    }
+
+    #[cfg(all(feature = "v4", feature = "v6"))]
+    fn select_mut<K: FamilyKeyed>(map: &mut FamilyMap<K>) -> &mut K::Of<Self> {
+        &mut map.v6
+    }
 }
 
+#[cfg(feature = "v6")]
 const IPV6_ADDR_BYTES: usize = 16;
 impl sealed::Sealed for Ipv6Addr {}
+#[cfg(feature = "v6")]
 impl IpFamilyAddr for Ipv6Addr {
     type Family = IpFamilyV6;
     type Raw = u128;
     type Bytes = [u8; IPV6_ADDR_BYTES];
+    type Segments = [u16; 8];
 
     const BYTES: usize = IPV6_ADDR_BYTES;
+    const MAX_DISPLAY_LEN: usize = "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff".len();
+    const MAX_PREFIX: u8 = 128;
     const LOCALHOST: Self = Self::LOCALHOST;
     const UNSPECIFIED: Self = Self::UNSPECIFIED;
 
@@ -161,6 +798,14 @@ impl IpFamilyAddr for Ipv6Addr {
         self.octets()
     }
 
+    fn segments(&self) -> Self::Segments {
+        self.segments()
+    }
+
+    fn from_segments(segments: Self::Segments) -> Self {
+        Self::from(segments)
+    }
+
     fn is_unspecified(&self) -> bool {
         self.is_unspecified()
     }
@@ -172,16 +817,76 @@ impl IpFamilyAddr for Ipv6Addr {
     fn is_multicast(&self) -> bool {
         self.is_multicast()
     }
+
+    fn is_private(&self) -> bool {
+        self.is_unique_local()
+    }
+
+    fn is_link_local(&self) -> bool {
+        self.is_unicast_link_local()
+    }
+
+    fn is_documentation(&self) -> bool {
+        classify::v6_is_documentation(*self)
+    }
+
+    fn is_benchmarking(&self) -> bool {
+        classify::v6_is_benchmarking(*self)
+    }
+
+    #[cfg(feature = "global")]
+    fn is_global(&self) -> bool {
+        classify::v6_is_global(*self)
+    }
+
+    fn try_from_ip_addr(addr: IpAddr) -> Result<Self, FamilyMismatchError> {
+        match addr {
+            IpAddr::V6(addr) => Ok(addr),
+            IpAddr::V4(_) => Err(FamilyMismatchError {
+                expected: IpFamily::V6,
+                actual: IpFamily::V4,
+            }),
+        }
+    }
+
+    fn to_ipv6_mapped(&self) -> Ipv6Addr {
+        *self
+    }
+
+    fn to_ipv4_mapped(&self) -> Option<Ipv4Addr> {
+        Ipv6Addr::to_ipv4_mapped(self)
+    }
+
+    fn to_canonical(&self) -> IpAddr {
+        Ipv6Addr::to_canonical(self)
+    }
 }
 
 impl sealed::Sealed for SocketAddrV6 {}
+#[cfg(feature = "v6")]
+const _: () = assert!(size_of::<AddrBuf<IpFamilyV6>>() == Ipv6Addr::BYTES);
+
+#[cfg(feature = "v6")]
 impl IpFamilySocketAddr for SocketAddrV6 {
     type Family = IpFamilyV6;
 
+    const MAX_DISPLAY_LEN: usize = Ipv6Addr::MAX_DISPLAY_LEN + "[%4294967295]:65535".len();
+    const LOCALHOST: Self = Self::new(Ipv6Addr::LOCALHOST, 0, 0, 0);
+    const UNSPECIFIED: Self = Self::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0);
+
     fn new(ip: <Self::Family as AnyIpFamily>::Addr, port: u16) -> Self {
         Self::new(ip, port, 0, 0)
     }
 
+    fn new_with_scope(
+        ip: <Self::Family as AnyIpFamily>::Addr,
+        port: u16,
+        flowinfo: u32,
+        scope_id: u32,
+    ) -> Self {
+        Self::new(ip, port, flowinfo, scope_id)
+    }
+
     fn ip(&self) -> <Self::Family as AnyIpFamily>::Addr {
         *self.ip()
     }
@@ -197,6 +902,51 @@ impl IpFamilySocketAddr for SocketAddrV6 {
     fn set_port(&mut self, new_port: u16) {
         self.set_port(new_port)
     }
+
+    fn try_from_socket_addr(addr: SocketAddr) -> Result<Self, FamilyMismatchError> {
+        match addr {
+            SocketAddr::V6(addr) => Ok(addr),
+            SocketAddr::V4(_) => Err(FamilyMismatchError {
+                expected: IpFamily::V6,
+                actual: IpFamily::V4,
+            }),
+        }
+    }
+
+    fn to_ipv6_mapped(&self) -> SocketAddrV6 {
+        *self
+    }
+
+    fn to_ipv4_mapped(&self) -> Option<SocketAddrV4> {
+        let ip = self.ip().to_ipv4_mapped()?;
+        Some(SocketAddrV4::new(ip, self.port()))
+    }
+
+    fn to_canonical(&self) -> SocketAddr {
+        match IpFamilySocketAddr::to_ipv4_mapped(self) {
+            Some(addr) => SocketAddr::V4(addr),
+            None => SocketAddr::V6(*self),
+        }
+    }
+}
+
+#[cfg(feature = "v6")]
+impl ScopedSocketAddr for SocketAddrV6 {
+    fn flowinfo(&self) -> u32 {
+        self.flowinfo()
+    }
+
+    fn set_flowinfo(&mut self, new_flowinfo: u32) {
+        self.set_flowinfo(new_flowinfo)
+    }
+
+    fn scope_id(&self) -> u32 {
+        self.scope_id()
+    }
+
+    fn set_scope_id(&mut self, new_scope_id: u32) {
+        self.set_scope_id(new_scope_id)
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -206,6 +956,34 @@ pub enum IpFamily {
 }
 
 impl IpFamily {
+    pub const ALL: [Self; 2] = [Self::V4, Self::V6];
+
+    /// Both families, IPv4 first.
+    pub fn iter() -> std::array::IntoIter<Self, 2> {
+        Self::ALL.into_iter()
+    }
+
+    /// 32 or 128.
+    pub const fn max_prefix(self) -> u8 {
+        match self {
+            Self::V4 => 32,
+            Self::V6 => 128,
+        }
+    }
+
+    /// The family a fallback should try next.
+    pub const fn other(self) -> Self {
+        match self {
+            Self::V4 => Self::V6,
+            Self::V6 => Self::V4,
+        }
+    }
+
+    /// Whether `addr` belongs to this family.
+    pub fn matches(&self, addr: &impl IpFamilyExt) -> bool {
+        addr.family() == *self
+    }
+
     pub const fn localhost(&self) -> IpAddr {
         match self {
             Self::V4 => IpAddr::V4(Ipv4Addr::LOCALHOST),
@@ -219,8 +997,58 @@ impl IpFamily {
             Self::V6 => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
         }
     }
+
+    pub const fn localhost_socket(&self, port: u16) -> SocketAddr {
+        match self {
+            Self::V4 => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port)),
+            Self::V6 => SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::LOCALHOST, port, 0, 0)),
+        }
+    }
+
+    /// `0.0.0.0:port` or `[::]:port`, for binding to all interfaces.
+    pub const fn unspecified_socket(&self, port: u16) -> SocketAddr {
+        match self {
+            Self::V4 => SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port)),
+            Self::V6 => SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, port, 0, 0)),
+        }
+    }
+}
+
+impl Display for IpFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::V4 => "IPv4",
+            Self::V6 => "IPv6",
+        })
+    }
 }
 
+impl Debug for IpFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+/// Accepts `4`, `v4`, `ipv4`, `inet`, `6`, `v6`, `ipv6` and `inet6`,
+/// ignoring case.
+impl FromStr for IpFamily {
+    type Err = ParseIpFamilyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const V4: &[&str] = &["4", "v4", "ipv4", "inet"];
+        const V6: &[&str] = &["6", "v6", "ipv6", "inet6"];
+        let is = |aliases: &[&str]| aliases.iter().any(|alias| s.eq_ignore_ascii_case(alias));
+        if is(V4) {
+            Ok(Self::V4)
+        } else if is(V6) {
+            Ok(Self::V6)
+        } else {
+            Err(ParseIpFamilyError(()))
+        }
+    }
+}
+
+impl sealed::Sealed for IpAddr {}
 impl AsRef<IpFamily> for IpAddr {
     fn as_ref(&self) -> &IpFamily {
         match self {
@@ -230,6 +1058,7 @@ impl AsRef<IpFamily> for IpAddr {
     }
 }
 
+impl sealed::Sealed for SocketAddr {}
 impl AsRef<IpFamily> for SocketAddr {
     fn as_ref(&self) -> &IpFamily {
         match self {
@@ -239,6 +1068,21 @@ impl AsRef<IpFamily> for SocketAddr {
     }
 }
 
+macro_rules! impl_as_ref_family {
+    ($family:expr => $($ty:ty),*) => {
+        $(
+            impl AsRef<IpFamily> for $ty {
+                fn as_ref(&self) -> &IpFamily {
+                    &$family
+                }
+            }
+        )*
+    };
+}
+
+impl_as_ref_family!(IpFamily::V4 => Ipv4Addr, SocketAddrV4);
+impl_as_ref_family!(IpFamily::V6 => Ipv6Addr, SocketAddrV6);
+
 impl<T: AsRef<IpFamily>> From<T> for IpFamily {
     fn from(value: T) -> Self {
         *value.as_ref()
@@ -254,3 +1098,87 @@ impl<T: AsRef<IpFamily>> IpFamilyExt for T {
         *self.as_ref()
     }
 }
+
+/// Reports the family of composite values, such as `(IpAddr, u16)` or
+/// `Option<SocketAddr>`.
+///
+/// With the `macros` feature it can be derived for structs and enums,
+/// delegating to the single field or the one marked `#[family]`.
+pub trait HasFamily {
+    type Output;
+
+    fn ip_family(&self) -> Self::Output;
+}
+
+macro_rules! impl_has_family {
+    ($family:expr => $($ty:ty),*) => {
+        $(
+            impl HasFamily for $ty {
+                type Output = IpFamily;
+
+                fn ip_family(&self) -> IpFamily {
+                    $family
+                }
+            }
+        )*
+    };
+}
+
+impl_has_family!(IpFamily::V4 => Ipv4Addr, SocketAddrV4);
+impl_has_family!(IpFamily::V6 => Ipv6Addr, SocketAddrV6);
+
+impl HasFamily for IpAddr {
+    type Output = IpFamily;
+
+    fn ip_family(&self) -> IpFamily {
+        self.family()
+    }
+}
+
+impl HasFamily for SocketAddr {
+    type Output = IpFamily;
+
+    fn ip_family(&self) -> IpFamily {
+        self.family()
+    }
+}
+
+impl HasFamily for IpFamily {
+    type Output = IpFamily;
+
+    fn ip_family(&self) -> IpFamily {
+        *self
+    }
+}
+
+impl<T: HasFamily + ?Sized> HasFamily for &T {
+    type Output = T::Output;
+
+    fn ip_family(&self) -> T::Output {
+        (**self).ip_family()
+    }
+}
+
+impl<T: HasFamily + ?Sized> HasFamily for Box<T> {
+    type Output = T::Output;
+
+    fn ip_family(&self) -> T::Output {
+        (**self).ip_family()
+    }
+}
+
+impl<T: HasFamily> HasFamily for Option<T> {
+    type Output = Option<T::Output>;
+
+    fn ip_family(&self) -> Self::Output {
+        self.as_ref().map(T::ip_family)
+    }
+}
+
+impl<T: HasFamily> HasFamily for (T, u16) {
+    type Output = T::Output;
+
+    fn ip_family(&self) -> T::Output {
+        self.0.ip_family()
+    }
+}
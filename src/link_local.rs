@@ -0,0 +1,88 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::{AddrGenerator, AnyIpFamily, IpFamily, IpFamilyAddr};
+
+fn mix(id: u64, attempt: u32) -> u64 {
+    AddrGenerator::new(id ^ u64::from(attempt).rotate_left(32)).next_u64()
+}
+
+/// A pseudo-random IPv4 link-local address as chosen by RFC 3927.
+///
+/// `id` should be stable per interface, such as its MAC address, so that
+/// the host picks the same address after every restart. After a conflict,
+/// retry with the next `attempt`. The result is always within
+/// `169.254.1.0` to `169.254.254.255`, the first and last /24 are reserved.
+pub fn link_local_v4(id: u64, attempt: u32) -> Ipv4Addr {
+    let host = mix(id, attempt) % (254 * 256);
+    Ipv4Addr::new(169, 254, (host / 256) as u8 + 1, host as u8)
+}
+
+/// `fe80::/64` followed by the interface identifier.
+pub const fn link_local_v6(iid: u64) -> Ipv6Addr {
+    Ipv6Addr::from_bits(0xfe80 << 112 | iid as u128)
+}
+
+/// The modified EUI-64 interface identifier of a MAC address: `ff:fe` is
+/// inserted in the middle and the universal/local bit is flipped.
+pub const fn eui64_interface_id(mac: [u8; 6]) -> u64 {
+    let [a, b, c, d, e, f] = mac;
+    u64::from_be_bytes([a ^ 0x02, b, c, 0xff, 0xfe, d, e, f])
+}
+
+/// The inverse of [`eui64_interface_id`], `None` unless the identifier has
+/// `ff:fe` in the middle.
+pub const fn mac_from_interface_id(iid: u64) -> Option<[u8; 6]> {
+    match iid.to_be_bytes() {
+        [a, b, c, 0xff, 0xfe, d, e, f] => Some([a ^ 0x02, b, c, d, e, f]),
+        _ => None,
+    }
+}
+
+/// EUI-64 addressing, implemented by [`Ipv6Addr`] alone.
+pub trait Eui64Addr: IpFamilyAddr {
+    /// `fe80::/64` with the EUI-64 interface identifier of `mac`.
+    fn link_local_from_mac(mac: [u8; 6]) -> Self;
+    /// Replaces the low 64 bits with the EUI-64 interface identifier of
+    /// `mac`.
+    fn with_mac(&self, mac: [u8; 6]) -> Self;
+    /// The low 64 bits.
+    fn interface_id(&self) -> u64;
+    /// The MAC address in an EUI-64 interface identifier.
+    fn mac(&self) -> Option<[u8; 6]>;
+}
+
+#[cfg(feature = "v6")]
+impl Eui64Addr for Ipv6Addr {
+    fn link_local_from_mac(mac: [u8; 6]) -> Self {
+        link_local_v6(eui64_interface_id(mac))
+    }
+
+    fn with_mac(&self, mac: [u8; 6]) -> Self {
+        let prefix = self.to_bits() & !u128::from(u64::MAX);
+        Ipv6Addr::from_bits(prefix | u128::from(eui64_interface_id(mac)))
+    }
+
+    fn interface_id(&self) -> u64 {
+        self.to_bits() as u64
+    }
+
+    fn mac(&self) -> Option<[u8; 6]> {
+        mac_from_interface_id(self.interface_id())
+    }
+}
+
+/// A link-local address of `F` for the interface identified by `id`.
+///
+/// IPv4 follows [`link_local_v4`]. IPv6 uses `id` as the interface
+/// identifier on the first attempt and derives a fresh one for every retry,
+/// like the `DAD_Counter` of RFC 7217.
+pub fn link_local<F: AnyIpFamily>(id: u64, attempt: u32) -> F::Addr {
+    match F::FAMILY {
+        IpFamily::V4 => F::Addr::from_byte_slice(&link_local_v4(id, attempt).octets()),
+        IpFamily::V6 => {
+            let iid = if attempt == 0 { id } else { mix(id, attempt) };
+            F::Addr::from_byte_slice(&link_local_v6(iid).octets())
+        }
+    }
+    .expect("address matches the family")
+}
@@ -0,0 +1,116 @@
+use std::{
+    ffi::{CStr, CString},
+    fmt::{self, Display, Formatter},
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddrV6},
+    ptr,
+};
+
+use crate::{AnyIpFamily, IpFamily, IpFamilyAddr};
+
+/// An address assigned to a local interface.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct LocalAddr {
+    pub interface: String,
+    pub addr: IpAddr,
+    /// The interface index for IPv6 link-local addresses, zero otherwise.
+    pub scope_id: u32,
+}
+
+/// The addresses of `family` assigned to local interfaces, in the order the
+/// OS lists them.
+pub fn local_addrs(family: IpFamily) -> io::Result<Vec<LocalAddr>> {
+    let mut head = ptr::null_mut();
+    // SAFETY: `head` is a valid out pointer; the list is freed below.
+    if unsafe { libc::getifaddrs(&mut head) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut addrs = Vec::new();
+    let mut cursor = head;
+    while !cursor.is_null() {
+        // SAFETY: `cursor` is a node of the list returned by `getifaddrs`,
+        // which stays valid until `freeifaddrs`. Its `ifa_addr` is either
+        // null or points to a `sockaddr_in` or `sockaddr_in6` as reported by
+        // `sa_family`, and `ifa_name` is a NUL-terminated string.
+        unsafe {
+            let ifa = &*cursor;
+            cursor = ifa.ifa_next;
+            if ifa.ifa_addr.is_null()
+                || IpFamily::from_af((*ifa.ifa_addr).sa_family) != Some(family)
+            {
+                continue;
+            }
+            let (addr, scope_id) = match family {
+                IpFamily::V4 => {
+                    let sin = ptr::read_unaligned(ifa.ifa_addr.cast::<libc::sockaddr_in>());
+                    let addr = Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr));
+                    (IpAddr::V4(addr), 0)
+                }
+                IpFamily::V6 => {
+                    let sin6 = ptr::read_unaligned(ifa.ifa_addr.cast::<libc::sockaddr_in6>());
+                    let addr = Ipv6Addr::from(sin6.sin6_addr.s6_addr);
+                    (IpAddr::V6(addr), sin6.sin6_scope_id)
+                }
+            };
+            addrs.push(LocalAddr {
+                interface: CStr::from_ptr(ifa.ifa_name).to_string_lossy().into_owned(),
+                addr,
+                scope_id,
+            });
+        }
+    }
+    // SAFETY: `head` came from `getifaddrs` and is not used afterwards.
+    unsafe { libc::freeifaddrs(head) };
+    Ok(addrs)
+}
+
+/// Like [`local_addrs`], typed as `F::Addr`.
+pub fn local_addrs_of<F: AnyIpFamily>() -> io::Result<Vec<F::Addr>> {
+    Ok(local_addrs(F::FAMILY)?
+        .into_iter()
+        .filter_map(|local| F::Addr::try_from_ip_addr(local.addr).ok())
+        .collect())
+}
+
+/// The index of the interface called `name`, `None` if there is none.
+pub fn interface_index(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    // SAFETY: `name` is a NUL-terminated string.
+    let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    (index != 0).then_some(index)
+}
+
+/// The name of the interface with `index`, `None` if there is none.
+pub fn interface_name(index: u32) -> Option<String> {
+    let mut buf = [0; libc::IF_NAMESIZE];
+    // SAFETY: `buf` has the `IF_NAMESIZE` bytes `if_indextoname` may write.
+    let name = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+    if name.is_null() {
+        return None;
+    }
+    // SAFETY: on success `buf` holds a NUL-terminated name.
+    Some(
+        unsafe { CStr::from_ptr(buf.as_ptr()) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+/// Renders the scope id of an IPv6 socket address as the interface name,
+/// `[fe80::1%eth0]:80`, falling back to the number if there is no such
+/// interface.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ZoneDisplay(pub SocketAddrV6);
+
+impl Display for ZoneDisplay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let addr = self.0;
+        match addr.scope_id() {
+            0 => write!(f, "[{}]:{}", addr.ip(), addr.port()),
+            scope_id => match interface_name(scope_id) {
+                Some(name) => write!(f, "[{}%{name}]:{}", addr.ip(), addr.port()),
+                None => write!(f, "[{}%{scope_id}]:{}", addr.ip(), addr.port()),
+            },
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    io,
+    net::{SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs},
+};
+
+use crate::{AnyIpFamily, IpFamily, IpFamilySocketAddr};
+
+/// Family filters on [`ToSocketAddrs`], implemented for every
+/// `ToSocketAddrs` type.
+pub trait ToSocketAddrsFamilyExt: ToSocketAddrs {
+    fn to_socket_addrs_v4(&self) -> io::Result<impl Iterator<Item = SocketAddrV4>> {
+        Ok(self.to_socket_addrs()?.filter_map(|addr| match addr {
+            SocketAddr::V4(addr) => Some(addr),
+            SocketAddr::V6(_) => None,
+        }))
+    }
+
+    fn to_socket_addrs_v6(&self) -> io::Result<impl Iterator<Item = SocketAddrV6>> {
+        Ok(self.to_socket_addrs()?.filter_map(|addr| match addr {
+            SocketAddr::V4(_) => None,
+            SocketAddr::V6(addr) => Some(addr),
+        }))
+    }
+
+    fn to_socket_addrs_family(
+        &self,
+        family: IpFamily,
+    ) -> io::Result<impl Iterator<Item = SocketAddr>> {
+        Ok(self
+            .to_socket_addrs()?
+            .filter(move |addr| family.matches(addr)))
+    }
+
+    /// Only the addresses of `F`, typed as `F::SocketAddr`.
+    fn to_socket_addrs_typed<F: AnyIpFamily>(
+        &self,
+    ) -> io::Result<impl Iterator<Item = F::SocketAddr>> {
+        Ok(self
+            .to_socket_addrs()?
+            .filter_map(|addr| F::SocketAddr::try_from_socket_addr(addr).ok()))
+    }
+}
+
+impl<T: ToSocketAddrs + ?Sized> ToSocketAddrsFamilyExt for T {}
@@ -0,0 +1,246 @@
+/// Builds an address of the given type from its octets, usable in `const`
+/// and `static` items: `from_octets!(Ipv4Addr, [192, 0, 2, 1])`.
+#[macro_export]
+macro_rules! from_octets {
+    ($addr:ty, $octets:expr $(,)?) => {
+        <<$addr as $crate::IpFamilyAddr>::Family>::from_octets($octets)
+    };
+}
+
+/// Implements `Display`, `FromStr`, conversions and (with the `serde`
+/// feature) `Serialize`/`Deserialize` for a newtype generic over the family,
+/// delegating to the wrapped `F::Addr` or `F::SocketAddr`: given
+/// `struct ServerAddr<F: AnyIpFamily>(F::SocketAddr);`, invoke
+/// `family_newtype!(ServerAddr, SocketAddr);`.
+#[macro_export]
+macro_rules! family_newtype {
+    ($name:ident, Addr) => {
+        $crate::family_newtype!(@impl $name, Addr, ::std::net::IpAddr, ::std::net::Ipv4Addr, ::std::net::Ipv6Addr);
+    };
+    ($name:ident, SocketAddr) => {
+        $crate::family_newtype!(@impl $name, SocketAddr, ::std::net::SocketAddr, ::std::net::SocketAddrV4, ::std::net::SocketAddrV6);
+    };
+    (@impl $name:ident, $assoc:ident, $any:ty, $v4:ty, $v6:ty) => {
+        impl<F: $crate::AnyIpFamily> ::std::fmt::Display for $name<F> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl<F: $crate::AnyIpFamily> ::std::str::FromStr for $name<F> {
+            type Err = <F::$assoc as ::std::str::FromStr>::Err;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                s.parse().map(Self)
+            }
+        }
+
+        impl<F: $crate::AnyIpFamily> ::std::convert::From<$name<F>> for $any {
+            fn from(value: $name<F>) -> Self {
+                value.0.into()
+            }
+        }
+
+        $crate::__cfg_v4! {
+            $crate::family_newtype!(@family $name, $crate::IpFamilyV4, $v4);
+        }
+        $crate::__cfg_v6! {
+            $crate::family_newtype!(@family $name, $crate::IpFamilyV6, $v6);
+        }
+        $crate::__family_newtype_serde!($name, $assoc);
+    };
+    (@family $name:ident, $family:ty, $inner:ty) => {
+        impl ::std::convert::From<$inner> for $name<$family> {
+            fn from(value: $inner) -> Self {
+                Self(value)
+            }
+        }
+
+        impl ::std::convert::From<$name<$family>> for $inner {
+            fn from(value: $name<$family>) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __family_newtype_serde {
+    ($name:ident, $assoc:ident) => {
+        impl<F: $crate::AnyIpFamily> $crate::__private::serde::Serialize for $name<F>
+        where
+            F::$assoc: $crate::__private::serde::Serialize,
+        {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::serde::Serializer,
+            {
+                $crate::__private::serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de, F: $crate::AnyIpFamily> $crate::__private::serde::Deserialize<'de> for $name<F>
+        where
+            F::$assoc: $crate::__private::serde::Deserialize<'de>,
+        {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: $crate::__private::serde::Deserializer<'de>,
+            {
+                $crate::__private::serde::Deserialize::deserialize(deserializer).map(Self)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __family_newtype_serde {
+    ($name:ident, $assoc:ident) => {};
+}
+
+/// Generates `#[test]` functions checking a family-generic newtype (as
+/// accepted by [`family_newtype!`]) against both families: `Display` matches
+/// the wrapped value, `FromStr` round-trips, and `Eq`/`Ord`/`Hash` agree.
+///
+/// Invoke it inside a test module: `ip_family_conformance_tests!(ServerAddr, SocketAddr);`.
+#[macro_export]
+macro_rules! ip_family_conformance_tests {
+    ($name:ident, Addr) => {
+        $crate::ip_family_conformance_tests!(@impl $name, addr_samples);
+    };
+    ($name:ident, SocketAddr) => {
+        $crate::ip_family_conformance_tests!(@impl $name, socket_addr_samples);
+    };
+    (@impl $name:ident, $samples:ident) => {
+        $crate::__cfg_v4! {
+            #[test]
+            fn conformance_v4() {
+                $crate::conformance::check_wrapper::<$name<$crate::IpFamilyV4>, _>(
+                    &$crate::conformance::$samples::<$crate::IpFamilyV4>(),
+                );
+            }
+        }
+
+        $crate::__cfg_v6! {
+            #[test]
+            fn conformance_v6() {
+                $crate::conformance::check_wrapper::<$name<$crate::IpFamilyV6>, _>(
+                    &$crate::conformance::$samples::<$crate::IpFamilyV6>(),
+                );
+            }
+        }
+    };
+}
+
+/// Runs `body` once per enabled family with `F` naming the marker type,
+/// `IpFamilyV4` first: `for_each_family!(|F| { bind::<F>()?; })`.
+///
+/// `F` is a type alias, not a type parameter, so associated items need the
+/// qualified form `<F as AnyIpFamily>::Addr`.
+#[macro_export]
+macro_rules! for_each_family {
+    (|$family:ident| $body:block) => {{
+        $crate::__cfg_v4! {{
+            type $family = $crate::IpFamilyV4;
+            $body;
+        }}
+        $crate::__cfg_v6! {{
+            type $family = $crate::IpFamilyV6;
+            $body;
+        }}
+    }};
+}
+
+#[cfg(feature = "v4")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_v4 {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "v4"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_v4 {
+    ($($item:tt)*) => {};
+}
+
+#[cfg(feature = "v6")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_v6 {
+    ($($item:tt)*) => { $($item)* };
+}
+
+#[cfg(not(feature = "v6"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __cfg_v6 {
+    ($($item:tt)*) => {};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::AnyIpFamily;
+
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+    struct HostAddr<F: AnyIpFamily>(F::Addr);
+
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+    struct ServerAddr<F: AnyIpFamily>(F::SocketAddr);
+
+    crate::family_newtype!(HostAddr, Addr);
+    crate::family_newtype!(ServerAddr, SocketAddr);
+
+    #[cfg(any(feature = "v4", feature = "v6"))]
+    mod addr {
+        use super::HostAddr;
+
+        crate::ip_family_conformance_tests!(HostAddr, Addr);
+    }
+
+    #[cfg(any(feature = "v4", feature = "v6"))]
+    mod socket_addr {
+        use super::ServerAddr;
+
+        crate::ip_family_conformance_tests!(ServerAddr, SocketAddr);
+    }
+
+    #[cfg(feature = "v4")]
+    #[test]
+    fn from_octets_builds_v4_consts() {
+        const ADDR: std::net::Ipv4Addr = crate::from_octets!(std::net::Ipv4Addr, [192, 0, 2, 1]);
+        assert_eq!(ADDR, crate::fixtures::V4_ADDR);
+    }
+
+    #[cfg(feature = "v6")]
+    #[test]
+    fn from_octets_builds_v6_consts() {
+        const ADDR: std::net::Ipv6Addr = crate::from_octets!(
+            std::net::Ipv6Addr,
+            [0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+        assert_eq!(ADDR, crate::fixtures::V6_ADDR);
+    }
+
+    #[test]
+    fn for_each_family_visits_enabled_families_in_order() {
+        // Nothing is pushed without any family enabled.
+        #[cfg_attr(not(any(feature = "v4", feature = "v6")), allow(unused_mut))]
+        let mut families: Vec<crate::IpFamily> = Vec::new();
+        crate::for_each_family!(|F| {
+            families.push(<F as AnyIpFamily>::FAMILY);
+        });
+        let expected: Vec<_> = crate::IpFamily::iter()
+            .filter(|family| match family {
+                crate::IpFamily::V4 => cfg!(feature = "v4"),
+                crate::IpFamily::V6 => cfg!(feature = "v6"),
+            })
+            .collect();
+        assert_eq!(families, expected);
+    }
+}
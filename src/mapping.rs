@@ -0,0 +1,50 @@
+use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
+
+use crate::{MappingError, Nat64Prefix};
+
+/// How an IPv4 socket address is represented inside IPv6.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MappingPolicy {
+    /// IPv4-mapped addresses, `::ffff:a.b.c.d`.
+    Mapped,
+    /// The IPv4 address embedded under an RFC 6052 prefix.
+    Nat64(Nat64Prefix),
+    /// Conversions between the socket types are refused.
+    Fail,
+}
+
+impl MappingPolicy {
+    /// The well-known NAT64 prefix `64:ff9b::/96`.
+    pub const WELL_KNOWN_NAT64: Self = Self::Nat64(Nat64Prefix::WELL_KNOWN);
+
+    /// The port is kept, flowinfo and scope id are zero.
+    pub fn to_v6(self, addr: SocketAddrV4) -> Result<SocketAddrV6, MappingError> {
+        let ip = match self {
+            Self::Mapped => addr.ip().to_ipv6_mapped(),
+            Self::Nat64(prefix) => prefix.embed(*addr.ip()),
+            Self::Fail => return Err(MappingError::Refused),
+        };
+        Ok(SocketAddrV6::new(ip, addr.port(), 0, 0))
+    }
+
+    /// The port is kept, flowinfo and scope id have no IPv4 equivalent and
+    /// are dropped.
+    pub fn to_v4(self, addr: SocketAddrV6) -> Result<SocketAddrV4, MappingError> {
+        let ip = match self {
+            Self::Mapped => addr.ip().to_ipv4_mapped(),
+            Self::Nat64(prefix) => prefix.extract(*addr.ip()),
+            Self::Fail => return Err(MappingError::Refused),
+        };
+        ip.map(|ip| SocketAddrV4::new(ip, addr.port()))
+            .ok_or(MappingError::NotEmbedded(*addr.ip()))
+    }
+
+    /// Unmaps IPv6 peers carrying an IPv4 address under this policy and
+    /// leaves every other address untouched.
+    pub fn normalize(self, addr: SocketAddr) -> SocketAddr {
+        match addr {
+            SocketAddr::V6(v6) => self.to_v4(v6).map_or(addr, SocketAddr::V4),
+            SocketAddr::V4(_) => addr,
+        }
+    }
+}
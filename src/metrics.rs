@@ -0,0 +1,256 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::{IpFamily, PerFamily};
+
+/// Upper bounds of the latency histogram buckets, in seconds. Slower
+/// observations land in a final overflow bucket.
+pub const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Default)]
+struct Counters {
+    attempts: AtomicU64,
+    successes: AtomicU64,
+    failures: AtomicU64,
+    latency_micros: AtomicU64,
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+}
+
+impl Counters {
+    fn observe(&self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.latency_micros.fetch_add(micros, Ordering::Relaxed);
+        let seconds = latency.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|&bound| seconds <= bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> CountersSnapshot {
+        CountersSnapshot {
+            attempts: self.attempts.load(Ordering::Relaxed),
+            successes: self.successes.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            latency_sum: Duration::from_micros(self.latency_micros.load(Ordering::Relaxed)),
+            latency_buckets: self
+                .latency_buckets
+                .each_ref()
+                .map(|bucket| bucket.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Point-in-time copy of one family's counters.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct CountersSnapshot {
+    pub attempts: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub latency_sum: Duration,
+    /// Observations per bucket of [`LATENCY_BUCKETS`] (not cumulative), plus
+    /// the overflow bucket.
+    pub latency_buckets: [u64; LATENCY_BUCKETS.len() + 1],
+}
+
+impl CountersSnapshot {
+    pub fn latency_count(&self) -> u64 {
+        self.latency_buckets.iter().sum()
+    }
+}
+
+/// Thread-safe attempt/success/failure counters and latency histograms per
+/// family.
+#[derive(Debug, Default)]
+pub struct FamilyCounters {
+    counters: PerFamily<Counters>,
+}
+
+impl FamilyCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn counters(&self, family: IpFamily) -> &Counters {
+        &self.counters[family]
+    }
+
+    pub fn record_attempt(&self, family: IpFamily) {
+        self.counters(family)
+            .attempts
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_success(&self, family: IpFamily, latency: Duration) {
+        let counters = self.counters(family);
+        counters.successes.fetch_add(1, Ordering::Relaxed);
+        counters.observe(latency);
+    }
+
+    pub fn record_failure(&self, family: IpFamily, latency: Duration) {
+        let counters = self.counters(family);
+        counters.failures.fetch_add(1, Ordering::Relaxed);
+        counters.observe(latency);
+    }
+
+    pub fn snapshot(&self, family: IpFamily) -> CountersSnapshot {
+        self.counters(family).snapshot()
+    }
+}
+
+#[cfg(feature = "prometheus-client")]
+mod prometheus {
+    use prometheus_client::{
+        collector::Collector,
+        encoding::{DescriptorEncoder, NoLabelSet},
+        metrics::MetricType,
+    };
+
+    use super::{CountersSnapshot, FamilyCounters, LATENCY_BUCKETS};
+    use crate::IpFamily;
+
+    fn label(family: IpFamily) -> [(&'static str, &'static str); 1] {
+        match family {
+            IpFamily::V4 => [("family", "ipv4")],
+            IpFamily::V6 => [("family", "ipv6")],
+        }
+    }
+
+    fn encode_counter(
+        encoder: &mut DescriptorEncoder,
+        name: &str,
+        help: &str,
+        snapshots: &[(IpFamily, CountersSnapshot)],
+        value: fn(&CountersSnapshot) -> u64,
+    ) -> Result<(), std::fmt::Error> {
+        let mut metric = encoder.encode_descriptor(name, help, None, MetricType::Counter)?;
+        for (family, snapshot) in snapshots {
+            let labels = label(*family);
+            metric
+                .encode_family(&labels)?
+                .encode_counter::<NoLabelSet, _, u64>(&value(snapshot), None)?;
+        }
+        Ok(())
+    }
+
+    /// Exposes the counters as `ip_family_attempts`, `ip_family_successes`,
+    /// `ip_family_failures` and the `ip_family_latency_seconds` histogram,
+    /// labelled with `family="ipv4"|"ipv6"`.
+    impl Collector for FamilyCounters {
+        fn encode(&self, mut encoder: DescriptorEncoder) -> Result<(), std::fmt::Error> {
+            let snapshots =
+                [IpFamily::V4, IpFamily::V6].map(|family| (family, self.snapshot(family)));
+            encode_counter(
+                &mut encoder,
+                "ip_family_attempts",
+                "Attempts per IP family",
+                &snapshots,
+                |s| s.attempts,
+            )?;
+            encode_counter(
+                &mut encoder,
+                "ip_family_successes",
+                "Successes per IP family",
+                &snapshots,
+                |s| s.successes,
+            )?;
+            encode_counter(
+                &mut encoder,
+                "ip_family_failures",
+                "Failures per IP family",
+                &snapshots,
+                |s| s.failures,
+            )?;
+
+            let mut metric = encoder.encode_descriptor(
+                "ip_family_latency_seconds",
+                "Latency per IP family",
+                None,
+                MetricType::Histogram,
+            )?;
+            for (family, snapshot) in &snapshots {
+                let buckets: Vec<(f64, u64)> = LATENCY_BUCKETS
+                    .iter()
+                    .copied()
+                    .chain([f64::MAX])
+                    .zip(snapshot.latency_buckets)
+                    .collect();
+                let labels = label(*family);
+                metric
+                    .encode_family(&labels)?
+                    .encode_histogram::<NoLabelSet>(
+                        snapshot.latency_sum.as_secs_f64(),
+                        snapshot.latency_count(),
+                        &buckets,
+                        None,
+                    )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_per_family() {
+        let counters = FamilyCounters::new();
+        counters.record_attempt(IpFamily::V6);
+        counters.record_attempt(IpFamily::V6);
+        counters.record_failure(IpFamily::V6, Duration::from_millis(3));
+        counters.record_success(IpFamily::V6, Duration::from_secs(20));
+        let v6 = counters.snapshot(IpFamily::V6);
+        assert_eq!((v6.attempts, v6.successes, v6.failures), (2, 1, 1));
+        assert_eq!(v6.latency_sum, Duration::from_micros(20_003_000));
+        // 3 ms lands in the 5 ms bucket, 20 s in the overflow bucket.
+        assert_eq!(v6.latency_buckets[1], 1);
+        assert_eq!(v6.latency_buckets[LATENCY_BUCKETS.len()], 1);
+        assert_eq!(v6.latency_count(), 2);
+        assert_eq!(counters.snapshot(IpFamily::V4), CountersSnapshot::default());
+    }
+
+    #[test]
+    fn bucket_bounds_are_inclusive() {
+        let counters = FamilyCounters::new();
+        counters.record_success(IpFamily::V4, Duration::from_millis(1));
+        assert_eq!(counters.snapshot(IpFamily::V4).latency_buckets[0], 1);
+    }
+
+    #[cfg(feature = "prometheus-client")]
+    #[test]
+    fn exports_counts() {
+        use prometheus_client::{encoding::text::encode, registry::Registry};
+
+        let counters = FamilyCounters::new();
+        counters.record_attempt(IpFamily::V4);
+        counters.record_attempt(IpFamily::V4);
+        counters.record_success(IpFamily::V4, Duration::from_millis(2));
+        counters.record_failure(IpFamily::V4, Duration::from_millis(2));
+        counters.record_attempt(IpFamily::V6);
+        let mut registry = Registry::default();
+        registry.register_collector(Box::new(counters));
+        let mut text = String::new();
+        encode(&mut text, &registry).unwrap();
+        for line in [
+            "ip_family_attempts_total{family=\"ipv4\"} 2",
+            "ip_family_attempts_total{family=\"ipv6\"} 1",
+            "ip_family_successes_total{family=\"ipv4\"} 1",
+            "ip_family_failures_total{family=\"ipv4\"} 1",
+            "ip_family_failures_total{family=\"ipv6\"} 0",
+            "ip_family_latency_seconds_count{family=\"ipv4\"} 2",
+            "ip_family_latency_seconds_bucket{le=\"0.005\",family=\"ipv4\"} 2",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "{line:?} missing from\n{text}"
+            );
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[cfg(not(feature = "tokio"))]
+use std::{
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    thread,
+    time::Instant,
+};
+#[cfg(feature = "tokio")]
+use tokio::time::sleep;
+
+use crate::{IpFamily, PerFamily, Resolver};
+
+#[derive(Clone, Debug, Default)]
+struct Answer {
+    addrs: Vec<IpAddr>,
+    error: Option<io::ErrorKind>,
+    delay: Duration,
+}
+
+/// A [`Resolver`] with scripted answers, for testing Happy Eyeballs and
+/// failover logic without DNS. Requires the `test-util` feature.
+///
+/// Every host and family has its own addresses, error and delay. Hosts
+/// without any script fail with [`io::ErrorKind::NotFound`]; the other
+/// family of a scripted host answers with no addresses.
+#[derive(Clone, Debug, Default)]
+pub struct MockResolver {
+    answers: HashMap<String, PerFamily<Answer>>,
+    queries: Arc<Mutex<Vec<(String, IpFamily)>>>,
+}
+
+impl MockResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `addrs` to the answer for `host` and `family`. Addresses of the
+    /// other family are returned as well, as a misbehaving resolver might.
+    pub fn with_answer<I>(mut self, host: &str, family: IpFamily, addrs: I) -> Self
+    where
+        I: IntoIterator<Item = IpAddr>,
+    {
+        self.answer_mut(host, family).addrs.extend(addrs);
+        self
+    }
+
+    /// Fails the query for `host` and `family` with `kind`, after the delay.
+    pub fn with_error(mut self, host: &str, family: IpFamily, kind: io::ErrorKind) -> Self {
+        self.answer_mut(host, family).error = Some(kind);
+        self
+    }
+
+    /// Delays the answer or error for `host` and `family`. With the `tokio`
+    /// feature the delay is a tokio timer and needs a runtime with time
+    /// enabled.
+    pub fn with_delay(mut self, host: &str, family: IpFamily, delay: Duration) -> Self {
+        self.answer_mut(host, family).delay = delay;
+        self
+    }
+
+    /// Every query so far, in the order they were made. Clones share the
+    /// log.
+    pub fn queries(&self) -> Vec<(String, IpFamily)> {
+        self.queries.lock().unwrap().clone()
+    }
+
+    fn answer_mut(&mut self, host: &str, family: IpFamily) -> &mut Answer {
+        self.answers
+            .entry(host.to_owned())
+            .or_default()
+            .get_mut(family)
+    }
+}
+
+impl Resolver for MockResolver {
+    fn lookup(
+        &self,
+        host: &str,
+        family: IpFamily,
+    ) -> impl Future<Output = io::Result<Vec<IpAddr>>> + Send {
+        self.queries.lock().unwrap().push((host.to_owned(), family));
+        let answer = self
+            .answers
+            .get(host)
+            .map(|answers| answers.get(family).clone());
+        let host = host.to_owned();
+        async move {
+            let Some(answer) = answer else {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no answer scripted for {host}"),
+                ));
+            };
+            if !answer.delay.is_zero() {
+                sleep(answer.delay).await;
+            }
+            match answer.error {
+                Some(kind) => Err(io::Error::new(kind, format!("scripted {family} error"))),
+                None => Ok(answer.addrs),
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+fn sleep(delay: Duration) -> Delay {
+    Delay::new(delay)
+}
+
+/// A timer that works under any executor, woken from a helper thread.
+#[cfg(not(feature = "tokio"))]
+struct Delay {
+    deadline: Instant,
+    waker: Option<Arc<Mutex<Waker>>>,
+}
+
+#[cfg(not(feature = "tokio"))]
+impl Delay {
+    fn new(delay: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + delay,
+            waker: None,
+        }
+    }
+}
+
+#[cfg(not(feature = "tokio"))]
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Poll::Ready(());
+        }
+        match &self.waker {
+            // The task may have moved since the thread was started.
+            Some(waker) => waker.lock().unwrap().clone_from(cx.waker()),
+            None => {
+                let waker = Arc::new(Mutex::new(cx.waker().clone()));
+                self.waker = Some(waker.clone());
+                thread::spawn(move || {
+                    thread::sleep(remaining);
+                    waker.lock().unwrap().wake_by_ref();
+                });
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::fixtures::{V4_ADDR, V6_ADDR};
+
+    #[tokio::test]
+    async fn scripted_answers_and_errors() {
+        let resolver = MockResolver::new()
+            .with_answer("example.com", IpFamily::V4, [V4_ADDR.into()])
+            .with_error("example.com", IpFamily::V6, io::ErrorKind::TimedOut);
+        assert_eq!(
+            resolver.lookup("example.com", IpFamily::V4).await.unwrap(),
+            [IpAddr::from(V4_ADDR)]
+        );
+        let err = resolver
+            .lookup("example.com", IpFamily::V6)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        let err = resolver
+            .lookup("unknown.test", IpFamily::V4)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(
+            resolver.queries(),
+            [
+                ("example.com".to_owned(), IpFamily::V4),
+                ("example.com".to_owned(), IpFamily::V6),
+                ("unknown.test".to_owned(), IpFamily::V4),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn unscripted_family_answers_empty() {
+        let resolver = MockResolver::new().with_answer("v6.test", IpFamily::V6, [V6_ADDR.into()]);
+        assert!(resolver
+            .lookup("v6.test", IpFamily::V4)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn delays_answers() {
+        let resolver = MockResolver::new()
+            .with_answer("slow.test", IpFamily::V4, [V4_ADDR.into()])
+            .with_delay("slow.test", IpFamily::V4, Duration::from_millis(50));
+        let start = Instant::now();
+        resolver.lookup("slow.test", IpFamily::V4).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,488 @@
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, UdpSocket},
+};
+
+use crate::{IpFamilyAddr, IpPrefix, MulticastError};
+
+/// Well-known groups, scopes and membership of multicast addresses,
+/// implemented by [`Ipv4Addr`] and [`Ipv6Addr`].
+pub trait MulticastGroup: IpFamilyAddr {
+    /// The local address of the interface for IPv4, the interface index for
+    /// IPv6.
+    type Interface: Copy;
+
+    /// Lets the OS pick the interface.
+    const ANY_INTERFACE: Self::Interface;
+
+    /// `224.0.0.1` or `ff02::1`.
+    const ALL_NODES: Self;
+    /// `224.0.0.2` or `ff02::2`.
+    const ALL_ROUTERS: Self;
+    /// `224.0.0.251` or `ff02::fb` (RFC 6762).
+    const MDNS: Self;
+    /// `239.255.255.250` or `ff02::c`.
+    const SSDP: Self;
+
+    /// `None` for unicast addresses and reserved IPv6 scopes.
+    ///
+    /// IPv4 follows RFC 2365: `224.0.0.0/24` is link-local,
+    /// `239.255.0.0/16` site-local, `239.192.0.0/14` organization-local,
+    /// the rest of `239.0.0.0/8` admin-local and everything else global.
+    fn multicast_scope(&self) -> Option<MulticastScope>;
+
+    fn join(&self, socket: &UdpSocket, interface: Self::Interface) -> io::Result<()>;
+    fn leave(&self, socket: &UdpSocket, interface: Self::Interface) -> io::Result<()>;
+}
+
+#[cfg(feature = "v4")]
+impl MulticastGroup for Ipv4Addr {
+    type Interface = Ipv4Addr;
+
+    const ANY_INTERFACE: Ipv4Addr = Ipv4Addr::UNSPECIFIED;
+
+    const ALL_NODES: Self = Ipv4Addr::new(224, 0, 0, 1);
+    const ALL_ROUTERS: Self = Ipv4Addr::new(224, 0, 0, 2);
+    const MDNS: Self = Ipv4Addr::new(224, 0, 0, 251);
+    const SSDP: Self = Ipv4Addr::new(239, 255, 255, 250);
+
+    fn multicast_scope(&self) -> Option<MulticastScope> {
+        v4_scope(*self)
+    }
+
+    fn join(&self, socket: &UdpSocket, interface: Ipv4Addr) -> io::Result<()> {
+        socket.join_multicast_v4(self, &interface)
+    }
+
+    fn leave(&self, socket: &UdpSocket, interface: Ipv4Addr) -> io::Result<()> {
+        socket.leave_multicast_v4(self, &interface)
+    }
+}
+
+#[cfg(feature = "v6")]
+impl MulticastGroup for Ipv6Addr {
+    type Interface = u32;
+
+    const ANY_INTERFACE: u32 = 0;
+
+    const ALL_NODES: Self = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1);
+    const ALL_ROUTERS: Self = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 2);
+    const MDNS: Self = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb);
+    const SSDP: Self = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xc);
+
+    fn multicast_scope(&self) -> Option<MulticastScope> {
+        v6_scope(*self)
+    }
+
+    fn join(&self, socket: &UdpSocket, interface: u32) -> io::Result<()> {
+        socket.join_multicast_v6(self, interface)
+    }
+
+    fn leave(&self, socket: &UdpSocket, interface: u32) -> io::Result<()> {
+        socket.leave_multicast_v6(self, interface)
+    }
+}
+
+/// See [`MulticastGroup::multicast_scope`].
+pub(crate) fn v4_scope(addr: Ipv4Addr) -> Option<MulticastScope> {
+    if !addr.is_multicast() {
+        return None;
+    }
+    Some(match addr.octets() {
+        [224, 0, 0, _] => MulticastScope::LinkLocal,
+        [239, 255, ..] => MulticastScope::SiteLocal,
+        [239, 192..=195, ..] => MulticastScope::OrganizationLocal,
+        [239, ..] => MulticastScope::AdminLocal,
+        _ => MulticastScope::Global,
+    })
+}
+
+/// See [`MulticastGroup::multicast_scope`].
+pub(crate) fn v6_scope(addr: Ipv6Addr) -> Option<MulticastScope> {
+    if !addr.is_multicast() {
+        return None;
+    }
+    MulticastScope::try_from(addr.octets()[1] & 0xf).ok()
+}
+
+/// The 4-bit scope field of an IPv6 multicast address (RFC 7346).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum MulticastScope {
+    InterfaceLocal,
+    LinkLocal,
+    RealmLocal,
+    AdminLocal,
+    SiteLocal,
+    OrganizationLocal,
+    Global,
+    /// Scopes 6, 7 and 9 to D, available to administrators.
+    Unassigned(u8),
+}
+
+impl MulticastScope {
+    pub const fn bits(self) -> u8 {
+        match self {
+            Self::InterfaceLocal => 0x1,
+            Self::LinkLocal => 0x2,
+            Self::RealmLocal => 0x3,
+            Self::AdminLocal => 0x4,
+            Self::SiteLocal => 0x5,
+            Self::OrganizationLocal => 0x8,
+            Self::Global => 0xe,
+            Self::Unassigned(bits) => bits,
+        }
+    }
+}
+
+/// Scopes 0 and F are reserved.
+impl TryFrom<u8> for MulticastScope {
+    type Error = MulticastError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x1 => Self::InterfaceLocal,
+            0x2 => Self::LinkLocal,
+            0x3 => Self::RealmLocal,
+            0x4 => Self::AdminLocal,
+            0x5 => Self::SiteLocal,
+            0x8 => Self::OrganizationLocal,
+            0xe => Self::Global,
+            0x6 | 0x7 | 0x9..=0xd => Self::Unassigned(value),
+            _ => return Err(MulticastError::ReservedScope(value)),
+        })
+    }
+}
+
+/// The 4-bit flags field of an IPv6 multicast address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MulticastFlags {
+    /// `T`: not a permanently assigned group.
+    pub transient: bool,
+    /// `P`: derived from a unicast prefix (RFC 3306).
+    pub prefix: bool,
+    /// `R`: embeds a rendezvous point address (RFC 3956).
+    pub rendezvous: bool,
+}
+
+impl MulticastFlags {
+    pub const fn bits(self) -> u8 {
+        (self.rendezvous as u8) << 2 | (self.prefix as u8) << 1 | self.transient as u8
+    }
+
+    /// The high bit is reserved, `P` requires `T` and `R` requires `P`.
+    pub const fn from_bits(bits: u8) -> Result<Self, MulticastError> {
+        let flags = Self {
+            transient: bits & 0x1 != 0,
+            prefix: bits & 0x2 != 0,
+            rendezvous: bits & 0x4 != 0,
+        };
+        if bits & !0x7 != 0
+            || (flags.prefix && !flags.transient)
+            || (flags.rendezvous && !flags.prefix)
+        {
+            return Err(MulticastError::ReservedFlags(bits));
+        }
+        Ok(flags)
+    }
+}
+
+/// The parts of an `ffXS::/16` IPv6 multicast address.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MulticastV6 {
+    pub flags: MulticastFlags,
+    pub scope: MulticastScope,
+    /// The low 112 bits of the address.
+    pub group_id: u128,
+}
+
+impl MulticastV6 {
+    pub const GROUP_ID_BITS: u32 = 112;
+
+    pub const fn new(scope: MulticastScope, group_id: u128) -> Self {
+        Self {
+            flags: MulticastFlags {
+                transient: false,
+                prefix: false,
+                rendezvous: false,
+            },
+            scope,
+            group_id,
+        }
+    }
+
+    pub const fn with_flags(mut self, flags: MulticastFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn build(&self) -> Result<Ipv6Addr, MulticastError> {
+        MulticastFlags::from_bits(self.flags.bits())?;
+        MulticastScope::try_from(self.scope.bits())?;
+        if self.group_id >> Self::GROUP_ID_BITS != 0 {
+            return Err(MulticastError::GroupIdTooLarge);
+        }
+        let head = 0xff00 | u16::from(self.flags.bits()) << 4 | u16::from(self.scope.bits());
+        Ok(Ipv6Addr::from_bits(
+            u128::from(head) << Self::GROUP_ID_BITS | self.group_id,
+        ))
+    }
+}
+
+/// Unicast-prefix-based addresses (RFC 3306).
+impl MulticastV6 {
+    /// Derives `ff3S:00LL:<prefix>:<group id>` from a unicast prefix of at
+    /// most 64 bits.
+    pub fn from_unicast_prefix(
+        scope: MulticastScope,
+        prefix: IpPrefix,
+        group_id: u32,
+    ) -> Result<Self, MulticastError> {
+        let IpAddr::V6(addr) = prefix.addr() else {
+            return Err(MulticastError::InvalidPrefix);
+        };
+        if prefix.len() > 64 {
+            return Err(MulticastError::InvalidPrefix);
+        }
+        let network = addr.to_bits() >> 64;
+        Ok(Self {
+            flags: MulticastFlags {
+                transient: true,
+                prefix: true,
+                rendezvous: false,
+            },
+            scope,
+            group_id: u128::from(prefix.len()) << 96 | network << 32 | u128::from(group_id),
+        })
+    }
+
+    /// The unicast prefix and 32-bit group id, if the `P` flag is set.
+    pub fn unicast_prefix(&self) -> Option<(IpPrefix, u32)> {
+        if !self.flags.prefix {
+            return None;
+        }
+        // With the `R` flag the low nibble of the reserved byte is the RIID.
+        let reserved =
+            (self.group_id >> 104) as u8 & if self.flags.rendezvous { 0xf0 } else { 0xff };
+        let len = (self.group_id >> 96) as u8;
+        if reserved != 0 || len > 64 {
+            return None;
+        }
+        let network = (self.group_id >> 32) as u64;
+        let addr = Ipv6Addr::from_bits(u128::from(network) << 64);
+        let prefix = IpPrefix::new(IpAddr::V6(addr), len);
+        // Bits past the prefix length must be zero.
+        (prefix.addr() == IpAddr::V6(addr)).then_some((prefix, self.group_id as u32))
+    }
+}
+
+/// Embedded-RP addresses (RFC 3956).
+impl MulticastV6 {
+    /// Embeds a rendezvous point whose address is the first `prefix_len`
+    /// bits of its network, zeros, and a 4-bit RIID as the last nibble.
+    pub fn from_rendezvous_point(
+        scope: MulticastScope,
+        rp: Ipv6Addr,
+        prefix_len: u8,
+        group_id: u32,
+    ) -> Result<Self, MulticastError> {
+        let riid = rp.to_bits() as u8 & 0xf;
+        let prefix = IpPrefix::new(IpAddr::V6(rp), prefix_len);
+        let network = Ipv6Addr::from_bits(rp.to_bits() & !0xf);
+        if prefix_len == 0 || prefix.addr() != IpAddr::V6(network) {
+            return Err(MulticastError::InvalidPrefix);
+        }
+        let mut addr = Self::from_unicast_prefix(scope, prefix, group_id)?;
+        addr.flags.rendezvous = true;
+        addr.group_id |= u128::from(riid) << 104;
+        Ok(addr)
+    }
+
+    /// The rendezvous point address and its RIID, if the `R` flag is set.
+    pub fn rendezvous_point(&self) -> Option<(Ipv6Addr, u8)> {
+        if !self.flags.rendezvous {
+            return None;
+        }
+        let (prefix, _) = self
+            .unicast_prefix()
+            .filter(|(prefix, _)| prefix.len() != 0)?;
+        let IpAddr::V6(network) = prefix.addr() else {
+            return None;
+        };
+        let riid = (self.group_id >> 104) as u8 & 0xf;
+        Some((
+            Ipv6Addr::from_bits(network.to_bits() | u128::from(riid)),
+            riid,
+        ))
+    }
+}
+
+impl TryFrom<Ipv6Addr> for MulticastV6 {
+    type Error = MulticastError;
+
+    fn try_from(value: Ipv6Addr) -> Result<Self, Self::Error> {
+        let [head, ..] = value.segments();
+        if head >> 8 != 0xff {
+            return Err(MulticastError::NotMulticast);
+        }
+        Ok(Self {
+            flags: MulticastFlags::from_bits((head >> 4) as u8 & 0xf)?,
+            scope: MulticastScope::try_from(head as u8 & 0xf)?,
+            group_id: value.to_bits() & ((1 << Self::GROUP_ID_BITS) - 1),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefix(s: &str) -> IpPrefix {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn unicast_prefix_based_rfc3306_example() {
+        let group = MulticastV6::from_unicast_prefix(
+            MulticastScope::Global,
+            prefix("3ffe:ffff:1::/48"),
+            0x12345678,
+        )
+        .unwrap();
+        let addr = group.build().unwrap();
+        assert_eq!(
+            addr,
+            "ff3e:30:3ffe:ffff:1::1234:5678"
+                .parse::<Ipv6Addr>()
+                .unwrap()
+        );
+        let parsed = MulticastV6::try_from(addr).unwrap();
+        assert_eq!(parsed, group);
+        assert_eq!(
+            parsed.unicast_prefix(),
+            Some((prefix("3ffe:ffff:1::/48"), 0x12345678))
+        );
+    }
+
+    #[test]
+    fn unicast_prefix_of_zero_and_full_length() {
+        let group =
+            MulticastV6::from_unicast_prefix(MulticastScope::SiteLocal, prefix("::/0"), 1).unwrap();
+        assert_eq!(
+            group.build().unwrap(),
+            "ff35::1".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(group.unicast_prefix(), Some((prefix("::/0"), 1)));
+
+        let group = MulticastV6::from_unicast_prefix(
+            MulticastScope::Global,
+            prefix("2001:db8:1:2::/64"),
+            u32::MAX,
+        )
+        .unwrap();
+        assert_eq!(
+            group.build().unwrap(),
+            "ff3e:40:2001:db8:1:2:ffff:ffff"
+                .parse::<Ipv6Addr>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn unicast_prefix_rejects_long_and_v4_prefixes() {
+        for prefix in [
+            prefix("2001:db8::/65"),
+            prefix("2001:db8::1/128"),
+            prefix("10.0.0.0/8"),
+        ] {
+            assert_eq!(
+                MulticastV6::from_unicast_prefix(MulticastScope::Global, prefix, 1),
+                Err(MulticastError::InvalidPrefix)
+            );
+        }
+    }
+
+    #[test]
+    fn unicast_prefix_needs_clean_fields() {
+        // No `P` flag.
+        let plain: MulticastV6 = "ff1e::1234"
+            .parse::<Ipv6Addr>()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(plain.unicast_prefix(), None);
+        // A non-zero reserved byte, a length over 64 and bits past the length.
+        for addr in [
+            "ff3e:130:3ffe:ffff:1::1",
+            "ff3e:41:3ffe:ffff:1::1",
+            "ff3e:10:3ffe:ffff:1::1",
+        ] {
+            let group: MulticastV6 = addr.parse::<Ipv6Addr>().unwrap().try_into().unwrap();
+            assert_eq!(group.unicast_prefix(), None, "{addr}");
+        }
+    }
+
+    #[test]
+    fn embedded_rp_rfc3956_example() {
+        let rp: Ipv6Addr = "2001:db8:beef:feed::7".parse().unwrap();
+        let group =
+            MulticastV6::from_rendezvous_point(MulticastScope::Global, rp, 64, 0x1234).unwrap();
+        let addr = group.build().unwrap();
+        assert_eq!(
+            addr,
+            "ff7e:740:2001:db8:beef:feed:0:1234"
+                .parse::<Ipv6Addr>()
+                .unwrap()
+        );
+        let parsed = MulticastV6::try_from(addr).unwrap();
+        assert!(parsed.flags.rendezvous);
+        assert_eq!(parsed.rendezvous_point(), Some((rp, 7)));
+        // The RIID does not hide the unicast prefix.
+        assert_eq!(
+            parsed.unicast_prefix(),
+            Some((prefix("2001:db8:beef:feed::/64"), 0x1234))
+        );
+    }
+
+    #[test]
+    fn embedded_rp_with_short_prefix_and_zero_riid() {
+        let rp: Ipv6Addr = "2001:db8::".parse().unwrap();
+        let group =
+            MulticastV6::from_rendezvous_point(MulticastScope::SiteLocal, rp, 32, 1).unwrap();
+        assert_eq!(
+            group.build().unwrap(),
+            "ff75:20:2001:db8::1".parse::<Ipv6Addr>().unwrap()
+        );
+        assert_eq!(group.rendezvous_point(), Some((rp, 0)));
+    }
+
+    #[test]
+    fn embedded_rp_rejects_unembeddable_addresses() {
+        let reject = |rp: &str, len| {
+            MulticastV6::from_rendezvous_point(MulticastScope::Global, rp.parse().unwrap(), len, 1)
+        };
+        // Only the RIID may follow the prefix.
+        assert_eq!(
+            reject("2001:db8::1:7", 64),
+            Err(MulticastError::InvalidPrefix)
+        );
+        assert_eq!(reject("2001:db8::7", 0), Err(MulticastError::InvalidPrefix));
+        assert_eq!(
+            reject("2001:db8::7", 65),
+            Err(MulticastError::InvalidPrefix)
+        );
+    }
+
+    #[test]
+    fn rendezvous_point_needs_the_r_flag_and_a_prefix() {
+        let group =
+            MulticastV6::from_unicast_prefix(MulticastScope::Global, prefix("2001:db8::/32"), 1)
+                .unwrap();
+        assert_eq!(group.rendezvous_point(), None);
+        let zero_len: MulticastV6 = "ff7e:700::1"
+            .parse::<Ipv6Addr>()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(zero_len.rendezvous_point(), None);
+    }
+}
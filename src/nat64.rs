@@ -0,0 +1,167 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use crate::Nat64PrefixError;
+
+/// Octet 8, bits 64 to 71, is always zero in RFC 6052 addresses.
+const U_OCTET: usize = 8;
+
+/// An RFC 6052 prefix for embedding IPv4 addresses in IPv6.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Nat64Prefix {
+    prefix: Ipv6Addr,
+    len: u8,
+}
+
+impl Nat64Prefix {
+    /// `64:ff9b::/96`.
+    pub const WELL_KNOWN: Self = Self {
+        prefix: Ipv6Addr::new(0x64, 0xff9b, 0, 0, 0, 0, 0, 0),
+        len: 96,
+    };
+
+    pub fn new(prefix: Ipv6Addr, len: u8) -> Result<Self, Nat64PrefixError> {
+        if !matches!(len, 32 | 40 | 48 | 56 | 64 | 96) {
+            return Err(Nat64PrefixError::Len(len));
+        }
+        if prefix.to_bits() & !mask(len) != 0 {
+            return Err(Nat64PrefixError::HostBits);
+        }
+        if prefix.octets()[U_OCTET] != 0 {
+            return Err(Nat64PrefixError::ReservedOctet);
+        }
+        Ok(Self { prefix, len })
+    }
+
+    pub const fn prefix(&self) -> Ipv6Addr {
+        self.prefix
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    pub const fn len(&self) -> u8 {
+        self.len
+    }
+
+    /// The octets of an address under the prefix that carry the IPv4
+    /// address.
+    fn v4_octets(&self) -> impl Iterator<Item = usize> {
+        (usize::from(self.len / 8)..)
+            .filter(|&i| i != U_OCTET)
+            .take(4)
+    }
+
+    /// The suffix is zero.
+    pub fn embed(&self, addr: Ipv4Addr) -> Ipv6Addr {
+        let mut octets = self.prefix.octets();
+        for (i, octet) in self.v4_octets().zip(addr.octets()) {
+            octets[i] = octet;
+        }
+        Ipv6Addr::from(octets)
+    }
+
+    /// Returns `None` unless `addr` is under the prefix. The suffix is
+    /// ignored.
+    pub fn extract(&self, addr: Ipv6Addr) -> Option<Ipv4Addr> {
+        let octets = addr.octets();
+        if addr.to_bits() & mask(self.len) != self.prefix.to_bits() || octets[U_OCTET] != 0 {
+            return None;
+        }
+        let mut v4 = [0; 4];
+        for (octet, i) in v4.iter_mut().zip(self.v4_octets()) {
+            *octet = octets[i];
+        }
+        Some(Ipv4Addr::from(v4))
+    }
+}
+
+impl Display for Nat64Prefix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.prefix, self.len)
+    }
+}
+
+fn mask(len: u8) -> u128 {
+    u128::MAX.checked_shl(128 - u32::from(len)).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: Ipv4Addr = Ipv4Addr::new(192, 0, 2, 33);
+
+    fn prefix(addr: &str, len: u8) -> Nat64Prefix {
+        Nat64Prefix::new(addr.parse().unwrap(), len).unwrap()
+    }
+
+    #[test]
+    fn rfc6052_examples() {
+        // RFC 6052, section 2.4: every prefix length, skipping octet 8.
+        let cases = [
+            ("2001:db8::", 32, "2001:db8:c000:221::"),
+            ("2001:db8:100::", 40, "2001:db8:1c0:2:21::"),
+            ("2001:db8:122::", 48, "2001:db8:122:c000:2:2100::"),
+            ("2001:db8:122:300::", 56, "2001:db8:122:3c0:0:221::"),
+            ("2001:db8:122:344::", 64, "2001:db8:122:344:c0:2:2100:0"),
+            ("2001:db8:122:344::", 96, "2001:db8:122:344::192.0.2.33"),
+        ];
+        for (addr, len, embedded) in cases {
+            let prefix = prefix(addr, len);
+            let embedded: Ipv6Addr = embedded.parse().unwrap();
+            assert_eq!(prefix.embed(CLIENT), embedded, "{prefix}");
+            assert_eq!(prefix.extract(embedded), Some(CLIENT), "{prefix}");
+        }
+    }
+
+    #[test]
+    fn well_known_prefix() {
+        let addr = Nat64Prefix::WELL_KNOWN.embed(CLIENT);
+        assert_eq!(addr, "64:ff9b::192.0.2.33".parse::<Ipv6Addr>().unwrap());
+        assert_eq!(Nat64Prefix::WELL_KNOWN.to_string(), "64:ff9b::/96");
+        assert_eq!(Nat64Prefix::WELL_KNOWN, prefix("64:ff9b::", 96));
+    }
+
+    #[test]
+    fn extract_ignores_the_suffix_but_not_octet_8() {
+        let prefix = prefix("2001:db8::", 32);
+        let with_suffix: Ipv6Addr = "2001:db8:c000:221:00ff:ffff:ffff:ffff".parse().unwrap();
+        assert_eq!(prefix.extract(with_suffix), Some(CLIENT));
+        let reserved: Ipv6Addr = "2001:db8:c000:221:100::".parse().unwrap();
+        assert_eq!(prefix.extract(reserved), None);
+        let elsewhere: Ipv6Addr = "2001:db9:c000:221::".parse().unwrap();
+        assert_eq!(prefix.extract(elsewhere), None);
+    }
+
+    #[test]
+    fn embeds_edge_addresses() {
+        for len in [32, 40, 48, 56, 64, 96] {
+            let prefix = prefix("2001:db8::", len);
+            for addr in [Ipv4Addr::UNSPECIFIED, Ipv4Addr::BROADCAST] {
+                let embedded = prefix.embed(addr);
+                assert_eq!(embedded.octets()[U_OCTET], 0, "{prefix}");
+                assert_eq!(prefix.extract(embedded), Some(addr), "{prefix}");
+            }
+        }
+    }
+
+    #[test]
+    fn new_rejects_invalid_prefixes() {
+        let addr = |s: &str| s.parse::<Ipv6Addr>().unwrap();
+        for len in [0, 31, 33, 72, 128] {
+            assert_eq!(
+                Nat64Prefix::new(addr("2001:db8::"), len),
+                Err(Nat64PrefixError::Len(len))
+            );
+        }
+        assert_eq!(
+            Nat64Prefix::new(addr("2001:db8::1"), 32),
+            Err(Nat64PrefixError::HostBits)
+        );
+        assert_eq!(
+            Nat64Prefix::new(addr("2001:db8::100:0:0:0"), 96),
+            Err(Nat64PrefixError::ReservedOctet)
+        );
+    }
+}
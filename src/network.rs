@@ -0,0 +1,519 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    marker::PhantomData,
+    net::IpAddr,
+    str::FromStr,
+};
+
+use crate::{
+    arith, AnyIpFamily, BroadcastAddr, HasFamily, IpFamily, IpFamilyAddr, IpPrefix,
+    ParsePrefixError,
+};
+
+fn width<F: AnyIpFamily>() -> u32 {
+    F::Addr::BYTES as u32 * 8
+}
+
+fn to_u128<F: AnyIpFamily>(addr: F::Addr) -> u128 {
+    arith::to_u128(addr)
+}
+
+fn from_u128<F: AnyIpFamily>(bits: u128) -> F::Addr {
+    arith::from_u128(bits).expect("bits fit the family")
+}
+
+fn host_mask<F: AnyIpFamily>(len: u8) -> u128 {
+    (u128::MAX >> (128 - width::<F>()))
+        .checked_shr(u32::from(len))
+        .unwrap_or(0)
+}
+
+/// A CIDR network of one family, such as `10.0.0.0/8` or `2001:db8::/32`.
+///
+/// Host bits are always zero.
+pub struct Network<F: AnyIpFamily> {
+    addr: F::Addr,
+    len: u8,
+}
+
+impl<F: AnyIpFamily> Network<F> {
+    /// Host bits of `addr` are cleared and `len` is clamped to the width of
+    /// the family.
+    pub fn new(addr: F::Addr, len: u8) -> Self {
+        let len = len.min(width::<F>() as u8);
+        Self {
+            addr: from_u128::<F>(to_u128::<F>(addr) & !host_mask::<F>(len)),
+            len,
+        }
+    }
+
+    pub fn network(&self) -> F::Addr {
+        self.addr
+    }
+
+    // A network is never empty, `len` is a bit count.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u8 {
+        self.len
+    }
+
+    pub fn last(&self) -> F::Addr {
+        from_u128::<F>(to_u128::<F>(self.addr) | host_mask::<F>(self.len))
+    }
+
+    /// Same as [`last`](Self::last), for families with broadcast.
+    pub fn broadcast(&self) -> F::Addr
+    where
+        F::Addr: BroadcastAddr,
+    {
+        self.last()
+    }
+
+    pub fn contains(&self, addr: F::Addr) -> bool {
+        to_u128::<F>(addr) & !host_mask::<F>(self.len) == to_u128::<F>(self.addr)
+    }
+
+    /// Whether every address of `other` is in `self`.
+    pub fn covers(&self, other: &Self) -> bool {
+        other.len >= self.len && self.contains(other.addr)
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.contains(other.addr) || other.contains(self.addr)
+    }
+
+    /// The usable host addresses. IPv4 networks up to /30 exclude the
+    /// network and broadcast addresses, /31 and /32 and every IPv6 network
+    /// include all addresses.
+    pub fn hosts(&self) -> Hosts<F> {
+        let (mut first, mut last) = (to_u128::<F>(self.addr), to_u128::<F>(self.last()));
+        if width::<F>() == 32 && self.len < 31 {
+            first += 1;
+            last -= 1;
+        }
+        Hosts::new(first, last)
+    }
+}
+
+// Implemented by hand so generic code needs no `F: Copy` etc. bounds.
+impl<F: AnyIpFamily> Clone for Network<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: AnyIpFamily> Copy for Network<F> {}
+
+impl<F: AnyIpFamily> PartialEq for Network<F> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.addr, self.len) == (other.addr, other.len)
+    }
+}
+
+impl<F: AnyIpFamily> Eq for Network<F> {}
+
+impl<F: AnyIpFamily> PartialOrd for Network<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: AnyIpFamily> Ord for Network<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.addr, self.len).cmp(&(other.addr, other.len))
+    }
+}
+
+impl<F: AnyIpFamily> Hash for Network<F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.addr, self.len).hash(state)
+    }
+}
+
+impl<F: AnyIpFamily> Debug for Network<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Network({self})")
+    }
+}
+
+impl<F: AnyIpFamily> Display for Network<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.len)
+    }
+}
+
+/// Parses `addr/len`, or a bare address as a host network. Host bits must
+/// be zero.
+impl<F: AnyIpFamily> FromStr for Network<F> {
+    type Err = ParsePrefixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        IpPrefix::from_str(s)?
+            .try_into()
+            .map_err(|_| ParsePrefixError::Family)
+    }
+}
+
+impl<F: AnyIpFamily> From<Network<F>> for IpPrefix {
+    fn from(value: Network<F>) -> Self {
+        IpPrefix::new(value.addr.into(), value.len)
+    }
+}
+
+/// Fails if the prefix belongs to the other family.
+impl<F: AnyIpFamily> TryFrom<IpPrefix> for Network<F> {
+    type Error = IpPrefix;
+
+    fn try_from(value: IpPrefix) -> Result<Self, Self::Error> {
+        let addr: IpAddr = value.addr();
+        match F::Addr::try_from_ip_addr(addr) {
+            Ok(addr) => Ok(Self::new(addr, value.len())),
+            Err(_) => Err(value),
+        }
+    }
+}
+
+/// A network of either family, for mixed collections.
+///
+/// A thin wrapper around [`IpPrefix`], whose order and containment it
+/// shares: IPv4 first, then by address, then by prefix length, so sorting
+/// puts a network right before the networks it covers that start at the
+/// same address.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct AnyIpNet(IpPrefix);
+
+impl AnyIpNet {
+    pub fn network(&self) -> IpAddr {
+        self.0.addr()
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u8 {
+        self.0.len()
+    }
+
+    /// Addresses of the other family are never contained.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        self.0.contains(addr)
+    }
+
+    /// Whether every address of `other` is in `self`.
+    pub fn covers(&self, other: &Self) -> bool {
+        self.0.covers(&other.0)
+    }
+
+    /// The network as family `F`, `None` if it belongs to the other family.
+    pub fn to_network<F: AnyIpFamily>(&self) -> Option<Network<F>> {
+        self.0.try_into().ok()
+    }
+}
+
+impl HasFamily for AnyIpNet {
+    type Output = IpFamily;
+
+    fn ip_family(&self) -> IpFamily {
+        self.0.ip_family()
+    }
+}
+
+impl Display for AnyIpNet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// Like [`Network`], accepts `addr/len` or a bare address.
+impl FromStr for AnyIpNet {
+    type Err = ParsePrefixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        IpPrefix::from_str(s).map(Self)
+    }
+}
+
+impl<F: AnyIpFamily> From<Network<F>> for AnyIpNet {
+    fn from(value: Network<F>) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<IpPrefix> for AnyIpNet {
+    fn from(value: IpPrefix) -> Self {
+        Self(value)
+    }
+}
+
+impl From<AnyIpNet> for IpPrefix {
+    fn from(value: AnyIpNet) -> Self {
+        value.0
+    }
+}
+
+/// Iterator returned by [`Network::hosts`] and over an
+/// [`AddrRange`](crate::AddrRange).
+pub struct Hosts<F: AnyIpFamily> {
+    next: Option<u128>,
+    last: u128,
+    family: PhantomData<F>,
+}
+
+impl<F: AnyIpFamily> Hosts<F> {
+    /// Yields `first` to `last` inclusive, nothing if `first > last`.
+    pub(crate) fn new(first: u128, last: u128) -> Self {
+        Self {
+            next: (first <= last).then_some(first),
+            last,
+            family: PhantomData,
+        }
+    }
+}
+
+impl<F: AnyIpFamily> Clone for Hosts<F> {
+    fn clone(&self) -> Self {
+        Self {
+            next: self.next,
+            last: self.last,
+            family: PhantomData,
+        }
+    }
+}
+
+impl<F: AnyIpFamily> Debug for Hosts<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hosts")
+            .field("next", &self.next.map(from_u128::<F>))
+            .field("last", &from_u128::<F>(self.last))
+            .finish()
+    }
+}
+
+impl<F: AnyIpFamily> Iterator for Hosts<F> {
+    type Item = F::Addr;
+
+    fn next(&mut self) -> Option<F::Addr> {
+        let next = self.next?;
+        self.next = (next < self.last).then(|| next + 1);
+        Some(from_u128::<F>(next))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.next {
+            Some(next) => {
+                let len = usize::try_from(self.last - next)
+                    .ok()
+                    .and_then(|len| len.checked_add(1));
+                (len.unwrap_or(usize::MAX), len)
+            }
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl<F: AnyIpFamily> FusedIterator for Hosts<F> {}
+
+#[cfg(all(test, feature = "v4", feature = "v6"))]
+mod tests {
+    use super::*;
+    use crate::{IpFamilyV4, IpFamilyV6};
+
+    type V4Net = Network<IpFamilyV4>;
+    type V6Net = Network<IpFamilyV6>;
+
+    #[test]
+    fn new_clears_host_bits_and_clamps_len() {
+        let net = V4Net::new("10.1.2.3".parse().unwrap(), 8);
+        assert_eq!(net.to_string(), "10.0.0.0/8");
+        assert_eq!(
+            net.last(),
+            "10.255.255.255".parse::<std::net::Ipv4Addr>().unwrap()
+        );
+        assert_eq!(net.broadcast(), net.last());
+        let net = V6Net::new("2001:db8::1".parse().unwrap(), 200);
+        assert_eq!(net.len(), 128);
+        assert_eq!(net.to_string(), "2001:db8::1/128");
+    }
+
+    #[test]
+    fn zero_and_full_length_networks() {
+        let all = V6Net::new("2001:db8::1".parse().unwrap(), 0);
+        assert_eq!(all.to_string(), "::/0");
+        assert_eq!(all.last(), std::net::Ipv6Addr::from(u128::MAX));
+        assert!(all.contains(std::net::Ipv6Addr::UNSPECIFIED));
+        assert!(all.contains(u128::MAX.into()));
+
+        let host: V6Net = "2001:db8::1/128".parse().unwrap();
+        assert_eq!(host.network(), host.last());
+        assert!(host.contains("2001:db8::1".parse().unwrap()));
+        assert!(!host.contains("2001:db8::2".parse().unwrap()));
+        assert!(all.covers(&host));
+        assert!(!host.covers(&all));
+        assert!(host.overlaps(&all) && all.overlaps(&host));
+
+        let all = V4Net::new(crate::fixtures::V4_ADDR, 0);
+        assert_eq!(all.last(), std::net::Ipv4Addr::BROADCAST);
+        assert!(all.contains(std::net::Ipv4Addr::UNSPECIFIED));
+    }
+
+    #[test]
+    fn disjoint_networks_do_not_overlap() {
+        let a: V4Net = "192.0.2.0/25".parse().unwrap();
+        let b: V4Net = "192.0.2.128/25".parse().unwrap();
+        let parent: V4Net = "192.0.2.0/24".parse().unwrap();
+        assert!(!a.overlaps(&b));
+        assert!(parent.covers(&a) && parent.covers(&b));
+        assert!(!a.covers(&parent));
+    }
+
+    #[test]
+    fn hosts_excludes_network_and_broadcast_for_small_v4_networks() {
+        let hosts = |net: &str| {
+            net.parse::<V4Net>()
+                .unwrap()
+                .hosts()
+                .map(|addr| addr.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hosts("192.0.2.0/30"), ["192.0.2.1", "192.0.2.2"]);
+        assert_eq!(hosts("192.0.2.0/31"), ["192.0.2.0", "192.0.2.1"]);
+        assert_eq!(hosts("192.0.2.1/32"), ["192.0.2.1"]);
+        assert_eq!(
+            "2001:db8::/127"
+                .parse::<V6Net>()
+                .unwrap()
+                .hosts()
+                .map(|addr| addr.to_string())
+                .collect::<Vec<_>>(),
+            ["2001:db8::", "2001:db8::1"]
+        );
+    }
+
+    #[test]
+    fn hosts_of_the_whole_v6_space() {
+        let mut hosts = V6Net::new(std::net::Ipv6Addr::UNSPECIFIED, 0).hosts();
+        assert_eq!(hosts.size_hint(), (usize::MAX, None));
+        assert_eq!(hosts.next(), Some(std::net::Ipv6Addr::UNSPECIFIED));
+
+        let mut last = V6Net::new(u128::MAX.into(), 128).hosts();
+        assert_eq!(last.size_hint(), (1, Some(1)));
+        assert_eq!(last.next(), Some(u128::MAX.into()));
+        assert_eq!(last.next(), None);
+        assert_eq!(last.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn parse_rejects_host_bits_and_the_other_family() {
+        assert_eq!(
+            "10.1.2.3/8".parse::<V4Net>(),
+            Err(ParsePrefixError::HostBits)
+        );
+        assert_eq!(
+            "2001:db8::/32".parse::<V4Net>(),
+            Err(ParsePrefixError::Family)
+        );
+        assert_eq!(
+            "192.0.2.1".parse::<V4Net>().unwrap().to_string(),
+            "192.0.2.1/32"
+        );
+        let prefix = IpPrefix::new("10.0.0.0".parse().unwrap(), 8);
+        assert!(V6Net::try_from(prefix).is_err());
+        assert_eq!(IpPrefix::from(V4Net::try_from(prefix).unwrap()), prefix);
+    }
+
+    fn nets(list: &[&str]) -> Vec<AnyIpNet> {
+        list.iter().map(|net| net.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn any_ip_net_sorts_by_family_address_and_length() {
+        let mut list = nets(&[
+            "2001:db8::/32",
+            "10.0.0.0/16",
+            "::/0",
+            "10.0.0.0/8",
+            "0.0.0.0/0",
+            "2001:db8::/48",
+            "9.0.0.0/8",
+        ]);
+        list.sort();
+        assert_eq!(
+            list,
+            nets(&[
+                "0.0.0.0/0",
+                "9.0.0.0/8",
+                "10.0.0.0/8",
+                "10.0.0.0/16",
+                "::/0",
+                "2001:db8::/32",
+                "2001:db8::/48",
+            ])
+        );
+    }
+
+    #[test]
+    fn any_ip_net_dedups_mixed_collections() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let mut list = nets(&["10.0.0.0/8", "::1/128", "::1", "192.0.2.0/24"]);
+        // Host bits are cleared, so this is `10.0.0.0/8` again.
+        list.push(IpPrefix::new("10.1.2.3".parse().unwrap(), 8).into());
+        let mut sorted = list.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, nets(&["10.0.0.0/8", "192.0.2.0/24", "::1/128"]));
+        assert_eq!(list.iter().collect::<HashSet<_>>().len(), 3);
+        assert_eq!(
+            list.into_iter()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            sorted
+        );
+    }
+
+    #[test]
+    fn any_ip_net_converts() {
+        let net: AnyIpNet = "2001:db8::/32".parse().unwrap();
+        assert_eq!(net.ip_family(), IpFamily::V6);
+        assert_eq!(net.to_string(), "2001:db8::/32");
+        assert!(net.contains("2001:db8::1".parse().unwrap()));
+        assert!(!net.contains("32.1.13.184".parse().unwrap()));
+        assert_eq!(AnyIpNet::from(IpPrefix::from(net)), net);
+        assert_eq!(
+            net.to_network::<IpFamilyV6>(),
+            Some("2001:db8::/32".parse().unwrap())
+        );
+        assert_eq!(net.to_network::<IpFamilyV4>(), None);
+        let v4: V4Net = "10.0.0.0/8".parse().unwrap();
+        assert_eq!(AnyIpNet::from(v4).to_network(), Some(v4));
+    }
+
+    #[test]
+    fn any_ip_net_agrees_with_ip_prefix() {
+        let list = nets(&[
+            "::/0",
+            "10.0.0.0/8",
+            "10.0.0.0/16",
+            "2001:db8::/32",
+            "0.0.0.0/0",
+        ]);
+        let mut by_net = list.clone();
+        by_net.sort();
+        let mut by_prefix: Vec<IpPrefix> = list.iter().copied().map(IpPrefix::from).collect();
+        by_prefix.sort();
+        assert_eq!(
+            by_net.into_iter().map(IpPrefix::from).collect::<Vec<_>>(),
+            by_prefix
+        );
+        for net in &list {
+            for other in &list {
+                assert_eq!(
+                    net.covers(other),
+                    IpPrefix::from(*net).covers(&(*other).into())
+                );
+            }
+        }
+    }
+}
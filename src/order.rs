@@ -0,0 +1,287 @@
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+};
+
+/// Orders `IpAddr` values by their IPv6 form, so IPv4 addresses sort next to
+/// their `::ffff:a.b.c.d` mapped counterparts instead of before every IPv6
+/// address.
+///
+/// Equality is unchanged: `1.2.3.4` and `::ffff:1.2.3.4` stay distinct, with
+/// the IPv4 address ordered first.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MappedOrd(pub IpAddr);
+
+impl MappedOrd {
+    fn key(&self) -> (Ipv6Addr, bool) {
+        match self.0 {
+            IpAddr::V4(addr) => (addr.to_ipv6_mapped(), false),
+            IpAddr::V6(addr) => (addr, true),
+        }
+    }
+}
+
+impl PartialOrd for MappedOrd {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MappedOrd {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl From<IpAddr> for MappedOrd {
+    fn from(value: IpAddr) -> Self {
+        Self(value)
+    }
+}
+
+impl From<MappedOrd> for IpAddr {
+    fn from(value: MappedOrd) -> Self {
+        value.0
+    }
+}
+
+/// Compares, orders and hashes `IpAddr` values by their canonical form, so
+/// `::ffff:1.2.3.4` equals `1.2.3.4`, for matching dual-stack peers against
+/// IPv4 allowlists.
+#[derive(Clone, Copy, Debug)]
+pub struct Canonical(pub IpAddr);
+
+impl PartialEq for Canonical {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_canonical() == other.0.to_canonical()
+    }
+}
+
+impl Eq for Canonical {}
+
+impl PartialOrd for Canonical {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Canonical {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.to_canonical().cmp(&other.0.to_canonical())
+    }
+}
+
+impl Hash for Canonical {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_canonical().hash(state)
+    }
+}
+
+impl From<IpAddr> for Canonical {
+    fn from(value: IpAddr) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Canonical> for IpAddr {
+    fn from(value: Canonical) -> Self {
+        value.0
+    }
+}
+
+/// Socket address types comparable by address and port only.
+pub trait AddrPort: crate::sealed::Sealed + Copy {
+    fn addr_port(&self) -> (IpAddr, u16);
+}
+
+impl AddrPort for SocketAddrV4 {
+    fn addr_port(&self) -> (IpAddr, u16) {
+        (IpAddr::V4(*self.ip()), self.port())
+    }
+}
+
+impl AddrPort for SocketAddrV6 {
+    fn addr_port(&self) -> (IpAddr, u16) {
+        (IpAddr::V6(*self.ip()), self.port())
+    }
+}
+
+impl AddrPort for SocketAddr {
+    fn addr_port(&self) -> (IpAddr, u16) {
+        (self.ip(), self.port())
+    }
+}
+
+/// Compares, orders and hashes socket addresses by address and port,
+/// ignoring the IPv6 `flowinfo` and `scope_id` fields.
+#[derive(Clone, Copy, Debug)]
+pub struct IgnoreMeta<T>(pub T);
+
+impl<T: AddrPort> PartialEq for IgnoreMeta<T> {
+    fn eq(&self, other: &Self) -> bool {
+        eq_ignore_meta(&self.0, &other.0)
+    }
+}
+
+impl<T: AddrPort> Eq for IgnoreMeta<T> {}
+
+impl<T: AddrPort> PartialOrd for IgnoreMeta<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: AddrPort> Ord for IgnoreMeta<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.addr_port().cmp(&other.0.addr_port())
+    }
+}
+
+impl<T: AddrPort> Hash for IgnoreMeta<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.addr_port().hash(state)
+    }
+}
+
+pub fn eq_ignore_meta<T: AddrPort>(a: &T, b: &T) -> bool {
+    a.addr_port() == b.addr_port()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, net::Ipv4Addr};
+
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn mapped_ord_sorts_ipv4_next_to_its_mapped_form() {
+        let mut addrs: Vec<MappedOrd> = [
+            "2001:db8::1",
+            "::ffff:10.0.0.2",
+            "10.0.0.1",
+            "::1",
+            "10.0.0.2",
+            "fe80::1",
+            "192.0.2.1",
+        ]
+        .into_iter()
+        .map(|s| MappedOrd(ip(s)))
+        .collect();
+        addrs.sort();
+        let sorted: Vec<String> = addrs.iter().map(|addr| addr.0.to_string()).collect();
+        assert_eq!(
+            sorted,
+            [
+                "::1",
+                "10.0.0.1",
+                "10.0.0.2",
+                "::ffff:10.0.0.2",
+                "192.0.2.1",
+                "2001:db8::1",
+                "fe80::1"
+            ]
+        );
+    }
+
+    #[test]
+    fn mapped_ord_is_a_total_order() {
+        let addrs: Vec<MappedOrd> = [
+            "0.0.0.0",
+            "::",
+            "::ffff:0.0.0.0",
+            "10.0.0.1",
+            "::ffff:a00:1",
+            "ff02::1",
+        ]
+        .into_iter()
+        .map(|s| MappedOrd(ip(s)))
+        .collect();
+        for a in &addrs {
+            for b in &addrs {
+                assert_eq!(a.cmp(b), b.cmp(a).reverse(), "{a:?} {b:?}");
+                assert_eq!(a.cmp(b) == Ordering::Equal, a == b, "{a:?} {b:?}");
+                for c in &addrs {
+                    if a < b && b < c {
+                        assert!(a < c, "{a:?} {b:?} {c:?}");
+                    }
+                }
+            }
+        }
+        assert!(MappedOrd(ip("10.0.0.1")) < MappedOrd(ip("::ffff:10.0.0.1")));
+        assert_eq!(
+            IpAddr::from(MappedOrd::from(IpAddr::V4(Ipv4Addr::LOCALHOST))),
+            ip("127.0.0.1")
+        );
+    }
+
+    #[test]
+    fn ignore_meta_skips_flowinfo_and_scope_id() {
+        let addr: Ipv6Addr = "fe80::1".parse().unwrap();
+        let plain = SocketAddrV6::new(addr, 443, 0, 0);
+        let scoped = SocketAddrV6::new(addr, 443, 7, 2);
+        assert_ne!(plain, scoped);
+        assert!(eq_ignore_meta(&plain, &scoped));
+        assert!(eq_ignore_meta(
+            &SocketAddr::V6(plain),
+            &SocketAddr::V6(scoped)
+        ));
+        assert!(!eq_ignore_meta(&plain, &SocketAddrV6::new(addr, 80, 0, 0)));
+        assert_eq!(IgnoreMeta(plain), IgnoreMeta(scoped));
+        assert_eq!(IgnoreMeta(plain).cmp(&IgnoreMeta(scoped)), Ordering::Equal);
+        let set: HashSet<_> = [IgnoreMeta(plain), IgnoreMeta(scoped)].into();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn ignore_meta_orders_by_address_then_port() {
+        let a = SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 1), 443);
+        let b = SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 1), 8443);
+        let c = SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 2), 80);
+        assert!(IgnoreMeta(a) < IgnoreMeta(b));
+        assert!(IgnoreMeta(b) < IgnoreMeta(c));
+        assert!(eq_ignore_meta(&a, &a));
+        assert!(!eq_ignore_meta(&a, &b));
+    }
+
+    #[test]
+    fn canonical_unmaps_ipv4() {
+        let v4 = ip("192.0.2.1");
+        let mapped = ip("::ffff:192.0.2.1");
+        assert_eq!(Canonical(v4), Canonical(mapped));
+        assert_ne!(Canonical(v4), Canonical(ip("::192.0.2.1")));
+        assert_eq!(Canonical(v4).cmp(&Canonical(mapped)), Ordering::Equal);
+        assert!(Canonical(mapped) < Canonical(ip("::1")));
+        let set: HashSet<_> = [Canonical(v4), Canonical(mapped)].into();
+        assert_eq!(set.len(), 1);
+        assert_eq!(IpAddr::from(Canonical::from(mapped)), mapped);
+    }
+
+    #[cfg(feature = "v4")]
+    #[test]
+    fn eq_canonical_matches_mapped_peers() {
+        use crate::IpFamilyAddr;
+
+        let v4 = Ipv4Addr::new(192, 0, 2, 1);
+        assert!(v4.eq_canonical(&ip("::ffff:192.0.2.1")));
+        assert!(v4.eq_canonical(&IpAddr::V4(v4)));
+        assert!(!v4.eq_canonical(&ip("::ffff:192.0.2.2")));
+        assert!(!v4.eq_canonical(&ip("::192.0.2.1")));
+    }
+
+    #[cfg(feature = "v6")]
+    #[test]
+    fn eq_canonical_of_ipv6_addresses() {
+        use crate::IpFamilyAddr;
+
+        let mapped = Ipv4Addr::new(192, 0, 2, 1).to_ipv6_mapped();
+        assert!(mapped.eq_canonical(&ip("192.0.2.1")));
+        let v6: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        assert!(v6.eq_canonical(&IpAddr::V6(v6)));
+        assert!(!v6.eq_canonical(&ip("2001:db8::2")));
+    }
+}
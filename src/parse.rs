@@ -0,0 +1,236 @@
+use std::net::{AddrParseError, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+
+use crate::{
+    FamilyMismatchError, IpFamily, IpFamilyExt, ParseOrMismatchError, ParseSocketAddrError,
+};
+
+impl IpFamily {
+    /// Parses an address and fails if it belongs to the other family.
+    pub fn parse_addr(&self, s: &str) -> Result<IpAddr, ParseOrMismatchError<AddrParseError>> {
+        let addr: IpAddr = s.parse().map_err(ParseOrMismatchError::Parse)?;
+        self.check(addr.family())?;
+        Ok(addr)
+    }
+
+    /// Like [`parse_socket_addr_with_family`], failing if the address
+    /// belongs to the other family.
+    pub fn parse_socket_addr(
+        &self,
+        s: &str,
+    ) -> Result<SocketAddr, ParseOrMismatchError<ParseSocketAddrError>> {
+        let (family, addr) =
+            parse_socket_addr_with_family(s).map_err(ParseOrMismatchError::Parse)?;
+        self.check(family)?;
+        Ok(addr)
+    }
+
+    fn check<E>(&self, actual: IpFamily) -> Result<(), ParseOrMismatchError<E>> {
+        if actual == *self {
+            Ok(())
+        } else {
+            Err(ParseOrMismatchError::Mismatch(FamilyMismatchError {
+                expected: *self,
+                actual,
+            }))
+        }
+    }
+}
+
+/// Parses `1.2.3.4:80` or `[::1]:80`, including a scope id such as
+/// `[fe80::1%2]:80`, and reports the family. With the `local-addrs` feature
+/// the scope can also name an interface, `[fe80::1%eth0]:80`.
+pub fn parse_socket_addr_with_family(
+    s: &str,
+) -> Result<(IpFamily, SocketAddr), ParseSocketAddrError> {
+    parse(s, None)
+}
+
+/// Like [`parse_socket_addr_with_family`], also accepting addresses without
+/// a port, `1.2.3.4`, `::1`, `fe80::1%eth0` or `[::1]`, which get
+/// `default_port`.
+///
+/// IPv6 addresses without brackets never carry a port: `::1:80` is the
+/// address `::1:80`.
+pub fn parse_socket_addr_or_default(
+    s: &str,
+    default_port: u16,
+) -> Result<(IpFamily, SocketAddr), ParseSocketAddrError> {
+    parse(s, Some(default_port))
+}
+
+fn parse(
+    s: &str,
+    default_port: Option<u16>,
+) -> Result<(IpFamily, SocketAddr), ParseSocketAddrError> {
+    if s.is_empty() {
+        return Err(ParseSocketAddrError::Empty);
+    }
+    let (ip, scope_id, port) = if let Some(rest) = s.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or(ParseSocketAddrError::UnclosedBracket)?;
+        let (ip, scope_id) = parse_scoped(host)?;
+        let port = match rest {
+            "" => None,
+            _ => Some(
+                rest.strip_prefix(':')
+                    .ok_or(ParseSocketAddrError::TrailingCharacters)?,
+            ),
+        };
+        (IpAddr::V6(ip), scope_id, port)
+    } else if s.matches(':').count() > 1 {
+        let (ip, scope_id) = parse_scoped(s)?;
+        (IpAddr::V6(ip), scope_id, None)
+    } else {
+        let (host, port) = match s.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (s, None),
+        };
+        let ip = host
+            .parse::<Ipv4Addr>()
+            .map_err(|_| ParseSocketAddrError::InvalidAddr)?;
+        (IpAddr::V4(ip), 0, port)
+    };
+    let port = match port {
+        Some(port) => port
+            .parse()
+            .map_err(|_| ParseSocketAddrError::InvalidPort)?,
+        None => default_port.ok_or(ParseSocketAddrError::MissingPort)?,
+    };
+    let addr = match ip {
+        IpAddr::V4(_) => SocketAddr::new(ip, port),
+        IpAddr::V6(ip) => SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id)),
+    };
+    Ok((addr.family(), addr))
+}
+
+/// `fe80::1%2` or `fe80::1%eth0`; an address without `%` gets scope id 0.
+fn parse_scoped(s: &str) -> Result<(Ipv6Addr, u32), ParseSocketAddrError> {
+    let (host, scope_id) = match s.split_once('%') {
+        Some((host, zone)) => (host, parse_zone(zone)?),
+        None => (s, 0),
+    };
+    let ip = host
+        .parse()
+        .map_err(|_| ParseSocketAddrError::InvalidAddr)?;
+    Ok((ip, scope_id))
+}
+
+fn parse_zone(zone: &str) -> Result<u32, ParseSocketAddrError> {
+    if let Ok(scope_id) = zone.parse() {
+        return Ok(scope_id);
+    }
+    #[cfg(all(unix, feature = "local-addrs"))]
+    if let Some(index) = crate::local_addrs::interface_index(zone) {
+        return Ok(index);
+    }
+    Err(ParseSocketAddrError::InvalidScope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(s: &str) -> Result<(IpFamily, String), ParseSocketAddrError> {
+        parse_socket_addr_with_family(s).map(|(family, addr)| (family, addr.to_string()))
+    }
+
+    #[test]
+    fn parses_both_families() {
+        assert_eq!(
+            parsed("192.0.2.1:80"),
+            Ok((IpFamily::V4, "192.0.2.1:80".into()))
+        );
+        assert_eq!(parsed("[::1]:443"), Ok((IpFamily::V6, "[::1]:443".into())));
+        // Mapped addresses are IPv6 socket addresses.
+        assert_eq!(
+            parsed("[::ffff:192.0.2.1]:80"),
+            Ok((IpFamily::V6, "[::ffff:192.0.2.1]:80".into()))
+        );
+    }
+
+    #[test]
+    fn parses_numeric_scope_ids() {
+        let (_, addr) = parse_socket_addr_with_family("[fe80::1%2]:80").unwrap();
+        let SocketAddr::V6(addr) = addr else {
+            panic!("{addr} is not IPv6");
+        };
+        assert_eq!((addr.scope_id(), addr.port()), (2, 80));
+        assert_eq!(
+            parsed("[fe80::1%]:80"),
+            Err(ParseSocketAddrError::InvalidScope)
+        );
+        assert_eq!(
+            parsed("[fe80::1%no-such-interface0]:80"),
+            Err(ParseSocketAddrError::InvalidScope)
+        );
+    }
+
+    #[test]
+    fn reports_specific_errors() {
+        use ParseSocketAddrError::*;
+
+        assert_eq!(parsed(""), Err(Empty));
+        assert_eq!(parsed("192.0.2.1"), Err(MissingPort));
+        assert_eq!(parsed("[::1]"), Err(MissingPort));
+        assert_eq!(parsed("::1"), Err(MissingPort));
+        assert_eq!(parsed("192.0.2.1:65536"), Err(InvalidPort));
+        assert_eq!(parsed("192.0.2.1:"), Err(InvalidPort));
+        assert_eq!(parsed("192.0.2.256:80"), Err(InvalidAddr));
+        assert_eq!(parsed("example.com:80"), Err(InvalidAddr));
+        assert_eq!(parsed("[::1:80"), Err(UnclosedBracket));
+        assert_eq!(parsed("[::1]80"), Err(TrailingCharacters));
+        assert_eq!(parsed("[192.0.2.1]:80"), Err(InvalidAddr));
+    }
+
+    #[test]
+    fn default_port_applies_only_without_a_port() {
+        let parsed = |s| {
+            parse_socket_addr_or_default(s, 53).map(|(family, addr)| (family, addr.to_string()))
+        };
+        assert_eq!(
+            parsed("192.0.2.1"),
+            Ok((IpFamily::V4, "192.0.2.1:53".into()))
+        );
+        assert_eq!(
+            parsed("192.0.2.1:5353"),
+            Ok((IpFamily::V4, "192.0.2.1:5353".into()))
+        );
+        assert_eq!(parsed("[::1]"), Ok((IpFamily::V6, "[::1]:53".into())));
+        // Without brackets the trailing group is part of the address.
+        assert_eq!(parsed("::1:80"), Ok((IpFamily::V6, "[::1:80]:53".into())));
+        assert_eq!(
+            parsed("fe80::1%3"),
+            Ok((IpFamily::V6, "[fe80::1%3]:53".into()))
+        );
+    }
+
+    #[test]
+    fn family_checked_parsing() {
+        assert!(IpFamily::V4.parse_addr("192.0.2.1").is_ok());
+        assert_eq!(
+            IpFamily::V4.parse_addr("::1"),
+            Err(ParseOrMismatchError::Mismatch(FamilyMismatchError {
+                expected: IpFamily::V4,
+                actual: IpFamily::V6,
+            }))
+        );
+        assert!(matches!(
+            IpFamily::V6.parse_addr("nope"),
+            Err(ParseOrMismatchError::Parse(_))
+        ));
+        assert_eq!(
+            IpFamily::V4.parse_socket_addr("[::1]:80"),
+            Err(ParseOrMismatchError::Mismatch(FamilyMismatchError {
+                expected: IpFamily::V4,
+                actual: IpFamily::V6,
+            }))
+        );
+        assert_eq!(
+            IpFamily::V6.parse_socket_addr("[::1]"),
+            Err(ParseOrMismatchError::Parse(
+                ParseSocketAddrError::MissingPort
+            ))
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use std::ops::{Index, IndexMut};
+
+use crate::IpFamily;
+
+/// One value per IP family.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct PerFamily<T> {
+    pub v4: T,
+    pub v6: T,
+}
+
+impl<T> PerFamily<T> {
+    pub const fn new(v4: T, v6: T) -> Self {
+        Self { v4, v6 }
+    }
+
+    pub const fn get(&self, family: IpFamily) -> &T {
+        match family {
+            IpFamily::V4 => &self.v4,
+            IpFamily::V6 => &self.v6,
+        }
+    }
+
+    pub fn get_mut(&mut self, family: IpFamily) -> &mut T {
+        match family {
+            IpFamily::V4 => &mut self.v4,
+            IpFamily::V6 => &mut self.v6,
+        }
+    }
+
+    pub fn map<U>(self, mut f: impl FnMut(IpFamily, T) -> U) -> PerFamily<U> {
+        PerFamily {
+            v4: f(IpFamily::V4, self.v4),
+            v6: f(IpFamily::V6, self.v6),
+        }
+    }
+
+    /// Iterates IPv4 first.
+    pub fn iter(&self) -> impl Iterator<Item = (IpFamily, &T)> {
+        [(IpFamily::V4, &self.v4), (IpFamily::V6, &self.v6)].into_iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (IpFamily, &mut T)> {
+        [(IpFamily::V4, &mut self.v4), (IpFamily::V6, &mut self.v6)].into_iter()
+    }
+}
+
+impl<T> Index<IpFamily> for PerFamily<T> {
+    type Output = T;
+
+    fn index(&self, family: IpFamily) -> &T {
+        self.get(family)
+    }
+}
+
+impl<T> IndexMut<IpFamily> for PerFamily<T> {
+    fn index_mut(&mut self, family: IpFamily) -> &mut T {
+        self.get_mut(family)
+    }
+}
+
+/// Families missing from the iterator get `T::default()`, later entries
+/// replace earlier ones.
+impl<T: Default> FromIterator<(IpFamily, T)> for PerFamily<T> {
+    fn from_iter<I: IntoIterator<Item = (IpFamily, T)>>(iter: I) -> Self {
+        let mut values = Self::default();
+        for (family, value) in iter {
+            values[family] = value;
+        }
+        values
+    }
+}
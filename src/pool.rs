@@ -0,0 +1,284 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Formatter},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use crate::{IpFamily, IpFamilyExt, PerFamily};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PoolConfig {
+    /// Idle connections kept per family.
+    pub max_idle: PerFamily<usize>,
+    pub idle_timeout: Duration,
+    /// Consecutive failures after which a family is quarantined.
+    pub failure_threshold: u32,
+    pub quarantine: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_idle: PerFamily::new(32, 32),
+            idle_timeout: Duration::from_secs(90),
+            failure_threshold: 3,
+            quarantine: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Health {
+    failures: u32,
+    quarantined_until: Option<Instant>,
+}
+
+/// Idle connections keyed by family and peer, with per-family limits and
+/// quarantine of a family after repeated failures, such as IPv6 timing out
+/// on a broken network.
+///
+/// Quarantining a family drops its idle connections and refuses new ones
+/// until the quarantine expires.
+pub struct FamilyPool<C> {
+    config: PoolConfig,
+    idle: BTreeMap<(IpFamily, SocketAddr), Vec<(C, Instant)>>,
+    idle_count: PerFamily<usize>,
+    health: PerFamily<Health>,
+}
+
+impl<C> FamilyPool<C> {
+    pub fn new(config: PoolConfig) -> Self {
+        Self {
+            config,
+            idle: BTreeMap::new(),
+            idle_count: PerFamily::default(),
+            health: PerFamily::default(),
+        }
+    }
+
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
+    /// The most recently returned connection to `addr` that has not been
+    /// idle for too long.
+    pub fn checkout(&mut self, addr: SocketAddr) -> Option<C> {
+        let family = addr.family();
+        if self.is_quarantined(family) {
+            return None;
+        }
+        let timeout = self.config.idle_timeout;
+        let conns = self.idle.get_mut(&(family, addr))?;
+        let mut found = None;
+        while let Some((conn, since)) = conns.pop() {
+            self.idle_count[family] -= 1;
+            if since.elapsed() < timeout {
+                found = Some(conn);
+                break;
+            }
+        }
+        if conns.is_empty() {
+            self.idle.remove(&(family, addr));
+        }
+        found
+    }
+
+    /// Returns the connection if the family is quarantined or at its idle
+    /// limit.
+    pub fn checkin(&mut self, addr: SocketAddr, conn: C) -> Result<(), C> {
+        let family = addr.family();
+        let max = self.config.max_idle[family];
+        if self.is_quarantined(family) || self.idle_count[family] >= max {
+            return Err(conn);
+        }
+        self.idle_count[family] += 1;
+        self.idle
+            .entry((family, addr))
+            .or_default()
+            .push((conn, Instant::now()));
+        Ok(())
+    }
+
+    pub fn idle_count(&self, family: IpFamily) -> usize {
+        self.idle_count[family]
+    }
+
+    pub fn record_success(&mut self, family: IpFamily) {
+        self.health[family] = Health::default();
+    }
+
+    /// Returns whether the failure quarantined the family.
+    pub fn record_failure(&mut self, family: IpFamily) -> bool {
+        let health = &mut self.health[family];
+        health.failures += 1;
+        if health.failures < self.config.failure_threshold {
+            return false;
+        }
+        health.failures = 0;
+        health.quarantined_until = Some(Instant::now() + self.config.quarantine);
+        self.drop_family(family);
+        true
+    }
+
+    pub fn is_quarantined(&self, family: IpFamily) -> bool {
+        self.health[family]
+            .quarantined_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Drops connections idle for longer than the timeout.
+    pub fn evict_expired(&mut self) {
+        let timeout = self.config.idle_timeout;
+        let idle_count = &mut self.idle_count;
+        self.idle.retain(|&(family, _), conns| {
+            conns.retain(|(_, since)| {
+                let keep = since.elapsed() < timeout;
+                if !keep {
+                    idle_count[family] -= 1;
+                }
+                keep
+            });
+            !conns.is_empty()
+        });
+    }
+
+    fn drop_family(&mut self, family: IpFamily) {
+        self.idle.retain(|&(f, _), _| f != family);
+        self.idle_count[family] = 0;
+    }
+}
+
+impl<C> Default for FamilyPool<C> {
+    fn default() -> Self {
+        Self::new(PoolConfig::default())
+    }
+}
+
+impl<C> Debug for FamilyPool<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FamilyPool")
+            .field("config", &self.config)
+            .field("idle_count", &self.idle_count)
+            .field("health", &self.health)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn v4(port: u16) -> SocketAddr {
+        SocketAddr::new(crate::fixtures::V4_ADDR.into(), port)
+    }
+
+    fn v6(port: u16) -> SocketAddr {
+        SocketAddr::new(crate::fixtures::V6_ADDR.into(), port)
+    }
+
+    fn pool(config: PoolConfig) -> FamilyPool<&'static str> {
+        FamilyPool::new(config)
+    }
+
+    #[test]
+    fn checkout_returns_the_latest_connection_per_peer() {
+        let mut pool = pool(PoolConfig::default());
+        pool.checkin(v4(80), "a").unwrap();
+        pool.checkin(v4(80), "b").unwrap();
+        pool.checkin(v4(443), "c").unwrap();
+        pool.checkin(v6(80), "d").unwrap();
+        assert_eq!(pool.idle_count(IpFamily::V4), 3);
+        assert_eq!(pool.idle_count(IpFamily::V6), 1);
+        assert_eq!(pool.checkout(v4(80)), Some("b"));
+        assert_eq!(pool.checkout(v4(80)), Some("a"));
+        assert_eq!(pool.checkout(v4(80)), None);
+        assert_eq!(pool.idle_count(IpFamily::V4), 1);
+        assert_eq!(pool.checkout(v6(443)), None);
+    }
+
+    #[test]
+    fn checkin_respects_per_family_limits() {
+        let mut pool = pool(PoolConfig {
+            max_idle: PerFamily::new(1, 0),
+            ..PoolConfig::default()
+        });
+        assert_eq!(pool.checkin(v4(80), "a"), Ok(()));
+        assert_eq!(pool.checkin(v4(443), "b"), Err("b"));
+        assert_eq!(pool.checkin(v6(80), "c"), Err("c"));
+        pool.checkout(v4(80));
+        assert_eq!(pool.checkin(v4(443), "b"), Ok(()));
+    }
+
+    #[test]
+    fn expired_connections_are_skipped_and_counted() {
+        let mut pool = pool(PoolConfig {
+            idle_timeout: Duration::ZERO,
+            ..PoolConfig::default()
+        });
+        pool.checkin(v4(80), "a").unwrap();
+        pool.checkin(v4(80), "b").unwrap();
+        assert_eq!(pool.checkout(v4(80)), None);
+        assert_eq!(pool.idle_count(IpFamily::V4), 0);
+
+        pool.checkin(v4(80), "a").unwrap();
+        pool.checkin(v6(80), "b").unwrap();
+        pool.evict_expired();
+        assert_eq!(pool.idle_count(IpFamily::V4), 0);
+        assert_eq!(pool.idle_count(IpFamily::V6), 0);
+    }
+
+    #[test]
+    fn evict_keeps_fresh_connections() {
+        let mut pool = pool(PoolConfig::default());
+        pool.checkin(v4(80), "a").unwrap();
+        pool.evict_expired();
+        assert_eq!(pool.idle_count(IpFamily::V4), 1);
+    }
+
+    #[test]
+    fn failures_quarantine_only_their_family() {
+        let mut pool = pool(PoolConfig {
+            failure_threshold: 2,
+            quarantine: HOUR,
+            ..PoolConfig::default()
+        });
+        pool.checkin(v4(80), "a").unwrap();
+        pool.checkin(v6(80), "b").unwrap();
+        assert!(!pool.record_failure(IpFamily::V6));
+        assert!(pool.record_failure(IpFamily::V6));
+        assert!(pool.is_quarantined(IpFamily::V6));
+        assert!(!pool.is_quarantined(IpFamily::V4));
+        // Idle connections of the family are dropped, new ones refused.
+        assert_eq!(pool.idle_count(IpFamily::V6), 0);
+        assert_eq!(pool.checkin(v6(80), "c"), Err("c"));
+        assert_eq!(pool.checkout(v6(80)), None);
+        assert_eq!(pool.checkout(v4(80)), Some("a"));
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let mut pool = pool(PoolConfig {
+            failure_threshold: 2,
+            ..PoolConfig::default()
+        });
+        assert!(!pool.record_failure(IpFamily::V4));
+        pool.record_success(IpFamily::V4);
+        assert!(!pool.record_failure(IpFamily::V4));
+        assert!(!pool.is_quarantined(IpFamily::V4));
+    }
+
+    #[test]
+    fn quarantine_expires() {
+        let mut pool = pool(PoolConfig {
+            failure_threshold: 1,
+            quarantine: Duration::ZERO,
+            ..PoolConfig::default()
+        });
+        assert!(pool.record_failure(IpFamily::V4));
+        assert!(!pool.is_quarantined(IpFamily::V4));
+        assert_eq!(pool.checkin(v4(80), "a"), Ok(()));
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{HasFamily, IpFamily, ParseFamilyPreferenceError};
+
+/// Which families an application uses, and in which order.
+///
+/// Filter and order candidate addresses with [`apply`](Self::apply) before
+/// handing them to [`connect_happy_eyeballs`](crate::connect_happy_eyeballs),
+/// which starts with the family of the first address, or pass the policy to
+/// [`sort_addrs_rfc6724_with`](crate::sort_addrs_rfc6724_with) and
+/// [`resolve_with_preference`](crate::resolve_with_preference).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum FamilyPreference {
+    V4Only,
+    V6Only,
+    PreferV4,
+    PreferV6,
+    /// Both families, ordered by RFC 6724 alone, which puts IPv6 first.
+    #[default]
+    Any,
+}
+
+impl FamilyPreference {
+    pub const fn allows(self, family: IpFamily) -> bool {
+        !matches!(
+            (self, family),
+            (Self::V4Only, IpFamily::V6) | (Self::V6Only, IpFamily::V4)
+        )
+    }
+
+    /// The family that comes first, `None` for [`Any`](Self::Any).
+    pub const fn preferred(self) -> Option<IpFamily> {
+        match self {
+            Self::V4Only | Self::PreferV4 => Some(IpFamily::V4),
+            Self::V6Only | Self::PreferV6 => Some(IpFamily::V6),
+            Self::Any => None,
+        }
+    }
+
+    /// The allowed families, preferred first. [`Any`](Self::Any) lists
+    /// IPv6 first, like RFC 6724.
+    pub fn families(self) -> impl Iterator<Item = IpFamily> {
+        let order = match self.preferred() {
+            Some(IpFamily::V4) => [IpFamily::V4, IpFamily::V6],
+            _ => [IpFamily::V6, IpFamily::V4],
+        };
+        order.into_iter().filter(move |&family| self.allows(family))
+    }
+
+    /// The family to bind a listener to. Preferences only order the
+    /// families, they never refuse one, so every policy that allows both
+    /// binds an IPv6 socket that also accepts IPv4-mapped connections.
+    /// Only [`V4Only`](Self::V4Only) binds IPv4.
+    pub const fn bind_family(self) -> IpFamily {
+        match self {
+            Self::V4Only => IpFamily::V4,
+            Self::V6Only | Self::PreferV4 | Self::PreferV6 | Self::Any => IpFamily::V6,
+        }
+    }
+
+    /// Drops items of a family that is not allowed.
+    pub fn filter<I>(self, items: I) -> impl Iterator<Item = I::Item>
+    where
+        I: IntoIterator,
+        I::Item: HasFamily<Output = IpFamily>,
+    {
+        items
+            .into_iter()
+            .filter(move |item| self.allows(item.ip_family()))
+    }
+
+    /// Moves items of the preferred family to the front, otherwise keeping
+    /// the given order.
+    pub fn sort<T: HasFamily<Output = IpFamily>>(self, items: &mut [T]) {
+        if let Some(preferred) = self.preferred() {
+            items.sort_by_key(|item| item.ip_family() != preferred);
+        }
+    }
+
+    /// [`filter`](Self::filter) and [`sort`](Self::sort) in place.
+    pub fn apply<T: HasFamily<Output = IpFamily>>(self, items: &mut Vec<T>) {
+        items.retain(|item| self.allows(item.ip_family()));
+        self.sort(items);
+    }
+}
+
+impl Display for FamilyPreference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::V4Only => "v4-only",
+            Self::V6Only => "v6-only",
+            Self::PreferV4 => "prefer-v4",
+            Self::PreferV6 => "prefer-v6",
+            Self::Any => "any",
+        })
+    }
+}
+
+/// Accepts the `Display` output ignoring case, so it can back a
+/// `--family v4-only|v6-only|prefer-v4|prefer-v6|any` flag.
+impl FromStr for FamilyPreference {
+    type Err = ParseFamilyPreferenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::V4Only,
+            Self::V6Only,
+            Self::PreferV4,
+            Self::PreferV6,
+            Self::Any,
+        ]
+        .into_iter()
+        .find(|preference| s.eq_ignore_ascii_case(&preference.to_string()))
+        .ok_or(ParseFamilyPreferenceError(()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FamilyPreference {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for FamilyPreference {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+
+    const ALL: [FamilyPreference; 5] = [
+        FamilyPreference::V4Only,
+        FamilyPreference::V6Only,
+        FamilyPreference::PreferV4,
+        FamilyPreference::PreferV6,
+        FamilyPreference::Any,
+    ];
+
+    #[test]
+    fn bind_family_serves_every_allowed_family() {
+        for preference in ALL {
+            let bound = preference.bind_family();
+            assert!(preference.allows(bound), "{preference}");
+            if preference.allows(IpFamily::V4) && preference.allows(IpFamily::V6) {
+                assert_eq!(bound, IpFamily::V6, "{preference}");
+            }
+        }
+        assert_eq!(FamilyPreference::V4Only.bind_family(), IpFamily::V4);
+    }
+
+    #[test]
+    fn apply_filters_and_orders() {
+        let addrs: Vec<SocketAddr> = vec![
+            "[2001:db8::1]:80".parse().unwrap(),
+            "192.0.2.1:80".parse().unwrap(),
+            "[2001:db8::2]:80".parse().unwrap(),
+            "192.0.2.2:80".parse().unwrap(),
+        ];
+        let mut v4_first = addrs.clone();
+        FamilyPreference::PreferV4.apply(&mut v4_first);
+        assert_eq!(v4_first, [addrs[1], addrs[3], addrs[0], addrs[2]]);
+
+        let mut v6_only = addrs.clone();
+        FamilyPreference::V6Only.apply(&mut v6_only);
+        assert_eq!(v6_only, [addrs[0], addrs[2]]);
+
+        let mut any = addrs.clone();
+        FamilyPreference::Any.apply(&mut any);
+        assert_eq!(any, addrs);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for preference in ALL {
+            assert_eq!(preference.to_string().parse(), Ok(preference));
+        }
+        assert_eq!("PREFER-V4".parse(), Ok(FamilyPreference::PreferV4));
+        assert!("v4".parse::<FamilyPreference>().is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::{HasFamily, IpFamily, IpFamilyExt, ParsePrefixError};
+
+pub(crate) fn clamp_prefix(family: IpFamily, prefix_len: u8) -> u8 {
+    match family {
+        IpFamily::V4 => prefix_len.min(32),
+        IpFamily::V6 => prefix_len.min(128),
+    }
+}
+
+pub(crate) fn truncate(addr: IpAddr, prefix_len: u8) -> IpAddr {
+    match addr {
+        IpAddr::V4(addr) => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(prefix_len))
+                .unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(addr) & mask))
+        }
+        IpAddr::V6(addr) => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(prefix_len))
+                .unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(addr) & mask))
+        }
+    }
+}
+
+/// An address prefix of either family, such as `192.0.2.0/24`.
+///
+/// Host bits are always zero.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct IpPrefix {
+    addr: IpAddr,
+    len: u8,
+}
+
+impl IpPrefix {
+    /// Host bits of `addr` are cleared and `len` is clamped to the width of
+    /// the family.
+    pub fn new(addr: IpAddr, len: u8) -> Self {
+        let len = clamp_prefix(addr.family(), len);
+        Self {
+            addr: truncate(addr, len),
+            len,
+        }
+    }
+
+    /// The prefix containing only `addr`.
+    pub fn host(addr: IpAddr) -> Self {
+        Self::new(addr, u8::MAX)
+    }
+
+    /// First address of the prefix.
+    pub const fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    // A prefix is never empty, `len` is a bit count.
+    #[allow(clippy::len_without_is_empty)]
+    pub const fn len(&self) -> u8 {
+        self.len
+    }
+
+    /// Addresses of the other family are never contained.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        addr.family() == self.addr.family() && truncate(addr, self.len) == self.addr
+    }
+
+    /// Whether every address of `other` is in `self`.
+    pub fn covers(&self, other: &Self) -> bool {
+        other.len >= self.len && self.contains(other.addr)
+    }
+}
+
+impl AsRef<IpFamily> for IpPrefix {
+    fn as_ref(&self) -> &IpFamily {
+        match self.addr {
+            IpAddr::V4(_) => &IpFamily::V4,
+            IpAddr::V6(_) => &IpFamily::V6,
+        }
+    }
+}
+
+impl HasFamily for IpPrefix {
+    type Output = IpFamily;
+
+    fn ip_family(&self) -> IpFamily {
+        self.addr.family()
+    }
+}
+
+impl Display for IpPrefix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.len)
+    }
+}
+
+/// Parses `addr/len`, or a bare address as a host prefix. Host bits must be
+/// zero.
+impl FromStr for IpPrefix {
+    type Err = ParsePrefixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, len) = match s.split_once('/') {
+            Some((addr, len)) => (addr, Some(len)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| ParsePrefixError::Addr)?;
+        let max = clamp_prefix(addr.family(), u8::MAX);
+        let len = match len {
+            Some(len) => len
+                .parse()
+                .ok()
+                .filter(|&len| len <= max)
+                .ok_or(ParsePrefixError::Len)?,
+            None => max,
+        };
+        let prefix = Self::new(addr, len);
+        if prefix.addr != addr {
+            return Err(ParsePrefixError::HostBits);
+        }
+        Ok(prefix)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for IpPrefix {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for IpPrefix {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
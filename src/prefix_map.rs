@@ -0,0 +1,269 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    slice,
+};
+
+use crate::{AnyIpFamily, IpFamilyAddr, Network};
+
+/// A map from networks of one family to values, with longest-prefix-match
+/// lookup for routing tables, geo-IP databases and ACLs.
+///
+/// Entries are kept sorted by network.
+pub struct PrefixMap<F: AnyIpFamily, T> {
+    entries: Vec<(Network<F>, T)>,
+}
+
+impl<F: AnyIpFamily, T> PrefixMap<F, T> {
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the previous value of an equal network.
+    pub fn insert(&mut self, network: Network<F>, value: T) -> Option<T> {
+        match self.search(&network) {
+            Ok(index) => Some(std::mem::replace(&mut self.entries[index].1, value)),
+            Err(index) => {
+                self.entries.insert(index, (network, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, network: &Network<F>) -> Option<T> {
+        let index = self.search(network).ok()?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// The value of exactly `network`, ignoring covering entries.
+    pub fn get(&self, network: &Network<F>) -> Option<&T> {
+        let index = self.search(network).ok()?;
+        Some(&self.entries[index].1)
+    }
+
+    pub fn get_mut(&mut self, network: &Network<F>) -> Option<&mut T> {
+        let index = self.search(network).ok()?;
+        Some(&mut self.entries[index].1)
+    }
+
+    /// The most specific entry containing `addr`.
+    pub fn longest_match(&self, addr: F::Addr) -> Option<(&Network<F>, &T)> {
+        (0..=F::Addr::MAX_PREFIX).rev().find_map(|len| {
+            let index = self.search(&Network::new(addr, len)).ok()?;
+            let (network, value) = &self.entries[index];
+            Some((network, value))
+        })
+    }
+
+    /// All entries containing `addr`, from the least to the most specific.
+    pub fn matches(&self, addr: F::Addr) -> impl Iterator<Item = (&Network<F>, &T)> + '_ {
+        (0..=F::Addr::MAX_PREFIX).filter_map(move |len| {
+            let index = self.search(&Network::new(addr, len)).ok()?;
+            let (network, value) = &self.entries[index];
+            Some((network, value))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries in ascending order of network.
+    pub fn iter(&self) -> PrefixMapIter<'_, F, T> {
+        PrefixMapIter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    fn search(&self, network: &Network<F>) -> Result<usize, usize> {
+        self.entries
+            .binary_search_by(|(entry, _)| entry.cmp(network))
+    }
+}
+
+impl<F: AnyIpFamily, T> Default for PrefixMap<F, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Implemented by hand so generic code needs no `F: Clone` etc. bounds.
+impl<F: AnyIpFamily, T: Clone> Clone for PrefixMap<F, T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<F: AnyIpFamily, T: PartialEq> PartialEq for PrefixMap<F, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<F: AnyIpFamily, T: Eq> Eq for PrefixMap<F, T> {}
+
+impl<F: AnyIpFamily, T: Debug> Debug for PrefixMap<F, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<F: AnyIpFamily, T> FromIterator<(Network<F>, T)> for PrefixMap<F, T> {
+    fn from_iter<I: IntoIterator<Item = (Network<F>, T)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<F: AnyIpFamily, T> Extend<(Network<F>, T)> for PrefixMap<F, T> {
+    fn extend<I: IntoIterator<Item = (Network<F>, T)>>(&mut self, iter: I) {
+        for (network, value) in iter {
+            self.insert(network, value);
+        }
+    }
+}
+
+impl<'a, F: AnyIpFamily, T> IntoIterator for &'a PrefixMap<F, T> {
+    type Item = (&'a Network<F>, &'a T);
+    type IntoIter = PrefixMapIter<'a, F, T>;
+
+    fn into_iter(self) -> PrefixMapIter<'a, F, T> {
+        self.iter()
+    }
+}
+
+/// Iterator over the entries of a [`PrefixMap`].
+pub struct PrefixMapIter<'a, F: AnyIpFamily, T> {
+    inner: slice::Iter<'a, (Network<F>, T)>,
+}
+
+impl<'a, F: AnyIpFamily, T> Iterator for PrefixMapIter<'a, F, T> {
+    type Item = (&'a Network<F>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(network, value)| (network, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<F: AnyIpFamily, T> DoubleEndedIterator for PrefixMapIter<'_, F, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(network, value)| (network, value))
+    }
+}
+
+impl<F: AnyIpFamily, T> ExactSizeIterator for PrefixMapIter<'_, F, T> {}
+
+#[cfg(all(test, feature = "v4", feature = "v6"))]
+mod tests {
+    use super::*;
+    use crate::{IpFamilyV4, IpFamilyV6};
+
+    fn v4(net: &str) -> Network<IpFamilyV4> {
+        net.parse().unwrap()
+    }
+
+    fn v6(net: &str) -> Network<IpFamilyV6> {
+        net.parse().unwrap()
+    }
+
+    fn routes() -> PrefixMap<IpFamilyV4, &'static str> {
+        [
+            (v4("0.0.0.0/0"), "default"),
+            (v4("10.0.0.0/8"), "corp"),
+            (v4("10.1.0.0/16"), "lab"),
+            (v4("10.1.2.3/32"), "host"),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn longest_match_prefers_the_most_specific_entry() {
+        let routes = routes();
+        let value = |addr: &str| routes.longest_match(addr.parse().unwrap()).map(|(_, v)| *v);
+        assert_eq!(value("10.1.2.3"), Some("host"));
+        assert_eq!(value("10.1.2.4"), Some("lab"));
+        assert_eq!(value("10.2.0.1"), Some("corp"));
+        assert_eq!(value("192.0.2.1"), Some("default"));
+    }
+
+    #[test]
+    fn longest_match_without_default_route() {
+        let mut routes = routes();
+        assert_eq!(routes.remove(&v4("0.0.0.0/0")), Some("default"));
+        assert_eq!(routes.longest_match(crate::fixtures::V4_ADDR), None);
+        assert_eq!(routes.remove(&v4("0.0.0.0/0")), None);
+    }
+
+    #[test]
+    fn longest_match_with_zero_and_full_length_v6_entries() {
+        let map: PrefixMap<IpFamilyV6, u8> = [(v6("::/0"), 0), (v6("2001:db8::1/128"), 128)]
+            .into_iter()
+            .collect();
+        let lookup = |addr: &str| map.longest_match(addr.parse().unwrap());
+        assert_eq!(lookup("2001:db8::1"), Some((&v6("2001:db8::1/128"), &128)));
+        assert_eq!(lookup("2001:db8::2"), Some((&v6("::/0"), &0)));
+        assert_eq!(
+            lookup("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"),
+            Some((&v6("::/0"), &0))
+        );
+    }
+
+    #[test]
+    fn matches_runs_from_least_to_most_specific() {
+        let routes = routes();
+        let matched: Vec<_> = routes
+            .matches("10.1.2.3".parse().unwrap())
+            .map(|(_, value)| *value)
+            .collect();
+        assert_eq!(matched, ["default", "corp", "lab", "host"]);
+        assert_eq!(routes.matches("192.0.2.1".parse().unwrap()).count(), 1);
+    }
+
+    #[test]
+    fn get_is_exact() {
+        let mut routes = routes();
+        assert_eq!(routes.get(&v4("10.1.0.0/16")), Some(&"lab"));
+        assert_eq!(routes.get(&v4("10.1.0.0/24")), None);
+        *routes.get_mut(&v4("10.0.0.0/8")).unwrap() = "office";
+        assert_eq!(routes.get(&v4("10.0.0.0/8")), Some(&"office"));
+    }
+
+    #[test]
+    fn insert_replaces_equal_networks_and_keeps_order() {
+        let mut routes = routes();
+        assert_eq!(routes.insert(v4("10.0.0.0/8"), "office"), Some("corp"));
+        assert_eq!(routes.insert(v4("9.0.0.0/8"), "other"), None);
+        assert_eq!(routes.len(), 5);
+        let networks: Vec<_> = routes.iter().map(|(network, _)| *network).collect();
+        let mut sorted = networks.clone();
+        sorted.sort();
+        assert_eq!(networks, sorted);
+        assert_eq!(
+            routes.iter().next_back().map(|(_, value)| *value),
+            Some("host")
+        );
+    }
+
+    #[test]
+    fn empty_map() {
+        let map = PrefixMap::<IpFamilyV6, ()>::default();
+        assert!(map.is_empty());
+        assert_eq!(map.longest_match(crate::fixtures::V6_ADDR), None);
+        assert_eq!(map.iter().len(), 0);
+    }
+}
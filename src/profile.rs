@@ -0,0 +1,300 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{FamilySet, IpFamily, IpPrefix, Nat64Prefix, PerFamily};
+
+/// A snapshot of what the host's network supports, see
+/// [`HostNetworkProfile`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct NetworkProfile {
+    /// Whether a socket of the family can be created at all.
+    pub supported: PerFamily<bool>,
+    /// Whether the routing table has a route to a global address.
+    pub default_route: PerFamily<bool>,
+    /// The NAT64 prefix discovered through `ipv4only.arpa` (RFC 7050).
+    pub nat64_prefix: Option<IpPrefix>,
+    /// Whether temporary IPv6 addresses (RFC 8981) are configured, if the
+    /// platform exposes it.
+    pub temporary_addresses: Option<bool>,
+    pub probed_at: Instant,
+}
+
+// Connecting a UDP socket only consults the routing table, no packet is
+// sent.
+const ROUTE_PROBES: PerFamily<SocketAddr> = PerFamily::new(
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), 53),
+    SocketAddr::new(
+        IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+        53,
+    ),
+);
+
+const WELL_KNOWN_IPV4_ONLY: [Ipv4Addr; 2] =
+    [Ipv4Addr::new(192, 0, 0, 170), Ipv4Addr::new(192, 0, 0, 171)];
+
+fn probe_family(family: IpFamily, target: SocketAddr) -> (bool, bool) {
+    match UdpSocket::bind(family.unspecified_socket(0)) {
+        Ok(socket) => (true, socket.connect(target).is_ok()),
+        Err(_) => (false, false),
+    }
+}
+
+/// The RFC 7050 prefix `addr` was synthesized under, if it embeds one of
+/// the well-known `ipv4only.arpa` addresses at any RFC 6052 length.
+fn nat64_prefix_of(addr: Ipv6Addr) -> Option<IpPrefix> {
+    [96, 64, 56, 48, 40, 32].into_iter().find_map(|len| {
+        let prefix = IpPrefix::new(IpAddr::V6(addr), len);
+        let IpAddr::V6(network) = prefix.addr() else {
+            unreachable!("the prefix of an IPv6 address")
+        };
+        let v4 = Nat64Prefix::new(network, len).ok()?.extract(addr)?;
+        WELL_KNOWN_IPV4_ONLY.contains(&v4).then_some(prefix)
+    })
+}
+
+fn probe_nat64() -> Option<IpPrefix> {
+    ("ipv4only.arpa", 0)
+        .to_socket_addrs()
+        .ok()?
+        .find_map(|addr| match addr.ip() {
+            IpAddr::V6(v6) => nat64_prefix_of(v6),
+            IpAddr::V4(_) => None,
+        })
+}
+
+#[cfg(target_os = "linux")]
+fn probe_temporary_addresses() -> Option<bool> {
+    const IFA_F_TEMPORARY: u32 = 0x01;
+
+    let table = std::fs::read_to_string("/proc/net/if_inet6").ok()?;
+    Some(table.lines().any(|line| {
+        line.split_whitespace()
+            .nth(4)
+            .and_then(|flags| u32::from_str_radix(flags, 16).ok())
+            .is_some_and(|flags| flags & IFA_F_TEMPORARY != 0)
+    }))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn probe_temporary_addresses() -> Option<bool> {
+    None
+}
+
+impl NetworkProfile {
+    /// Probes the host. This may block on a DNS lookup for the NAT64
+    /// prefix.
+    pub fn probe() -> Self {
+        let (v4, v4_route) = probe_family(IpFamily::V4, ROUTE_PROBES.v4);
+        let (v6, v6_route) = probe_family(IpFamily::V6, ROUTE_PROBES.v6);
+        Self {
+            supported: PerFamily::new(v4, v6),
+            default_route: PerFamily::new(v4_route, v6_route),
+            nat64_prefix: if v6_route { probe_nat64() } else { None },
+            temporary_addresses: if v6 {
+                probe_temporary_addresses()
+            } else {
+                Some(false)
+            },
+            probed_at: Instant::now(),
+        }
+    }
+
+    /// The families a socket can be created for.
+    pub fn supported_families(&self) -> FamilySet {
+        IpFamily::iter()
+            .filter(|&family| self.supported[family])
+            .collect()
+    }
+
+    /// The families with a route to a global address.
+    pub fn routable_families(&self) -> FamilySet {
+        IpFamily::iter()
+            .filter(|&family| self.default_route[family])
+            .collect()
+    }
+}
+
+static SUPPORTED: Mutex<Option<FamilySet>> = Mutex::new(None);
+
+fn probe_supported() -> FamilySet {
+    IpFamily::iter()
+        .filter(|&family| UdpSocket::bind(family.unspecified_socket(0)).is_ok())
+        .collect()
+}
+
+/// The families a socket can be created for, probed once per process by
+/// binding a UDP socket. For routes and NAT64 see [`NetworkProfile`].
+pub fn detect_supported_families() -> FamilySet {
+    *SUPPORTED
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .get_or_insert_with(probe_supported)
+}
+
+/// Probes again, for example after IPv6 was enabled at runtime, and
+/// replaces the result [`detect_supported_families`] returns.
+pub fn refresh_supported_families() -> FamilySet {
+    let supported = probe_supported();
+    *SUPPORTED.lock().unwrap_or_else(|err| err.into_inner()) = Some(supported);
+    supported
+}
+
+impl IpFamily {
+    /// See [`detect_supported_families`].
+    pub fn is_supported(&self) -> bool {
+        detect_supported_families().contains(*self)
+    }
+}
+
+type Probe = Box<dyn Fn() -> NetworkProfile + Send + Sync>;
+
+/// Caches a [`NetworkProfile`] so subsystems share one probe, re-probing
+/// once it is older than the TTL or after [`invalidate`].
+///
+/// [`invalidate`]: Self::invalidate
+pub struct HostNetworkProfile {
+    ttl: Duration,
+    probe: Probe,
+    cached: Mutex<Option<Arc<NetworkProfile>>>,
+}
+
+impl HostNetworkProfile {
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_probe(ttl, NetworkProfile::probe)
+    }
+
+    /// Uses `probe` instead of [`NetworkProfile::probe`], for tests or
+    /// platform-specific probing.
+    pub fn with_probe(
+        ttl: Duration,
+        probe: impl Fn() -> NetworkProfile + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            ttl,
+            probe: Box::new(probe),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// The cached profile, probing first if it is missing or expired.
+    pub fn get(&self) -> Arc<NetworkProfile> {
+        let mut cached = self.cached.lock().unwrap_or_else(|err| err.into_inner());
+        match &*cached {
+            Some(profile) if profile.probed_at.elapsed() < self.ttl => profile.clone(),
+            _ => cached.insert(Arc::new((self.probe)())).clone(),
+        }
+    }
+
+    /// Probes now, replacing the cached profile.
+    pub fn refresh(&self) -> Arc<NetworkProfile> {
+        let profile = Arc::new((self.probe)());
+        *self.cached.lock().unwrap_or_else(|err| err.into_inner()) = Some(profile.clone());
+        profile
+    }
+
+    /// Makes the next [`get`](Self::get) probe again, for example after a
+    /// network change notification.
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    }
+}
+
+impl Debug for HostNetworkProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostNetworkProfile")
+            .field("ttl", &self.ttl)
+            .field("cached", &self.cached)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn counting_profile(ttl: Duration) -> (HostNetworkProfile, Arc<AtomicUsize>) {
+        let probes = Arc::new(AtomicUsize::new(0));
+        let counter = probes.clone();
+        let profile = HostNetworkProfile::with_probe(ttl, move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            NetworkProfile {
+                supported: PerFamily::new(true, true),
+                default_route: PerFamily::new(true, false),
+                nat64_prefix: None,
+                temporary_addresses: None,
+                probed_at: Instant::now(),
+            }
+        });
+        (profile, probes)
+    }
+
+    #[test]
+    fn caches_until_the_ttl_expires() {
+        let (profile, probes) = counting_profile(Duration::from_secs(3600));
+        assert_eq!(profile.get().routable_families(), FamilySet::V4);
+        profile.get();
+        assert_eq!(probes.load(Ordering::Relaxed), 1);
+
+        let (profile, probes) = counting_profile(Duration::ZERO);
+        profile.get();
+        profile.get();
+        assert_eq!(probes.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn invalidate_and_refresh_probe_again() {
+        let (profile, probes) = counting_profile(Duration::from_secs(3600));
+        let first = profile.get();
+        profile.invalidate();
+        assert_eq!(probes.load(Ordering::Relaxed), 1);
+        let second = profile.get();
+        assert_eq!(probes.load(Ordering::Relaxed), 2);
+        assert!(!Arc::ptr_eq(&first, &second));
+        let third = profile.refresh();
+        assert!(Arc::ptr_eq(&third, &profile.get()));
+        assert_eq!(probes.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn nat64_prefix_at_every_rfc6052_length() {
+        let cases = [
+            ("64:ff9b::c000:aa", "64:ff9b::/96"),
+            ("2001:db8:122:344:c0:0:aa00:0", "2001:db8:122:344::/64"),
+            ("2001:db8:122:3c0:0:aa::", "2001:db8:122:300::/56"),
+            ("2001:db8:122:c000:0:aa00::", "2001:db8:122::/48"),
+            ("2001:db8:1c0:0:aa:0:0:0", "2001:db8:100::/40"),
+            ("2001:db8:c000:aa::", "2001:db8::/32"),
+            ("64:ff9b::c000:ab", "64:ff9b::/96"),
+        ];
+        for (addr, prefix) in cases {
+            assert_eq!(
+                nat64_prefix_of(addr.parse().unwrap()),
+                Some(prefix.parse().unwrap()),
+                "{addr}"
+            );
+        }
+        assert_eq!(nat64_prefix_of("64:ff9b::c000:201".parse().unwrap()), None);
+        assert_eq!(nat64_prefix_of("2001:db8::1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn passes_the_probed_nat64_prefix_through() {
+        let nat64 = nat64_prefix_of("64:ff9b::c000:aa".parse().unwrap());
+        let profile =
+            HostNetworkProfile::with_probe(Duration::from_secs(3600), move || NetworkProfile {
+                supported: PerFamily::new(false, true),
+                default_route: PerFamily::new(false, true),
+                nat64_prefix: nat64,
+                temporary_addresses: Some(false),
+                probed_at: Instant::now(),
+            });
+        assert_eq!(profile.get().nat64_prefix, nat64);
+        assert_eq!(profile.get().supported_families(), FamilySet::V6);
+    }
+}
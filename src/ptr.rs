@@ -0,0 +1,130 @@
+use std::fmt::Write;
+
+use crate::ParsePtrError;
+
+/// `d.c.b.a.in-addr.arpa` for 4 octets, the nibble-reversed `ip6.arpa`
+/// name for 16.
+pub(crate) fn ptr_name(octets: &[u8]) -> String {
+    let mut name = String::new();
+    if octets.len() == 4 {
+        for octet in octets.iter().rev() {
+            let _ = write!(name, "{octet}.");
+        }
+        name.push_str("in-addr.arpa");
+    } else {
+        for octet in octets.iter().rev() {
+            let _ = write!(name, "{:x}.{:x}.", octet & 0xf, octet >> 4);
+        }
+        name.push_str("ip6.arpa");
+    }
+    name
+}
+
+/// Accepts a trailing dot and any case. Labels must not have leading
+/// zeros.
+pub(crate) fn parse_ptr_name(name: &str, len: usize) -> Result<Vec<u8>, ParsePtrError> {
+    let name = name.strip_suffix('.').unwrap_or(name);
+    let (suffix, labels) = if len == 4 {
+        (".in-addr.arpa", 4)
+    } else {
+        (".ip6.arpa", 2 * len)
+    };
+    let head = name
+        .len()
+        .checked_sub(suffix.len())
+        .filter(|&at| name.is_char_boundary(at) && name[at..].eq_ignore_ascii_case(suffix))
+        .map(|at| &name[..at])
+        .ok_or(ParsePtrError(()))?;
+    let labels_rev: Vec<&str> = head.split('.').collect();
+    if labels_rev.len() != labels {
+        return Err(ParsePtrError(()));
+    }
+    let mut octets = Vec::with_capacity(len);
+    if len == 4 {
+        for label in labels_rev.iter().rev() {
+            let digits = !label.is_empty() && label.bytes().all(|b| b.is_ascii_digit());
+            if !digits || (label.len() > 1 && label.starts_with('0')) {
+                return Err(ParsePtrError(()));
+            }
+            octets.push(label.parse().map_err(|_| ParsePtrError(()))?);
+        }
+    } else {
+        let nibble = |label: &str| match label.as_bytes() {
+            [digit] => (*digit as char).to_digit(16).map(|n| n as u8),
+            _ => None,
+        };
+        for pair in labels_rev.chunks(2).rev() {
+            let low = nibble(pair[0]).ok_or(ParsePtrError(()))?;
+            let high = nibble(pair[1]).ok_or(ParsePtrError(()))?;
+            octets.push(high << 4 | low);
+        }
+    }
+    Ok(octets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V6: [u8; 16] = [
+        0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x56, 0x7f,
+    ];
+    const V6_NAME: &str =
+        "f.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa";
+
+    #[test]
+    fn formats_both_families() {
+        assert_eq!(ptr_name(&[192, 0, 2, 1]), "1.2.0.192.in-addr.arpa");
+        assert_eq!(ptr_name(&[0; 4]), "0.0.0.0.in-addr.arpa");
+        assert_eq!(ptr_name(&V6), V6_NAME);
+    }
+
+    #[test]
+    fn round_trips() {
+        for octets in [[192, 0, 2, 1], [0; 4], [255; 4], [10, 0, 0, 1]] {
+            assert_eq!(parse_ptr_name(&ptr_name(&octets), 4), Ok(octets.to_vec()));
+        }
+        for octets in [V6, [0; 16], [0xff; 16]] {
+            assert_eq!(parse_ptr_name(&ptr_name(&octets), 16), Ok(octets.to_vec()));
+        }
+    }
+
+    #[test]
+    fn accepts_trailing_dot_and_any_case() {
+        assert_eq!(
+            parse_ptr_name("1.2.0.192.IN-ADDR.ARPA.", 4),
+            Ok(vec![192, 0, 2, 1])
+        );
+        assert_eq!(
+            parse_ptr_name(&format!("{}.", V6_NAME.to_uppercase()), 16),
+            Ok(V6.to_vec())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_names() {
+        for name in [
+            "",
+            "in-addr.arpa",
+            "2.0.192.in-addr.arpa",
+            "1.1.2.0.192.in-addr.arpa",
+            "01.2.0.192.in-addr.arpa",
+            "+1.2.0.192.in-addr.arpa",
+            "256.2.0.192.in-addr.arpa",
+            "1..0.192.in-addr.arpa",
+            "1.2.0.192.ip6.arpa",
+            "1.2.0.192.in-addr.arpa..",
+        ] {
+            assert_eq!(parse_ptr_name(name, 4), Err(ParsePtrError(())), "{name}");
+        }
+        for name in [
+            &V6_NAME[2..],
+            &format!("0.{V6_NAME}"),
+            &V6_NAME.replacen('f', "g", 1),
+            &V6_NAME.replacen("f.7", "f7", 1),
+            &V6_NAME.replace("ip6.arpa", "in-addr.arpa"),
+        ] {
+            assert_eq!(parse_ptr_name(name, 16), Err(ParsePtrError(())), "{name}");
+        }
+    }
+}
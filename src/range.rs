@@ -0,0 +1,381 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+};
+
+use crate::{arith, AnyIpFamily, Hosts, IpFamilyAddr, Network};
+
+/// An inclusive range of addresses of one family, such as
+/// `10.0.0.10-10.0.0.99`.
+pub struct AddrRange<F: AnyIpFamily> {
+    start: F::Addr,
+    end: F::Addr,
+}
+
+impl<F: AnyIpFamily> AddrRange<F> {
+    /// Returns `None` if `start > end`.
+    pub fn new(start: F::Addr, end: F::Addr) -> Option<Self> {
+        (start <= end).then_some(Self { start, end })
+    }
+
+    pub fn start(&self) -> F::Addr {
+        self.start
+    }
+
+    pub fn end(&self) -> F::Addr {
+        self.end
+    }
+
+    pub fn contains(&self, addr: F::Addr) -> bool {
+        self.start <= addr && addr <= self.end
+    }
+
+    /// The number of addresses, saturating at `u128::MAX` for the whole
+    /// IPv6 space.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u128 {
+        (arith::to_u128(self.end) - arith::to_u128(self.start)).saturating_add(1)
+    }
+
+    pub fn iter(&self) -> Hosts<F> {
+        Hosts::new(arith::to_u128(self.start), arith::to_u128(self.end))
+    }
+
+    /// The addresses in both ranges.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// One range covering both, `None` if they neither overlap nor touch.
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        let (first, second) = if self.start <= other.start {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let touches = first
+            .end
+            .checked_add(1)
+            .is_none_or(|after| second.start <= after);
+        touches.then(|| Self {
+            start: first.start,
+            end: first.end.max(second.end),
+        })
+    }
+
+    /// The fewest networks that together cover exactly this range, in
+    /// ascending order.
+    pub fn to_networks(&self) -> Vec<Network<F>> {
+        let width = F::Addr::BYTES as u32 * 8;
+        let (mut start, end) = (arith::to_u128(self.start), arith::to_u128(self.end));
+        let mut networks = Vec::new();
+        loop {
+            // The largest aligned block at `start` that does not pass `end`.
+            let mut host_bits = start.trailing_zeros().min(width);
+            let last = loop {
+                let last = start | u128::MAX.checked_shr(128 - host_bits).unwrap_or(0);
+                if last <= end {
+                    break last;
+                }
+                host_bits -= 1;
+            };
+            let addr = arith::from_u128(start).expect("start fits the family");
+            networks.push(Network::new(addr, (width - host_bits) as u8));
+            if last == end {
+                return networks;
+            }
+            start = last + 1;
+        }
+    }
+
+    /// Merges overlapping and adjacent ranges, in ascending order.
+    pub fn merge<I: IntoIterator<Item = Self>>(ranges: I) -> Vec<Self> {
+        let mut ranges: Vec<_> = ranges.into_iter().collect();
+        ranges.sort();
+        let mut merged: Vec<Self> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            if let Some(last) = merged.last_mut() {
+                if let Some(union) = last.union(&range) {
+                    *last = union;
+                    continue;
+                }
+            }
+            merged.push(range);
+        }
+        merged
+    }
+}
+
+impl<F: AnyIpFamily> Network<F> {
+    /// The fewest networks covering exactly the addresses of `networks`,
+    /// in ascending order.
+    pub fn aggregate<I: IntoIterator<Item = Self>>(networks: I) -> Vec<Self> {
+        AddrRange::merge(networks.into_iter().map(AddrRange::from))
+            .iter()
+            .flat_map(AddrRange::to_networks)
+            .collect()
+    }
+}
+
+impl<F: AnyIpFamily> From<Network<F>> for AddrRange<F> {
+    fn from(value: Network<F>) -> Self {
+        Self {
+            start: value.network(),
+            end: value.last(),
+        }
+    }
+}
+
+impl<F: AnyIpFamily> IntoIterator for AddrRange<F> {
+    type Item = F::Addr;
+    type IntoIter = Hosts<F>;
+
+    fn into_iter(self) -> Hosts<F> {
+        self.iter()
+    }
+}
+
+impl<F: AnyIpFamily> IntoIterator for &AddrRange<F> {
+    type Item = F::Addr;
+    type IntoIter = Hosts<F>;
+
+    fn into_iter(self) -> Hosts<F> {
+        self.iter()
+    }
+}
+
+// Implemented by hand so generic code needs no `F: Copy` etc. bounds.
+impl<F: AnyIpFamily> Clone for AddrRange<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: AnyIpFamily> Copy for AddrRange<F> {}
+
+impl<F: AnyIpFamily> PartialEq for AddrRange<F> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.start, self.end) == (other.start, other.end)
+    }
+}
+
+impl<F: AnyIpFamily> Eq for AddrRange<F> {}
+
+impl<F: AnyIpFamily> PartialOrd for AddrRange<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: AnyIpFamily> Ord for AddrRange<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.start, self.end).cmp(&(other.start, other.end))
+    }
+}
+
+impl<F: AnyIpFamily> Hash for AddrRange<F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.start, self.end).hash(state)
+    }
+}
+
+impl<F: AnyIpFamily> Debug for AddrRange<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "AddrRange({self})")
+    }
+}
+
+impl<F: AnyIpFamily> Display for AddrRange<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+#[cfg(all(test, feature = "v4", feature = "v6"))]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+    use crate::{IpFamilyV4, IpFamilyV6};
+
+    fn v4(start: &str, end: &str) -> AddrRange<IpFamilyV4> {
+        AddrRange::new(start.parse().unwrap(), end.parse().unwrap()).unwrap()
+    }
+
+    fn v6(start: &str, end: &str) -> AddrRange<IpFamilyV6> {
+        AddrRange::new(start.parse().unwrap(), end.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn new_rejects_reversed_bounds() {
+        let (low, high) = (crate::fixtures::V4_ADDR, crate::fixtures::V4_ADDR_2);
+        assert!(AddrRange::<IpFamilyV4>::new(low.max(high), low.min(high)).is_none());
+        let single = AddrRange::<IpFamilyV4>::new(low, low).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single.iter().collect::<Vec<_>>(), [low]);
+    }
+
+    #[test]
+    fn len_contains_and_iter() {
+        let range = v4("10.0.0.10", "10.0.0.99");
+        assert_eq!(range.len(), 90);
+        assert!(range.contains("10.0.0.10".parse().unwrap()));
+        assert!(range.contains("10.0.0.99".parse().unwrap()));
+        assert!(!range.contains("10.0.0.100".parse().unwrap()));
+        assert_eq!(range.iter().count(), 90);
+        assert_eq!(range.to_string(), "10.0.0.10-10.0.0.99");
+    }
+
+    #[test]
+    fn len_saturates_for_the_whole_v6_space() {
+        let all = AddrRange::<IpFamilyV6>::new(Ipv6Addr::UNSPECIFIED, u128::MAX.into()).unwrap();
+        assert_eq!(all.len(), u128::MAX);
+        let all = AddrRange::<IpFamilyV4>::new(Ipv4Addr::UNSPECIFIED, Ipv4Addr::BROADCAST).unwrap();
+        assert_eq!(all.len(), 1 << 32);
+    }
+
+    #[test]
+    fn iter_stops_at_the_top_of_the_space() {
+        let top = v6(
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe",
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+        );
+        assert_eq!(top.iter().count(), 2);
+        let top = v4("255.255.255.254", "255.255.255.255");
+        assert_eq!(top.into_iter().last(), Some(Ipv4Addr::BROADCAST));
+    }
+
+    #[test]
+    fn intersection() {
+        let a = v4("10.0.0.0", "10.0.0.50");
+        let b = v4("10.0.0.40", "10.0.0.99");
+        assert_eq!(a.intersection(&b), Some(v4("10.0.0.40", "10.0.0.50")));
+        assert_eq!(a.intersection(&v4("10.0.0.51", "10.0.0.60")), None);
+    }
+
+    #[test]
+    fn union_joins_overlapping_and_adjacent_ranges() {
+        let a = v4("10.0.0.0", "10.0.0.9");
+        assert_eq!(
+            a.union(&v4("10.0.0.10", "10.0.0.19")),
+            Some(v4("10.0.0.0", "10.0.0.19"))
+        );
+        assert_eq!(
+            v4("10.0.0.5", "10.0.0.30").union(&a),
+            Some(v4("10.0.0.0", "10.0.0.30"))
+        );
+        assert_eq!(a.union(&v4("10.0.0.11", "10.0.0.19")), None);
+    }
+
+    #[test]
+    fn union_at_the_top_of_the_space() {
+        let top = v6("ffff::", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff");
+        assert_eq!(top.union(&top), Some(top));
+        assert_eq!(top.union(&v6("::", "::1")), None);
+    }
+
+    #[test]
+    fn orders_by_start_then_end() {
+        let mut ranges = vec![
+            v4("10.0.0.5", "10.0.0.9"),
+            v4("10.0.0.0", "10.0.0.9"),
+            v4("10.0.0.0", "10.0.0.3"),
+        ];
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            [
+                v4("10.0.0.0", "10.0.0.3"),
+                v4("10.0.0.0", "10.0.0.9"),
+                v4("10.0.0.5", "10.0.0.9"),
+            ]
+        );
+    }
+
+    #[test]
+    fn from_network() {
+        let range = AddrRange::from("192.0.2.0/24".parse::<Network<IpFamilyV4>>().unwrap());
+        assert_eq!(range, v4("192.0.2.0", "192.0.2.255"));
+    }
+
+    fn v4_nets(list: &[&str]) -> Vec<Network<IpFamilyV4>> {
+        list.iter().map(|net| net.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn to_networks_splits_unaligned_ranges() {
+        assert_eq!(
+            v4("10.0.0.10", "10.0.0.99").to_networks(),
+            v4_nets(&[
+                "10.0.0.10/31",
+                "10.0.0.12/30",
+                "10.0.0.16/28",
+                "10.0.0.32/27",
+                "10.0.0.64/27",
+                "10.0.0.96/30",
+            ])
+        );
+        assert_eq!(
+            v4("192.0.2.7", "192.0.2.7").to_networks(),
+            v4_nets(&["192.0.2.7/32"])
+        );
+    }
+
+    #[test]
+    fn to_networks_of_whole_spaces() {
+        assert_eq!(
+            v4("0.0.0.0", "255.255.255.255").to_networks(),
+            v4_nets(&["0.0.0.0/0"])
+        );
+        let all = v6("::", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff");
+        assert_eq!(
+            all.to_networks(),
+            ["::/0".parse::<Network<IpFamilyV6>>().unwrap()]
+        );
+        // Ends at the top of the space without overflowing.
+        assert_eq!(
+            v6("::1", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")
+                .to_networks()
+                .len(),
+            128
+        );
+    }
+
+    #[test]
+    fn merge_joins_overlapping_and_adjacent_ranges() {
+        let merged = AddrRange::merge([
+            v4("10.0.0.20", "10.0.0.29"),
+            v4("10.0.0.0", "10.0.0.9"),
+            v4("10.0.0.10", "10.0.0.14"),
+            v4("10.0.0.5", "10.0.0.12"),
+            v4("10.0.1.0", "10.0.1.0"),
+        ]);
+        assert_eq!(
+            merged,
+            [
+                v4("10.0.0.0", "10.0.0.14"),
+                v4("10.0.0.20", "10.0.0.29"),
+                v4("10.0.1.0", "10.0.1.0"),
+            ]
+        );
+        assert!(AddrRange::<IpFamilyV4>::merge([]).is_empty());
+    }
+
+    #[test]
+    fn aggregate_collapses_sibling_networks() {
+        assert_eq!(
+            Network::aggregate(v4_nets(&[
+                "192.0.2.128/25",
+                "192.0.2.0/25",
+                "192.0.2.64/26",
+                "198.51.100.0/24",
+            ])),
+            v4_nets(&["192.0.2.0/24", "198.51.100.0/24"])
+        );
+        assert_eq!(
+            Network::aggregate(v4_nets(&["10.0.0.0/8", "0.0.0.0/0"])),
+            v4_nets(&["0.0.0.0/0"])
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+};
+
+use crate::sealed;
+
+/// How many leading bits of an address [`Redacted`] keeps visible.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RedactionPolicy {
+    v4_prefix: u8,
+    v6_prefix: u8,
+}
+
+impl RedactionPolicy {
+    /// Keeps the /24 of IPv4 and the /48 of IPv6 addresses.
+    pub const DEFAULT: Self = Self::new(24, 48);
+    /// Hides the whole address.
+    pub const FULL: Self = Self::new(0, 0);
+
+    /// Prefix lengths above 32 / 128 are clamped. IPv4 prefixes are rounded
+    /// down to whole octets.
+    pub const fn new(v4_prefix: u8, v6_prefix: u8) -> Self {
+        Self {
+            v4_prefix: if v4_prefix > 32 { 32 } else { v4_prefix },
+            v6_prefix: if v6_prefix > 128 { 128 } else { v6_prefix },
+        }
+    }
+
+    pub const fn v4_prefix(&self) -> u8 {
+        self.v4_prefix
+    }
+
+    pub const fn v6_prefix(&self) -> u8 {
+        self.v6_prefix
+    }
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+pub trait Redact: sealed::Sealed {
+    fn fmt_redacted(&self, policy: RedactionPolicy, f: &mut Formatter<'_>) -> fmt::Result;
+}
+
+/// Wraps an address so that `Display`, `Debug` and `Serialize` only ever
+/// output the part allowed by its [`RedactionPolicy`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Redacted<T> {
+    value: T,
+    policy: RedactionPolicy,
+}
+
+impl<T: Redact> Redacted<T> {
+    pub const fn new(value: T) -> Self {
+        Self::with_policy(value, RedactionPolicy::DEFAULT)
+    }
+
+    pub const fn with_policy(value: T, policy: RedactionPolicy) -> Self {
+        Self { value, policy }
+    }
+
+    pub const fn policy(&self) -> RedactionPolicy {
+        self.policy
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Redact> Display for Redacted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.value.fmt_redacted(self.policy, f)
+    }
+}
+
+impl<T: Redact> Debug for Redacted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.value.fmt_redacted(self.policy, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Redact> serde::Serialize for Redacted<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Redact for Ipv4Addr {
+    fn fmt_redacted(&self, policy: RedactionPolicy, f: &mut Formatter<'_>) -> fmt::Result {
+        let visible = usize::from(policy.v4_prefix / 8);
+        for (i, octet) in self.octets().iter().enumerate() {
+            if i != 0 {
+                f.write_str(".")?;
+            }
+            if i < visible {
+                write!(f, "{octet}")?;
+            } else {
+                f.write_str("xxx")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Redact for Ipv6Addr {
+    fn fmt_redacted(&self, policy: RedactionPolicy, f: &mut Formatter<'_>) -> fmt::Result {
+        let prefix = policy.v6_prefix;
+        if prefix == 128 {
+            return write!(f, "{self}");
+        }
+        let mask = u128::MAX.checked_shl(u32::from(128 - prefix)).unwrap_or(0);
+        write!(f, "{}/{prefix}", Ipv6Addr::from(u128::from(*self) & mask))
+    }
+}
+
+impl Redact for IpAddr {
+    fn fmt_redacted(&self, policy: RedactionPolicy, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            IpAddr::V4(addr) => addr.fmt_redacted(policy, f),
+            IpAddr::V6(addr) => addr.fmt_redacted(policy, f),
+        }
+    }
+}
+
+impl Redact for SocketAddrV4 {
+    fn fmt_redacted(&self, policy: RedactionPolicy, f: &mut Formatter<'_>) -> fmt::Result {
+        self.ip().fmt_redacted(policy, f)?;
+        write!(f, ":{}", self.port())
+    }
+}
+
+impl Redact for SocketAddrV6 {
+    fn fmt_redacted(&self, policy: RedactionPolicy, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        self.ip().fmt_redacted(policy, f)?;
+        write!(f, "]:{}", self.port())
+    }
+}
+
+impl Redact for SocketAddr {
+    fn fmt_redacted(&self, policy: RedactionPolicy, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketAddr::V4(addr) => addr.fmt_redacted(policy, f),
+            SocketAddr::V6(addr) => addr.fmt_redacted(policy, f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redacted<T: Redact>(value: T, policy: RedactionPolicy) -> String {
+        Redacted::with_policy(value, policy).to_string()
+    }
+
+    #[test]
+    fn ipv4_keeps_whole_octets() {
+        let addr = Ipv4Addr::new(203, 0, 113, 42);
+        assert_eq!(Redacted::new(addr).to_string(), "203.0.113.xxx");
+        assert_eq!(
+            redacted(addr, RedactionPolicy::new(20, 48)),
+            "203.0.xxx.xxx"
+        );
+        assert_eq!(redacted(addr, RedactionPolicy::FULL), "xxx.xxx.xxx.xxx");
+        assert_eq!(redacted(addr, RedactionPolicy::new(40, 48)), "203.0.113.42");
+    }
+
+    #[test]
+    fn ipv6_is_truncated_to_a_prefix() {
+        let addr: Ipv6Addr = "2001:db8:1:2:3:4:5:6".parse().unwrap();
+        assert_eq!(Redacted::new(addr).to_string(), "2001:db8:1::/48");
+        assert_eq!(
+            redacted(addr, RedactionPolicy::new(24, 64)),
+            "2001:db8:1:2::/64"
+        );
+        assert_eq!(redacted(addr, RedactionPolicy::FULL), "::/0");
+        assert_eq!(
+            redacted(addr, RedactionPolicy::new(24, 200)),
+            "2001:db8:1:2:3:4:5:6"
+        );
+    }
+
+    #[test]
+    fn mapped_ipv4_follows_the_ipv6_policy() {
+        let addr = IpAddr::V6(Ipv4Addr::new(203, 0, 113, 42).to_ipv6_mapped());
+        assert_eq!(Redacted::new(addr).to_string(), "::/48");
+        assert_eq!(
+            redacted(addr, RedactionPolicy::new(24, 120)),
+            "::ffff:203.0.113.0/120"
+        );
+    }
+
+    #[test]
+    fn socket_addresses_keep_the_port() {
+        let v4 = SocketAddr::from((Ipv4Addr::new(192, 0, 2, 1), 443));
+        assert_eq!(Redacted::new(v4).to_string(), "192.0.2.xxx:443");
+        let v6 = SocketAddr::from(("2001:db8::1".parse::<Ipv6Addr>().unwrap(), 443));
+        assert_eq!(Redacted::new(v6).to_string(), "[2001:db8::/48]:443");
+    }
+
+    #[test]
+    fn debug_matches_display() {
+        let addr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        let value = Redacted::new(addr);
+        assert_eq!(format!("{value:?}"), "192.0.2.xxx");
+        assert_eq!(format!("{value:#?}"), value.to_string());
+        assert_eq!(value.policy(), RedactionPolicy::default());
+        assert_eq!(value.into_inner(), addr);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_the_redacted_string() {
+        let value = Redacted::new(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(serde_json::to_string(&value).unwrap(), r#""192.0.2.xxx""#);
+    }
+}
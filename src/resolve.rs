@@ -0,0 +1,380 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    io,
+    net::IpAddr,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use crate::{AnyIpFamily, FamilyCounters, FamilyPreference, IpFamily, IpFamilyAddr};
+
+/// A DNS client that can query a single record type, A for IPv4 and AAAA
+/// for IPv6, unlike the std resolver.
+///
+/// Lookups of a host that does not exist fail with
+/// [`io::ErrorKind::NotFound`]. A host without records of the family
+/// answers with no addresses, so the resolve functions can fail over to the
+/// other family.
+pub trait Resolver {
+    fn lookup(
+        &self,
+        host: &str,
+        family: IpFamily,
+    ) -> impl Future<Output = io::Result<Vec<IpAddr>>> + Send;
+}
+
+/// A [`Resolver`] that records every query in [`FamilyCounters`], for use
+/// with any of the resolve functions. Failed queries count as failures,
+/// empty answers as successes.
+#[derive(Debug)]
+pub struct InstrumentedResolver<'a, R> {
+    inner: R,
+    counters: &'a FamilyCounters,
+}
+
+impl<'a, R: Resolver> InstrumentedResolver<'a, R> {
+    pub fn new(inner: R, counters: &'a FamilyCounters) -> Self {
+        Self { inner, counters }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Resolver> Resolver for InstrumentedResolver<'_, R> {
+    fn lookup(
+        &self,
+        host: &str,
+        family: IpFamily,
+    ) -> impl Future<Output = io::Result<Vec<IpAddr>>> + Send {
+        let counters = self.counters;
+        counters.record_attempt(family);
+        let started = Instant::now();
+        let lookup = self.inner.lookup(host, family);
+        async move {
+            let result = lookup.await;
+            match &result {
+                Ok(_) => counters.record_success(family, started.elapsed()),
+                Err(_) => counters.record_failure(family, started.elapsed()),
+            }
+            result
+        }
+    }
+}
+
+/// Queries only the records of `family`. Addresses of the other family
+/// returned by the resolver are dropped.
+pub async fn resolve_family<R: Resolver>(
+    resolver: &R,
+    host: &str,
+    family: IpFamily,
+) -> io::Result<Vec<IpAddr>> {
+    let mut addrs = resolver.lookup(host, family).await?;
+    addrs.retain(|addr| family.matches(addr));
+    Ok(addrs)
+}
+
+/// Like [`resolve_family`], typed as `F::Addr`.
+pub async fn resolve<F: AnyIpFamily, R: Resolver>(
+    resolver: &R,
+    host: &str,
+) -> io::Result<Vec<F::Addr>> {
+    let addrs = resolver.lookup(host, F::FAMILY).await?;
+    Ok(addrs
+        .into_iter()
+        .filter_map(|addr| F::Addr::try_from_ip_addr(addr).ok())
+        .collect())
+}
+
+/// Queries every family `preference` allows, one after the other, and
+/// returns the addresses of the preferred family first.
+///
+/// A family whose query fails is skipped, so a broken AAAA lookup still
+/// leaves the IPv4 addresses to connect to. Fails with the last error only
+/// if every query failed.
+pub async fn resolve_with_preference<R: Resolver>(
+    resolver: &R,
+    host: &str,
+    preference: FamilyPreference,
+) -> io::Result<Vec<IpAddr>> {
+    let mut addrs = Vec::new();
+    let mut last_err = None;
+    let mut answered = false;
+    for family in preference.families() {
+        match resolve_family(resolver, host, family).await {
+            Ok(found) => {
+                answered = true;
+                addrs.extend(found);
+            }
+            Err(err) => last_err = Some(err),
+        }
+    }
+    match last_err {
+        Some(err) if !answered => Err(err),
+        _ => Ok(addrs),
+    }
+}
+
+type Lookup<'a> = Pin<Box<dyn Future<Output = io::Result<Vec<IpAddr>>> + Send + 'a>>;
+
+/// Addresses from concurrent queries of every family `preference` allows,
+/// yielded as soon as each answer arrives, see [`resolve_stream`].
+///
+/// `poll_next` has the signature of `futures::Stream::poll_next`, so
+/// `futures::stream::poll_fn` turns this into a `Stream`.
+pub struct ResolveStream<'a> {
+    /// Pending queries, preferred family first.
+    lookups: Vec<(IpFamily, Lookup<'a>)>,
+    ready: VecDeque<IpAddr>,
+    answered: bool,
+    last_err: Option<io::Error>,
+}
+
+impl ResolveStream<'_> {
+    /// Answers that arrive together are yielded preferred family first.
+    /// Ends with the last error if every query failed, and quietly if any
+    /// query answered.
+    pub fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<io::Result<IpAddr>>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(addr) = this.ready.pop_front() {
+                return Poll::Ready(Some(Ok(addr)));
+            }
+            if this.lookups.is_empty() {
+                let err = this.last_err.take().filter(|_| !this.answered);
+                return Poll::Ready(err.map(Err));
+            }
+            let pending = this.lookups.len();
+            let (ready, last_err, answered) =
+                (&mut this.ready, &mut this.last_err, &mut this.answered);
+            this.lookups
+                .retain_mut(|(family, lookup)| match lookup.as_mut().poll(cx) {
+                    Poll::Pending => true,
+                    Poll::Ready(Ok(addrs)) => {
+                        *answered = true;
+                        ready.extend(addrs.into_iter().filter(|addr| family.matches(addr)));
+                        false
+                    }
+                    Poll::Ready(Err(err)) => {
+                        *last_err = Some(err);
+                        false
+                    }
+                });
+            if this.lookups.len() == pending {
+                return Poll::Pending;
+            }
+        }
+    }
+
+    pub async fn next(&mut self) -> Option<io::Result<IpAddr>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+/// Like [`resolve_with_preference`], but runs the queries concurrently and
+/// yields each family's addresses as soon as they arrive, so a connector
+/// can start on AAAA answers before the A query finishes or vice versa.
+pub fn resolve_stream<'a, R: Resolver>(
+    resolver: &'a R,
+    host: &'a str,
+    preference: FamilyPreference,
+) -> ResolveStream<'a> {
+    ResolveStream {
+        lookups: preference
+            .families()
+            .map(|family| {
+                (
+                    family,
+                    Box::pin(resolver.lookup(host, family)) as Lookup<'a>,
+                )
+            })
+            .collect(),
+        ready: VecDeque::new(),
+        answered: false,
+        last_err: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        fixtures::{V4_ADDR, V6_ADDR, V6_ADDR_2},
+        MockResolver,
+    };
+
+    const HOST: &str = "dual.test";
+
+    fn dual_stack() -> MockResolver {
+        MockResolver::new()
+            .with_answer(HOST, IpFamily::V4, [V4_ADDR.into()])
+            .with_answer(HOST, IpFamily::V6, [V6_ADDR.into(), V6_ADDR_2.into()])
+    }
+
+    #[tokio::test]
+    async fn resolve_family_queries_one_family() {
+        // A resolver that answers AAAA queries with an A record.
+        let resolver = dual_stack().with_answer(HOST, IpFamily::V6, [V4_ADDR.into()]);
+        let addrs = resolve_family(&resolver, HOST, IpFamily::V6).await.unwrap();
+        assert_eq!(addrs, [IpAddr::from(V6_ADDR), V6_ADDR_2.into()]);
+        assert_eq!(resolver.queries(), [(HOST.to_owned(), IpFamily::V6)]);
+    }
+
+    #[tokio::test]
+    async fn unknown_hosts_fail_and_missing_records_are_empty() {
+        let resolver = MockResolver::new().with_answer("v6.test", IpFamily::V6, [V6_ADDR.into()]);
+        let err = resolve_family(&resolver, "unknown.test", IpFamily::V6)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        let err = resolve_with_preference(&resolver, "unknown.test", FamilyPreference::Any)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        let addrs = resolve_family(&resolver, "v6.test", IpFamily::V4)
+            .await
+            .unwrap();
+        assert!(addrs.is_empty());
+        let addrs = resolve_with_preference(&resolver, "v6.test", FamilyPreference::PreferV4)
+            .await
+            .unwrap();
+        assert_eq!(addrs, [IpAddr::from(V6_ADDR)]);
+    }
+
+    #[cfg(feature = "v4")]
+    #[tokio::test]
+    async fn resolve_is_typed() {
+        let addrs: Vec<std::net::Ipv4Addr> = resolve::<crate::IpFamilyV4, _>(&dual_stack(), HOST)
+            .await
+            .unwrap();
+        assert_eq!(addrs, [V4_ADDR]);
+    }
+
+    #[tokio::test]
+    async fn preference_orders_and_filters() {
+        let resolver = dual_stack();
+        let addrs = resolve_with_preference(&resolver, HOST, FamilyPreference::PreferV4)
+            .await
+            .unwrap();
+        assert_eq!(
+            addrs,
+            [IpAddr::from(V4_ADDR), V6_ADDR.into(), V6_ADDR_2.into()]
+        );
+
+        let resolver = dual_stack();
+        let addrs = resolve_with_preference(&resolver, HOST, FamilyPreference::V6Only)
+            .await
+            .unwrap();
+        assert_eq!(addrs, [IpAddr::from(V6_ADDR), V6_ADDR_2.into()]);
+        assert_eq!(resolver.queries(), [(HOST.to_owned(), IpFamily::V6)]);
+    }
+
+    #[tokio::test]
+    async fn preference_fails_over_to_the_family_that_answers() {
+        let resolver = dual_stack().with_error(HOST, IpFamily::V6, io::ErrorKind::TimedOut);
+        let addrs = resolve_with_preference(&resolver, HOST, FamilyPreference::Any)
+            .await
+            .unwrap();
+        assert_eq!(addrs, [IpAddr::from(V4_ADDR)]);
+
+        let resolver = resolver.with_error(HOST, IpFamily::V4, io::ErrorKind::ConnectionRefused);
+        let err = resolve_with_preference(&resolver, HOST, FamilyPreference::Any)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+    }
+
+    async fn collect(mut stream: ResolveStream<'_>) -> Vec<io::Result<IpAddr>> {
+        let mut items = Vec::new();
+        while let Some(item) = stream.next().await {
+            items.push(item);
+        }
+        items
+    }
+
+    async fn collect_ok(stream: ResolveStream<'_>) -> Vec<IpAddr> {
+        collect(stream)
+            .await
+            .into_iter()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn stream_yields_the_first_answer_first() {
+        let resolver = dual_stack().with_delay(HOST, IpFamily::V6, Duration::from_millis(100));
+        let addrs = collect_ok(resolve_stream(&resolver, HOST, FamilyPreference::PreferV6)).await;
+        assert_eq!(
+            addrs,
+            [IpAddr::from(V4_ADDR), V6_ADDR.into(), V6_ADDR_2.into()]
+        );
+
+        let resolver = dual_stack().with_delay(HOST, IpFamily::V4, Duration::from_millis(100));
+        let addrs = collect_ok(resolve_stream(&resolver, HOST, FamilyPreference::PreferV4)).await;
+        assert_eq!(
+            addrs,
+            [IpAddr::from(V6_ADDR), V6_ADDR_2.into(), V4_ADDR.into()]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_orders_simultaneous_answers_by_preference() {
+        let resolver = dual_stack();
+        let addrs = collect_ok(resolve_stream(&resolver, HOST, FamilyPreference::PreferV4)).await;
+        assert_eq!(
+            addrs,
+            [IpAddr::from(V4_ADDR), V6_ADDR.into(), V6_ADDR_2.into()]
+        );
+        let addrs = collect_ok(resolve_stream(&resolver, HOST, FamilyPreference::V4Only)).await;
+        assert_eq!(addrs, [IpAddr::from(V4_ADDR)]);
+    }
+
+    #[tokio::test]
+    async fn stream_errors_only_if_every_query_fails() {
+        let resolver = dual_stack().with_error(HOST, IpFamily::V6, io::ErrorKind::TimedOut);
+        let addrs = collect_ok(resolve_stream(&resolver, HOST, FamilyPreference::Any)).await;
+        assert_eq!(addrs, [IpAddr::from(V4_ADDR)]);
+
+        let items = collect(resolve_stream(
+            &resolver,
+            "unknown.test",
+            FamilyPreference::Any,
+        ))
+        .await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0].as_ref().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn instrumented_resolver_records_queries() {
+        let counters = FamilyCounters::new();
+        let resolver = InstrumentedResolver::new(
+            dual_stack().with_error(HOST, IpFamily::V6, io::ErrorKind::TimedOut),
+            &counters,
+        );
+        let addrs = resolve_with_preference(&resolver, HOST, FamilyPreference::Any)
+            .await
+            .unwrap();
+        assert_eq!(addrs, [IpAddr::from(V4_ADDR)]);
+        resolve_family(&resolver, "unknown.test", IpFamily::V4)
+            .await
+            .unwrap_err();
+        let v4 = counters.snapshot(IpFamily::V4);
+        assert_eq!((v4.attempts, v4.successes, v4.failures), (2, 1, 1));
+        let v6 = counters.snapshot(IpFamily::V6);
+        assert_eq!((v6.attempts, v6.successes, v6.failures), (1, 0, 1));
+        assert_eq!(resolver.into_inner().queries().len(), 3);
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    cmp::Reverse,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+};
+
+use crate::{FamilyPreference, IpFamily};
+
+/// `(prefix, prefix_len, precedence)` from the RFC 6724 default policy
+/// table, longest prefixes first.
+const POLICY: &[(u128, u8, u8)] = &[
+    (0x0000_0000_0000_0000_0000_0000_0000_0001, 128, 50),
+    (0x0000_0000_0000_0000_0000_ffff_0000_0000, 96, 35),
+    (0x0000_0000_0000_0000_0000_0000_0000_0000, 96, 1),
+    (0x2001_0000_0000_0000_0000_0000_0000_0000, 32, 5),
+    (0x2002_0000_0000_0000_0000_0000_0000_0000, 16, 30),
+    (0x3ffe_0000_0000_0000_0000_0000_0000_0000, 16, 1),
+    (0xfec0_0000_0000_0000_0000_0000_0000_0000, 10, 1),
+    (0xfc00_0000_0000_0000_0000_0000_0000_0000, 7, 3),
+    (0x0000_0000_0000_0000_0000_0000_0000_0000, 0, 40),
+];
+
+fn precedence(addr: Ipv6Addr) -> u8 {
+    let bits = addr.to_bits();
+    POLICY
+        .iter()
+        .find(|&&(prefix, len, _)| {
+            let mask = u128::MAX.checked_shl(u32::from(128 - len)).unwrap_or(0);
+            bits & mask == prefix
+        })
+        .map_or(40, |&(_, _, precedence)| precedence)
+}
+
+/// Scope values as in RFC 6724 §3.1, IPv4 loopback and link-local count as
+/// link-local.
+fn scope(addr: Ipv6Addr) -> u8 {
+    if let Some(v4) = addr.to_ipv4_mapped() {
+        return if v4.is_loopback() || v4.is_link_local() {
+            0x2
+        } else {
+            0xe
+        };
+    }
+    if addr.is_multicast() {
+        (addr.segments()[0] & 0x000f) as u8
+    } else if addr.is_loopback() || addr.is_unicast_link_local() {
+        0x2
+    } else if addr.segments()[0] & 0xffc0 == 0xfec0 {
+        0x5
+    } else {
+        0xe
+    }
+}
+
+fn as_v6(addr: IpAddr) -> Ipv6Addr {
+    match addr {
+        IpAddr::V4(addr) => addr.to_ipv6_mapped(),
+        IpAddr::V6(addr) => addr,
+    }
+}
+
+fn sort_key(addr: &SocketAddr, preference: FamilyPreference) -> (bool, Reverse<u8>, u8) {
+    let ip = as_v6(addr.ip());
+    let demoted = preference
+        .preferred()
+        .is_some_and(|family| IpFamily::from(*addr) != family);
+    (demoted, Reverse(precedence(ip)), scope(ip))
+}
+
+/// Sorts `addrs` by the RFC 6724 destination address selection rules that do
+/// not need a source address: higher precedence first (rule 6), then
+/// smaller scope (rule 8), otherwise keeping the given order (rule 10).
+pub fn sort_addrs_rfc6724(addrs: &mut [SocketAddr]) {
+    sort_addrs_rfc6724_with(addrs, FamilyPreference::Any);
+}
+
+/// Like [`sort_addrs_rfc6724`], putting the preferred family first.
+/// Addresses of a family that is not allowed are kept, see
+/// [`sorted_rfc6724`] to drop them.
+pub fn sort_addrs_rfc6724_with(addrs: &mut [SocketAddr], preference: FamilyPreference) {
+    addrs.sort_by_key(|addr| sort_key(addr, preference));
+}
+
+/// Collects the addresses `preference` allows and yields them in
+/// [`sort_addrs_rfc6724_with`] order.
+pub fn sorted_rfc6724<I>(addrs: I, preference: FamilyPreference) -> std::vec::IntoIter<SocketAddr>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let mut addrs: Vec<_> = preference.filter(addrs).collect();
+    sort_addrs_rfc6724_with(&mut addrs, preference);
+    addrs.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs(list: &[&str]) -> Vec<SocketAddr> {
+        list.iter()
+            .map(|ip| SocketAddr::new(ip.parse().unwrap(), 443))
+            .collect()
+    }
+
+    #[test]
+    fn default_policy_precedence() {
+        let precedence = |ip: &str| precedence(as_v6(ip.parse().unwrap()));
+        assert_eq!(precedence("::1"), 50);
+        assert_eq!(precedence("2001:db8::1"), 40);
+        assert_eq!(precedence("192.0.2.1"), 35);
+        assert_eq!(precedence("2002:c000:201::1"), 30);
+        assert_eq!(precedence("2001::1"), 5);
+        assert_eq!(precedence("fd00::1"), 3);
+        assert_eq!(precedence("::192.0.2.1"), 1);
+        assert_eq!(precedence("fec0::1"), 1);
+        assert_eq!(precedence("3ffe::1"), 1);
+        assert_eq!(precedence("::"), 1);
+    }
+
+    #[test]
+    fn scopes() {
+        let scope = |ip: &str| scope(as_v6(ip.parse().unwrap()));
+        assert_eq!(scope("fe80::1"), 0x2);
+        assert_eq!(scope("::1"), 0x2);
+        assert_eq!(scope("127.0.0.1"), 0x2);
+        assert_eq!(scope("169.254.0.1"), 0x2);
+        assert_eq!(scope("fec0::1"), 0x5);
+        assert_eq!(scope("ff05::1"), 0x5);
+        assert_eq!(scope("192.0.2.1"), 0xe);
+        assert_eq!(scope("2001:db8::1"), 0xe);
+    }
+
+    #[test]
+    fn sorts_by_precedence() {
+        let mut list = addrs(&[
+            "fd00::1",
+            "2001::1",
+            "2002:c000:201::1",
+            "192.0.2.1",
+            "2001:db8::1",
+            "::1",
+        ]);
+        sort_addrs_rfc6724(&mut list);
+        assert_eq!(
+            list,
+            addrs(&[
+                "::1",
+                "2001:db8::1",
+                "192.0.2.1",
+                "2002:c000:201::1",
+                "2001::1",
+                "fd00::1",
+            ])
+        );
+    }
+
+    #[test]
+    fn equal_precedence_prefers_smaller_scope_then_keeps_order() {
+        let mut list = addrs(&["2001:db8::2", "fe80::1", "2001:db8::1"]);
+        sort_addrs_rfc6724(&mut list);
+        assert_eq!(list, addrs(&["fe80::1", "2001:db8::2", "2001:db8::1"]));
+        // IPv4-mapped addresses sort as IPv4.
+        let mut list = addrs(&["::ffff:192.0.2.2", "192.0.2.1"]);
+        sort_addrs_rfc6724(&mut list);
+        assert_eq!(list, addrs(&["::ffff:192.0.2.2", "192.0.2.1"]));
+    }
+
+    #[test]
+    fn preferred_family_goes_first() {
+        let mut list = addrs(&["2001:db8::1", "192.0.2.1", "::1", "10.0.0.1"]);
+        sort_addrs_rfc6724_with(&mut list, FamilyPreference::PreferV4);
+        assert_eq!(
+            list,
+            addrs(&["192.0.2.1", "10.0.0.1", "::1", "2001:db8::1"])
+        );
+        sort_addrs_rfc6724_with(&mut list, FamilyPreference::V4Only);
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn sorted_drops_disallowed_families() {
+        let list = addrs(&["192.0.2.1", "2001::1", "2001:db8::1"]);
+        assert_eq!(
+            sorted_rfc6724(list.clone(), FamilyPreference::V6Only).collect::<Vec<_>>(),
+            addrs(&["2001:db8::1", "2001::1"])
+        );
+        assert_eq!(
+            sorted_rfc6724(list, FamilyPreference::Any).collect::<Vec<_>>(),
+            addrs(&["2001:db8::1", "192.0.2.1", "2001::1"])
+        );
+    }
+}
@@ -0,0 +1,547 @@
+//! `serde` support for code generic over [`AnyIpFamily`](crate::AnyIpFamily).
+//!
+//! `F::Addr` and `F::SocketAddr` are not known to implement `Serialize`, so
+//! fields of those types name one of these modules:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Listen<F: AnyIpFamily> {
+//!     #[serde(with = "ip_family::serde_with::socket_addr")]
+//!     bind: F::SocketAddr,
+//! }
+//! ```
+//!
+//! [`addr_bytes`], [`addr_int`] and [`addr_str`] pick a fixed encoding
+//! instead of following `IpAddr`.
+
+use std::fmt::{self, Formatter};
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::IpFamily;
+
+/// For fields of type `F::Addr`, encoded like `IpAddr`.
+pub mod addr {
+    use std::net::IpAddr;
+
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{AnyIpFamily, IpFamilyAddr};
+
+    pub fn serialize<A: IpFamilyAddr, S: Serializer>(
+        addr: &A,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        (*addr).into().serialize(serializer)
+    }
+
+    /// Fails on addresses of the other family.
+    pub fn deserialize<'de, A: IpFamilyAddr, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<A, D::Error> {
+        let addr = IpAddr::deserialize(deserializer)?;
+        A::try_from_ip_addr(addr).map_err(|_| {
+            de::Error::custom(format_args!(
+                "expected an {} address, got {addr}",
+                <A::Family as AnyIpFamily>::FAMILY
+            ))
+        })
+    }
+}
+
+/// For fields of type `F::SocketAddr`, encoded like `SocketAddr`.
+pub mod socket_addr {
+    use std::net::SocketAddr;
+
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{AnyIpFamily, IpFamilySocketAddr};
+
+    pub fn serialize<A: IpFamilySocketAddr, S: Serializer>(
+        addr: &A,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        (*addr).into().serialize(serializer)
+    }
+
+    /// Fails on socket addresses of the other family.
+    pub fn deserialize<'de, A: IpFamilySocketAddr, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<A, D::Error> {
+        let addr = SocketAddr::deserialize(deserializer)?;
+        A::try_from_socket_addr(addr).map_err(|_| {
+            de::Error::custom(format_args!(
+                "expected an {} socket address, got {addr}",
+                <A::Family as AnyIpFamily>::FAMILY
+            ))
+        })
+    }
+}
+
+/// For fields of type `F::Addr`, encoded as the 4 or 16 address bytes in
+/// every format.
+pub mod addr_bytes {
+    use std::{
+        fmt::{self, Formatter},
+        marker::PhantomData,
+    };
+
+    use serde::{
+        de::{self, SeqAccess, Visitor},
+        Deserializer, Serializer,
+    };
+
+    use crate::IpFamilyAddr;
+
+    pub fn serialize<A: IpFamilyAddr, S: Serializer>(
+        addr: &A,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(addr.octets().as_ref())
+    }
+
+    /// Also accepts a sequence of bytes, as JSON has no byte strings.
+    pub fn deserialize<'de, A: IpFamilyAddr, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<A, D::Error> {
+        struct BytesVisitor<A>(PhantomData<A>);
+
+        impl<'de, A: IpFamilyAddr> Visitor<'de> for BytesVisitor<A> {
+            type Value = A;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write!(f, "{} address bytes", A::BYTES)
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<A, E> {
+                A::from_byte_slice(v).map_err(|_| E::invalid_length(v.len(), &self))
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<A, S::Error> {
+                let mut bytes = A::Bytes::default();
+                for (i, byte) in bytes.as_mut().iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::invalid_length(A::BYTES + 1, &self));
+                }
+                Ok(A::from(bytes))
+            }
+        }
+
+        deserializer.deserialize_bytes(BytesVisitor(PhantomData))
+    }
+}
+
+/// For fields of type `F::Addr`, encoded as a big-endian `u32` for IPv4 and
+/// `u128` for IPv6.
+pub mod addr_int {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::{AnyIpFamily, IpFamily, IpFamilyAddr};
+
+    pub fn serialize<A: IpFamilyAddr, S: Serializer>(
+        addr: &A,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let octets = addr.octets();
+        match <A::Family as AnyIpFamily>::FAMILY {
+            IpFamily::V4 => serializer.serialize_u32(u32::from_be_bytes(
+                octets
+                    .as_ref()
+                    .try_into()
+                    .expect("IPv4 addresses have 4 bytes"),
+            )),
+            IpFamily::V6 => serializer.serialize_u128(u128::from_be_bytes(
+                octets
+                    .as_ref()
+                    .try_into()
+                    .expect("IPv6 addresses have 16 bytes"),
+            )),
+        }
+    }
+
+    pub fn deserialize<'de, A: IpFamilyAddr, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<A, D::Error> {
+        let addr = match <A::Family as AnyIpFamily>::FAMILY {
+            IpFamily::V4 => A::from_byte_slice(&u32::deserialize(deserializer)?.to_be_bytes()),
+            IpFamily::V6 => A::from_byte_slice(&u128::deserialize(deserializer)?.to_be_bytes()),
+        };
+        Ok(addr.expect("the integer has the address width"))
+    }
+}
+
+/// For fields of type `F::Addr`, encoded as a string even in binary formats.
+pub mod addr_str {
+    use std::{borrow::Cow, net::IpAddr};
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    use crate::{AnyIpFamily, IpFamilyAddr};
+
+    pub fn serialize<A: IpFamilyAddr, S: Serializer>(
+        addr: &A,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(addr)
+    }
+
+    /// Fails on addresses of the other family.
+    pub fn deserialize<'de, A: IpFamilyAddr, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<A, D::Error> {
+        let s = <Cow<'de, str>>::deserialize(deserializer)?;
+        let addr: IpAddr = s.parse().map_err(de::Error::custom)?;
+        A::try_from_ip_addr(addr).map_err(|_| {
+            de::Error::custom(format_args!(
+                "expected an {} address, got {addr}",
+                <A::Family as AnyIpFamily>::FAMILY
+            ))
+        })
+    }
+}
+
+/// For fields of type [`PerFamily<T>`](crate::PerFamily), encoded as a map
+/// with the keys `v4` and `v6`.
+pub mod per_family_map {
+    use std::{
+        fmt::{self, Formatter},
+        marker::PhantomData,
+    };
+
+    use serde::{
+        de::{self, MapAccess, Visitor},
+        ser::SerializeMap,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use crate::{IpFamily, PerFamily};
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        value: &PerFamily<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        for (family, value) in value.iter() {
+            map.serialize_entry(&family, value)?;
+        }
+        map.end()
+    }
+
+    /// Both keys are required.
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<PerFamily<T>, D::Error> {
+        struct MapVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for MapVisitor<T> {
+            type Value = PerFamily<T>;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("a map with the keys \"v4\" and \"v6\"")
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<PerFamily<T>, M::Error> {
+                let (mut v4, mut v6) = (None, None);
+                while let Some(family) = map.next_key::<IpFamily>()? {
+                    let slot = match family {
+                        IpFamily::V4 => &mut v4,
+                        IpFamily::V6 => &mut v6,
+                    };
+                    if slot.is_some() {
+                        return Err(de::Error::custom(format_args!("duplicate {family} entry")));
+                    }
+                    *slot = Some(map.next_value()?);
+                }
+                Ok(PerFamily::new(
+                    v4.ok_or_else(|| de::Error::missing_field("v4"))?,
+                    v6.ok_or_else(|| de::Error::missing_field("v6"))?,
+                ))
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+/// For fields of type [`FamilySet`](crate::FamilySet), encoded as a list of
+/// families instead of the `v4,v6` string.
+pub mod family_set_list {
+    use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    use crate::{FamilySet, IpFamily};
+
+    pub fn serialize<S: Serializer>(set: &FamilySet, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(set.len()))?;
+        for family in set.iter() {
+            seq.serialize_element(&family)?;
+        }
+        seq.end()
+    }
+
+    /// Repeated families are ignored.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<FamilySet, D::Error> {
+        Ok(Vec::<IpFamily>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+/// `"v4"` or `"v6"` in human-readable formats, the tag byte `4` or `6`
+/// otherwise.
+impl Serialize for IpFamily {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (serializer.is_human_readable(), self) {
+            (true, Self::V4) => serializer.serialize_str("v4"),
+            (true, Self::V6) => serializer.serialize_str("v6"),
+            (false, Self::V4) => serializer.serialize_u8(4),
+            (false, Self::V6) => serializer.serialize_u8(6),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IpFamily {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FamilyVisitor;
+
+        impl Visitor<'_> for FamilyVisitor {
+            type Value = IpFamily;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                f.write_str("\"v4\", \"v6\", 4 or 6")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<IpFamily, E> {
+                match v {
+                    "v4" => Ok(IpFamily::V4),
+                    "v6" => Ok(IpFamily::V6),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+                }
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<IpFamily, E> {
+                match v {
+                    4 => Ok(IpFamily::V4),
+                    6 => Ok(IpFamily::V6),
+                    _ => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+                }
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FamilyVisitor)
+        } else {
+            deserializer.deserialize_u8(FamilyVisitor)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "v4", feature = "v6"))]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+
+    use bincode::{DefaultOptions, Options};
+
+    use super::*;
+    use crate::{
+        fixtures::{V4_ADDR, V4_SOCKET_ADDR, V6_ADDR, V6_SOCKET_ADDR},
+        FamilySet, PerFamily,
+    };
+
+    type JsonSerializer<'a> = serde_json::Serializer<&'a mut Vec<u8>>;
+    type BincodeSerializer<'a> = bincode::Serializer<&'a mut Vec<u8>, DefaultOptions>;
+
+    fn json(serialize: impl FnOnce(&mut JsonSerializer<'_>) -> serde_json::Result<()>) -> String {
+        let mut out = Vec::new();
+        serialize(&mut serde_json::Serializer::new(&mut out)).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn bincode(
+        serialize: impl FnOnce(&mut BincodeSerializer<'_>) -> bincode::Result<()>,
+    ) -> Vec<u8> {
+        let mut out = Vec::new();
+        serialize(&mut bincode::Serializer::new(
+            &mut out,
+            DefaultOptions::new(),
+        ))
+        .unwrap();
+        out
+    }
+
+    macro_rules! from_json {
+        ($input:expr, $deserialize:expr) => {
+            $deserialize(&mut serde_json::Deserializer::from_str($input))
+        };
+    }
+
+    macro_rules! from_bincode {
+        ($input:expr, $deserialize:expr) => {
+            $deserialize(&mut bincode::Deserializer::from_slice(
+                $input,
+                DefaultOptions::new(),
+            ))
+        };
+    }
+
+    #[test]
+    fn family_is_a_string_or_a_tag() {
+        assert_eq!(serde_json::to_string(&IpFamily::V4).unwrap(), r#""v4""#);
+        assert_eq!(serde_json::to_string(&IpFamily::V6).unwrap(), r#""v6""#);
+        let v6: IpFamily = serde_json::from_str(r#""v6""#).unwrap();
+        assert_eq!(v6, IpFamily::V6);
+        assert!(serde_json::from_str::<IpFamily>(r#""ipv6""#).is_err());
+        assert!(serde_json::from_str::<IpFamily>("6").is_err());
+
+        let options = DefaultOptions::new();
+        assert_eq!(options.serialize(&IpFamily::V4).unwrap(), [4]);
+        assert_eq!(options.serialize(&IpFamily::V6).unwrap(), [6]);
+        assert_eq!(options.deserialize::<IpFamily>(&[4]).unwrap(), IpFamily::V4);
+        assert!(options.deserialize::<IpFamily>(&[5]).is_err());
+    }
+
+    #[test]
+    fn addr_round_trips() {
+        let text = json(|s| addr::serialize(&V4_ADDR, s));
+        assert_eq!(text, r#""192.0.2.1""#);
+        assert_eq!(
+            from_json!(&text, addr::deserialize::<Ipv4Addr, _>).unwrap(),
+            V4_ADDR
+        );
+        let text = json(|s| addr::serialize(&V6_ADDR, s));
+        assert_eq!(text, r#""2001:db8::1""#);
+        assert_eq!(
+            from_json!(&text, addr::deserialize::<Ipv6Addr, _>).unwrap(),
+            V6_ADDR
+        );
+
+        let bytes = bincode(|s| addr::serialize(&V4_ADDR, s));
+        assert_eq!(
+            from_bincode!(&bytes, addr::deserialize::<Ipv4Addr, _>).unwrap(),
+            V4_ADDR
+        );
+        let bytes = bincode(|s| addr::serialize(&V6_ADDR, s));
+        assert_eq!(
+            from_bincode!(&bytes, addr::deserialize::<Ipv6Addr, _>).unwrap(),
+            V6_ADDR
+        );
+    }
+
+    #[test]
+    fn socket_addr_round_trips() {
+        let text = json(|s| socket_addr::serialize(&V6_SOCKET_ADDR, s));
+        assert_eq!(
+            from_json!(&text, socket_addr::deserialize::<SocketAddrV6, _>).unwrap(),
+            V6_SOCKET_ADDR
+        );
+        let bytes = bincode(|s| socket_addr::serialize(&V4_SOCKET_ADDR, s));
+        assert_eq!(
+            from_bincode!(&bytes, socket_addr::deserialize::<SocketAddrV4, _>).unwrap(),
+            V4_SOCKET_ADDR
+        );
+    }
+
+    #[test]
+    fn rejects_the_other_family() {
+        let err = from_json!(r#""192.0.2.1""#, addr_str::deserialize::<Ipv6Addr, _>).unwrap_err();
+        assert!(
+            err.to_string().contains("expected an IPv6 address"),
+            "{err}"
+        );
+        let err = from_json!(r#""2001:db8::1""#, addr::deserialize::<Ipv4Addr, _>).unwrap_err();
+        assert!(
+            err.to_string().contains("expected an IPv4 address"),
+            "{err}"
+        );
+        let err = from_json!(r#""192.0.2.1""#, |d| {
+            addr_str::deserialize::<Ipv6Addr, _>(d)
+        })
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("expected an IPv6 address"),
+            "{err}"
+        );
+        let bytes = bincode(|s| socket_addr::serialize(&V4_SOCKET_ADDR, s));
+        assert!(from_bincode!(&bytes, socket_addr::deserialize::<SocketAddrV6, _>).is_err());
+    }
+
+    #[test]
+    fn fixed_encodings() {
+        assert_eq!(json(|s| addr_bytes::serialize(&V4_ADDR, s)), "[192,0,2,1]");
+        assert_eq!(
+            from_json!("[192,0,2,1]", addr_bytes::deserialize::<Ipv4Addr, _>).unwrap(),
+            V4_ADDR
+        );
+        assert!(from_json!("[192,0,2]", addr_bytes::deserialize::<Ipv4Addr, _>).is_err());
+        assert!(from_json!("[192,0,2,1,0]", addr_bytes::deserialize::<Ipv4Addr, _>).is_err());
+        let bytes = bincode(|s| addr_bytes::serialize(&V6_ADDR, s));
+        assert_eq!(
+            from_bincode!(&bytes, addr_bytes::deserialize::<Ipv6Addr, _>).unwrap(),
+            V6_ADDR
+        );
+
+        assert_eq!(json(|s| addr_int::serialize(&V4_ADDR, s)), "3221225985");
+        assert_eq!(
+            from_json!("3221225985", addr_int::deserialize::<Ipv4Addr, _>).unwrap(),
+            V4_ADDR
+        );
+        let bytes = bincode(|s| addr_int::serialize(&V6_ADDR, s));
+        assert_eq!(
+            from_bincode!(&bytes, addr_int::deserialize::<Ipv6Addr, _>).unwrap(),
+            V6_ADDR
+        );
+
+        let bytes = bincode(|s| addr_str::serialize(&V6_ADDR, s));
+        assert_eq!(
+            from_bincode!(&bytes, String::deserialize).unwrap(),
+            "2001:db8::1"
+        );
+        assert_eq!(
+            from_bincode!(&bytes, addr_str::deserialize::<Ipv6Addr, _>).unwrap(),
+            V6_ADDR
+        );
+    }
+
+    #[test]
+    fn per_family_map_round_trips() {
+        let value = PerFamily::new(1u8, 2u8);
+        let text = json(|s| per_family_map::serialize(&value, s));
+        assert_eq!(text, r#"{"v4":1,"v6":2}"#);
+        assert_eq!(
+            from_json!(r#"{"v6":2,"v4":1}"#, per_family_map::deserialize::<u8, _>).unwrap(),
+            value
+        );
+        assert!(from_json!(r#"{"v4":1}"#, per_family_map::deserialize::<u8, _>).is_err());
+        assert!(from_json!(
+            r#"{"v4":1,"v4":1,"v6":2}"#,
+            per_family_map::deserialize::<u8, _>
+        )
+        .is_err());
+        let bytes = bincode(|s| per_family_map::serialize(&value, s));
+        assert_eq!(
+            from_bincode!(&bytes, per_family_map::deserialize::<u8, _>).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn family_set_list_round_trips() {
+        let text = json(|s| family_set_list::serialize(&FamilySet::BOTH, s));
+        assert_eq!(text, r#"["v4","v6"]"#);
+        assert_eq!(
+            from_json!(r#"["v6","v6"]"#, family_set_list::deserialize).unwrap(),
+            FamilySet::V6
+        );
+        let bytes = bincode(|s| family_set_list::serialize(&FamilySet::NONE, s));
+        assert_eq!(
+            from_bincode!(&bytes, family_set_list::deserialize).unwrap(),
+            FamilySet::NONE
+        );
+    }
+}
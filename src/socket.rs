@@ -0,0 +1,125 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, UdpSocket},
+};
+
+use crate::{AnyIpFamily, ConnectError, IpFamily, IpFamilyAddr, IpFamilyExt, IpFamilySocketAddr};
+
+/// Which address of the family an ephemeral socket is bound to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum BindAddr {
+    #[default]
+    Unspecified,
+    Loopback,
+}
+
+impl BindAddr {
+    fn socket_addr<F: AnyIpFamily>(self) -> SocketAddr {
+        let ip = match self {
+            Self::Unspecified => F::Addr::UNSPECIFIED,
+            Self::Loopback => F::Addr::LOCALHOST,
+        };
+        F::SocketAddr::new(ip, 0).into()
+    }
+}
+
+impl IpFamily {
+    /// Binds a TCP listener to `port` on the unspecified address.
+    pub fn bind_tcp(&self, port: u16) -> io::Result<TcpListener> {
+        TcpListener::bind(self.unspecified_socket(port))
+    }
+
+    /// Binds a UDP socket to `port` on the unspecified address.
+    pub fn bind_udp(&self, port: u16) -> io::Result<UdpSocket> {
+        UdpSocket::bind(self.unspecified_socket(port))
+    }
+
+    pub fn bind_tcp_localhost(&self, port: u16) -> io::Result<TcpListener> {
+        TcpListener::bind(self.localhost_socket(port))
+    }
+
+    pub fn bind_udp_localhost(&self, port: u16) -> io::Result<UdpSocket> {
+        UdpSocket::bind(self.localhost_socket(port))
+    }
+}
+
+fn any_port<F: AnyIpFamily>() -> SocketAddr {
+    BindAddr::Unspecified.socket_addr::<F>()
+}
+
+pub(crate) fn typed<F: AnyIpFamily>(addr: SocketAddr) -> io::Result<F::SocketAddr> {
+    F::SocketAddr::try_from_socket_addr(addr).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("socket bound to {addr} of the wrong family"),
+        )
+    })
+}
+
+/// Binds a TCP listener to the unspecified address of `F` on an ephemeral
+/// port and returns it along with the address it was given.
+pub fn listen_any<F: AnyIpFamily>() -> io::Result<(TcpListener, F::SocketAddr)> {
+    let listener = TcpListener::bind(any_port::<F>())?;
+    let addr = typed::<F>(listener.local_addr()?)?;
+    Ok((listener, addr))
+}
+
+/// Like [`listen_any`], binding a `tokio` listener.
+#[cfg(feature = "tokio")]
+pub async fn listen_any_tokio<F: AnyIpFamily>(
+) -> io::Result<(tokio::net::TcpListener, F::SocketAddr)> {
+    let listener = tokio::net::TcpListener::bind(any_port::<F>()).await?;
+    let addr = typed::<F>(listener.local_addr()?)?;
+    Ok((listener, addr))
+}
+
+/// Binds a UDP socket to `bind` of `F` on an ephemeral port and returns it
+/// along with the address it was given.
+pub fn udp_ephemeral<F: AnyIpFamily>(bind: BindAddr) -> io::Result<(UdpSocket, F::SocketAddr)> {
+    let socket = UdpSocket::bind(bind.socket_addr::<F>())?;
+    let addr = typed::<F>(socket.local_addr()?)?;
+    Ok((socket, addr))
+}
+
+fn check_families(source: SocketAddr, destination: SocketAddr) -> Result<(), ConnectError> {
+    if source.family() == destination.family() {
+        Ok(())
+    } else {
+        Err(ConnectError::FamilyMismatch {
+            source,
+            destination,
+        })
+    }
+}
+
+/// Binds a UDP socket to `source` and connects it to `destination`, failing
+/// before any system call if the two are of different families.
+pub fn udp_connect_from(
+    source: SocketAddr,
+    destination: SocketAddr,
+) -> Result<UdpSocket, ConnectError> {
+    check_families(source, destination)?;
+    let socket = UdpSocket::bind(source)?;
+    socket.connect(destination)?;
+    Ok(socket)
+}
+
+/// Binds a TCP socket to `source` and connects it to `destination`, failing
+/// before any system call if the two are of different families.
+#[cfg(feature = "socket2")]
+pub fn tcp_connect_from(
+    source: SocketAddr,
+    destination: SocketAddr,
+) -> Result<std::net::TcpStream, ConnectError> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    check_families(source, destination)?;
+    let socket = Socket::new(
+        Domain::for_address(destination),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+    socket.bind(&source.into())?;
+    socket.connect(&destination.into())?;
+    Ok(socket.into())
+}
@@ -0,0 +1,169 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, UdpSocket},
+};
+
+use socket2::{Domain, Protocol, Socket, Type};
+
+use crate::{AnyIpFamily, FamilyTcpListener, FamilyUdpSocket, FamilyValue, IpFamily, IpFamilyExt};
+
+impl From<IpFamily> for Domain {
+    fn from(value: IpFamily) -> Self {
+        match value {
+            IpFamily::V4 => Domain::IPV4,
+            IpFamily::V6 => Domain::IPV6,
+        }
+    }
+}
+
+/// Fails for domains other than IPv4 and IPv6.
+impl TryFrom<Domain> for IpFamily {
+    type Error = Domain;
+
+    fn try_from(value: Domain) -> Result<Self, Self::Error> {
+        if value == Domain::IPV4 {
+            Ok(IpFamily::V4)
+        } else if value == Domain::IPV6 {
+            Ok(IpFamily::V6)
+        } else {
+            Err(value)
+        }
+    }
+}
+
+/// Creates and configures a `socket2` socket of one family.
+///
+/// A dual-stack listener is an IPv6 socket with `only_v6(false)`.
+#[derive(Clone, Copy, Debug)]
+pub struct SocketBuilder {
+    family: IpFamily,
+    ty: Type,
+    protocol: Option<Protocol>,
+    only_v6: Option<bool>,
+    reuse_address: bool,
+    #[cfg(all(
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+    ))]
+    reuse_port: bool,
+    nonblocking: bool,
+}
+
+impl SocketBuilder {
+    pub fn new(family: IpFamily, ty: Type, protocol: Option<Protocol>) -> Self {
+        Self {
+            family,
+            ty,
+            protocol,
+            only_v6: None,
+            reuse_address: false,
+            #[cfg(all(
+                unix,
+                not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+            ))]
+            reuse_port: false,
+            nonblocking: false,
+        }
+    }
+
+    pub fn tcp(family: IpFamily) -> Self {
+        Self::new(family, Type::STREAM, Some(Protocol::TCP))
+    }
+
+    pub fn udp(family: IpFamily) -> Self {
+        Self::new(family, Type::DGRAM, Some(Protocol::UDP))
+    }
+
+    pub fn family(&self) -> IpFamily {
+        self.family
+    }
+
+    /// Sets `IPV6_V6ONLY`, left at the system default otherwise. Ignored for
+    /// IPv4 sockets.
+    pub fn only_v6(mut self, only_v6: bool) -> Self {
+        self.only_v6 = Some(only_v6);
+        self
+    }
+
+    /// Sets `SO_REUSEADDR`.
+    pub fn reuse_address(mut self, reuse: bool) -> Self {
+        self.reuse_address = reuse;
+        self
+    }
+
+    /// Sets `SO_REUSEPORT`.
+    #[cfg(all(
+        unix,
+        not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+    ))]
+    pub fn reuse_port(mut self, reuse: bool) -> Self {
+        self.reuse_port = reuse;
+        self
+    }
+
+    pub fn nonblocking(mut self, nonblocking: bool) -> Self {
+        self.nonblocking = nonblocking;
+        self
+    }
+
+    pub fn build(&self) -> io::Result<Socket> {
+        let socket = Socket::new(self.family.into(), self.ty, self.protocol)?;
+        if let (IpFamily::V6, Some(only_v6)) = (self.family, self.only_v6) {
+            socket.set_only_v6(only_v6)?;
+        }
+        if self.reuse_address {
+            socket.set_reuse_address(true)?;
+        }
+        #[cfg(all(
+            unix,
+            not(any(target_os = "solaris", target_os = "illumos", target_os = "cygwin"))
+        ))]
+        if self.reuse_port {
+            socket.set_reuse_port(true)?;
+        }
+        if self.nonblocking {
+            socket.set_nonblocking(true)?;
+        }
+        Ok(socket)
+    }
+
+    /// Like [`build`](Self::build), tagged with the family.
+    pub fn build_tagged(&self) -> io::Result<FamilyValue<Socket, Socket>> {
+        let socket = self.build()?;
+        Ok(match self.family {
+            IpFamily::V4 => FamilyValue::V4(socket),
+            IpFamily::V6 => FamilyValue::V6(socket),
+        })
+    }
+
+    fn bind_typed<F: AnyIpFamily>(&self, addr: F::SocketAddr) -> io::Result<Socket> {
+        let addr: SocketAddr = addr.into();
+        if addr.family() != self.family {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cannot bind {addr} on a socket of the other family"),
+            ));
+        }
+        let socket = self.build()?;
+        socket.bind(&addr.into())?;
+        Ok(socket)
+    }
+
+    /// Builds, binds and listens. Fails if the builder is for the other
+    /// family.
+    pub fn listen<F: AnyIpFamily>(
+        &self,
+        addr: F::SocketAddr,
+        backlog: i32,
+    ) -> io::Result<FamilyTcpListener<F>> {
+        let socket = self.bind_typed::<F>(addr)?;
+        socket.listen(backlog)?;
+        FamilyTcpListener::from_std(TcpListener::from(socket))
+    }
+
+    /// Builds and binds. Fails if the builder is for the other family.
+    pub fn bind_udp<F: AnyIpFamily>(&self, addr: F::SocketAddr) -> io::Result<FamilyUdpSocket<F>> {
+        let socket = self.bind_typed::<F>(addr)?;
+        FamilyUdpSocket::from_std(UdpSocket::from(socket))
+    }
+}
@@ -0,0 +1,125 @@
+use std::io;
+
+use socket2::SockRef;
+
+use crate::{AnyIpFamily, IpFamily, ScopedSocketAddr};
+
+fn hop_limit<F: AnyIpFamily>(socket: SockRef<'_>) -> io::Result<u32> {
+    match F::FAMILY {
+        IpFamily::V4 => socket.ttl_v4(),
+        IpFamily::V6 => socket.unicast_hops_v6(),
+    }
+}
+
+fn set_hop_limit<F: AnyIpFamily>(socket: SockRef<'_>, hops: u32) -> io::Result<()> {
+    match F::FAMILY {
+        IpFamily::V4 => socket.set_ttl_v4(hops),
+        IpFamily::V6 => socket.set_unicast_hops_v6(hops),
+    }
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "cygwin",
+    target_os = "illumos",
+))]
+fn traffic_class<F: AnyIpFamily>(socket: SockRef<'_>) -> io::Result<u32> {
+    match F::FAMILY {
+        IpFamily::V4 => socket.tos_v4(),
+        IpFamily::V6 => socket.tclass_v6(),
+    }
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "cygwin",
+    target_os = "illumos",
+))]
+fn set_traffic_class<F: AnyIpFamily>(socket: SockRef<'_>, class: u32) -> io::Result<()> {
+    match F::FAMILY {
+        IpFamily::V4 => socket.set_tos_v4(class),
+        IpFamily::V6 => socket.set_tclass_v6(class),
+    }
+}
+
+macro_rules! socket_options {
+    ($($ty:ty),* $(,)?) => {$(
+        impl<F: AnyIpFamily> $ty {
+            /// `IP_TTL` or `IPV6_UNICAST_HOPS`.
+            pub fn hop_limit(&self) -> io::Result<u32> {
+                hop_limit::<F>(SockRef::from(self.get_ref()))
+            }
+
+            /// Sets `IP_TTL` or `IPV6_UNICAST_HOPS`.
+            pub fn set_hop_limit(&self, hops: u32) -> io::Result<()> {
+                set_hop_limit::<F>(SockRef::from(self.get_ref()), hops)
+            }
+
+            /// `IP_TOS` or `IPV6_TCLASS`.
+            #[cfg(any(
+                target_os = "android",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "cygwin",
+                target_os = "illumos",
+            ))]
+            pub fn traffic_class(&self) -> io::Result<u32> {
+                traffic_class::<F>(SockRef::from(self.get_ref()))
+            }
+
+            /// Sets `IP_TOS` or `IPV6_TCLASS`.
+            #[cfg(any(
+                target_os = "android",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "linux",
+                target_os = "macos",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "cygwin",
+                target_os = "illumos",
+            ))]
+            pub fn set_traffic_class(&self, class: u32) -> io::Result<()> {
+                set_traffic_class::<F>(SockRef::from(self.get_ref()), class)
+            }
+
+            /// `IPV6_V6ONLY`. It can only be changed before binding, see
+            /// [`SocketBuilder::only_v6`](crate::SocketBuilder::only_v6).
+            pub fn only_v6(&self) -> io::Result<bool>
+            where
+                F::SocketAddr: ScopedSocketAddr,
+            {
+                SockRef::from(self.get_ref()).only_v6()
+            }
+        }
+    )*};
+}
+
+socket_options!(
+    crate::FamilyTcpListener<F>,
+    crate::FamilyTcpStream<F>,
+    crate::FamilyUdpSocket<F>,
+);
+
+#[cfg(feature = "tokio")]
+socket_options!(
+    crate::tokio_net::FamilyTcpListener<F>,
+    crate::tokio_net::FamilyTcpStream<F>,
+    crate::tokio_net::FamilyUdpSocket<F>,
+);
@@ -0,0 +1,56 @@
+use std::net::{IpAddr, SocketAddr};
+
+use crate::{IpFamily, IpFamilyExt, IpPrefix, PerFamily};
+
+/// The prefixes replies are expected to come from, such as the ranges of
+/// the configured resolvers.
+///
+/// Peers are canonicalized first, so an IPv4-mapped IPv6 peer such as
+/// `::ffff:192.0.2.1` is only accepted by an IPv4 prefix containing
+/// `192.0.2.1`, never by an IPv6 prefix like `::/0`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ExpectedSources {
+    prefixes: PerFamily<Vec<IpPrefix>>,
+}
+
+impl ExpectedSources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allow(&mut self, prefix: IpPrefix) -> &mut Self {
+        self.prefixes[prefix.family()].push(prefix);
+        self
+    }
+
+    pub fn prefixes(&self, family: IpFamily) -> &[IpPrefix] {
+        &self.prefixes[family]
+    }
+
+    pub fn is_expected(&self, peer: IpAddr) -> bool {
+        let peer = peer.to_canonical();
+        self.prefixes(peer.family())
+            .iter()
+            .any(|prefix| prefix.contains(peer))
+    }
+
+    pub fn is_expected_peer(&self, peer: SocketAddr) -> bool {
+        self.is_expected(peer.ip())
+    }
+}
+
+impl FromIterator<IpPrefix> for ExpectedSources {
+    fn from_iter<I: IntoIterator<Item = IpPrefix>>(iter: I) -> Self {
+        let mut sources = Self::new();
+        sources.extend(iter);
+        sources
+    }
+}
+
+impl Extend<IpPrefix> for ExpectedSources {
+    fn extend<I: IntoIterator<Item = IpPrefix>>(&mut self, iter: I) {
+        for prefix in iter {
+            self.allow(prefix);
+        }
+    }
+}
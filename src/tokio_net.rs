@@ -0,0 +1,197 @@
+//! `tokio` counterparts of [`FamilyTcpListener`](crate::FamilyTcpListener),
+//! [`FamilyTcpStream`](crate::FamilyTcpStream) and
+//! [`FamilyUdpSocket`](crate::FamilyUdpSocket).
+
+use std::{
+    io,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpListener, TcpStream, UdpSocket},
+};
+
+use crate::{socket::typed, AnyIpFamily};
+
+/// A `tokio` [`TcpListener`] that only ever deals in `F::SocketAddr`.
+#[derive(Debug)]
+pub struct FamilyTcpListener<F: AnyIpFamily> {
+    inner: TcpListener,
+    family: PhantomData<fn() -> F>,
+}
+
+impl<F: AnyIpFamily> FamilyTcpListener<F> {
+    pub async fn bind(addr: F::SocketAddr) -> io::Result<Self> {
+        Ok(Self::wrap(TcpListener::bind(addr.into()).await?))
+    }
+
+    /// Fails if `listener` is bound to an address of the other family.
+    pub fn from_tokio(listener: TcpListener) -> io::Result<Self> {
+        typed::<F>(listener.local_addr()?)?;
+        Ok(Self::wrap(listener))
+    }
+
+    fn wrap(inner: TcpListener) -> Self {
+        Self {
+            inner,
+            family: PhantomData,
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.local_addr()?)
+    }
+
+    pub async fn accept(&self) -> io::Result<(FamilyTcpStream<F>, F::SocketAddr)> {
+        let (stream, addr) = self.inner.accept().await?;
+        Ok((FamilyTcpStream::wrap(stream), typed::<F>(addr)?))
+    }
+
+    pub fn get_ref(&self) -> &TcpListener {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> TcpListener {
+        self.inner
+    }
+}
+
+/// A `tokio` [`TcpStream`] that only ever deals in `F::SocketAddr`.
+#[derive(Debug)]
+pub struct FamilyTcpStream<F: AnyIpFamily> {
+    inner: TcpStream,
+    family: PhantomData<fn() -> F>,
+}
+
+impl<F: AnyIpFamily> FamilyTcpStream<F> {
+    pub async fn connect(addr: F::SocketAddr) -> io::Result<Self> {
+        Ok(Self::wrap(TcpStream::connect(addr.into()).await?))
+    }
+
+    /// Fails if `stream` is connected to an address of the other family.
+    pub fn from_tokio(stream: TcpStream) -> io::Result<Self> {
+        typed::<F>(stream.local_addr()?)?;
+        Ok(Self::wrap(stream))
+    }
+
+    fn wrap(inner: TcpStream) -> Self {
+        Self {
+            inner,
+            family: PhantomData,
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.local_addr()?)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.peer_addr()?)
+    }
+
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut TcpStream {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> TcpStream {
+        self.inner
+    }
+}
+
+impl<F: AnyIpFamily> AsyncRead for FamilyTcpStream<F> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+    }
+}
+
+impl<F: AnyIpFamily> AsyncWrite for FamilyTcpStream<F> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+/// A `tokio` [`UdpSocket`] that only ever deals in `F::SocketAddr`.
+#[derive(Debug)]
+pub struct FamilyUdpSocket<F: AnyIpFamily> {
+    inner: UdpSocket,
+    family: PhantomData<fn() -> F>,
+}
+
+impl<F: AnyIpFamily> FamilyUdpSocket<F> {
+    pub async fn bind(addr: F::SocketAddr) -> io::Result<Self> {
+        Ok(Self::wrap(UdpSocket::bind(addr.into()).await?))
+    }
+
+    /// Fails if `socket` is bound to an address of the other family.
+    pub fn from_tokio(socket: UdpSocket) -> io::Result<Self> {
+        typed::<F>(socket.local_addr()?)?;
+        Ok(Self::wrap(socket))
+    }
+
+    fn wrap(inner: UdpSocket) -> Self {
+        Self {
+            inner,
+            family: PhantomData,
+        }
+    }
+
+    pub async fn connect(&self, addr: F::SocketAddr) -> io::Result<()> {
+        self.inner.connect(addr.into()).await
+    }
+
+    pub fn local_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.local_addr()?)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.peer_addr()?)
+    }
+
+    pub async fn send_to(&self, buf: &[u8], target: F::SocketAddr) -> io::Result<usize> {
+        self.inner.send_to(buf, target.into()).await
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, F::SocketAddr)> {
+        let (len, addr) = self.inner.recv_from(buf).await?;
+        Ok((len, typed::<F>(addr)?))
+    }
+
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.send(buf).await
+    }
+
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.recv(buf).await
+    }
+
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> UdpSocket {
+        self.inner
+    }
+}
@@ -0,0 +1,115 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4};
+
+/// Whether `addr` is in `2002::/16`.
+pub const fn is_6to4(addr: Ipv6Addr) -> bool {
+    addr.segments()[0] == 0x2002
+}
+
+/// The IPv4 address of the 6to4 router, bits 16 to 47.
+pub const fn sixto4_v4(addr: Ipv6Addr) -> Option<Ipv4Addr> {
+    if !is_6to4(addr) {
+        return None;
+    }
+    Some(Ipv4Addr::from_bits((addr.to_bits() >> 80) as u32))
+}
+
+/// Whether `addr` is in `2001::/32`.
+pub const fn is_teredo(addr: Ipv6Addr) -> bool {
+    let [a, b, ..] = addr.segments();
+    a == 0x2001 && b == 0
+}
+
+/// The fields of a Teredo address (RFC 4380), with the client port and
+/// address de-obfuscated.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Teredo {
+    pub server: Ipv4Addr,
+    pub flags: u16,
+    /// The client's public NAT mapping.
+    pub client: SocketAddrV4,
+}
+
+impl Teredo {
+    /// Returns `None` outside of `2001::/32`.
+    pub const fn from_addr(addr: Ipv6Addr) -> Option<Self> {
+        if !is_teredo(addr) {
+            return None;
+        }
+        let bits = addr.to_bits();
+        Some(Self {
+            server: Ipv4Addr::from_bits((bits >> 64) as u32),
+            flags: (bits >> 48) as u16,
+            client: SocketAddrV4::new(Ipv4Addr::from_bits(!(bits as u32)), !((bits >> 32) as u16)),
+        })
+    }
+
+    pub const fn to_addr(&self) -> Ipv6Addr {
+        let bits = 0x2001_u128 << 112
+            | (self.server.to_bits() as u128) << 64
+            | (self.flags as u128) << 48
+            | (!self.client.port() as u128) << 32
+            | !self.client.ip().to_bits() as u128;
+        Ipv6Addr::from_bits(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 4380, section 4: a cone NAT client of the server 65.54.227.120.
+    const TEREDO: Ipv6Addr =
+        Ipv6Addr::new(0x2001, 0, 0x4136, 0xe378, 0x8000, 0x63bf, 0x3fff, 0xfdd2);
+
+    #[test]
+    fn teredo_rfc4380_example() {
+        let teredo = Teredo::from_addr(TEREDO).unwrap();
+        assert_eq!(
+            teredo,
+            Teredo {
+                server: Ipv4Addr::new(65, 54, 227, 120),
+                flags: 0x8000,
+                client: SocketAddrV4::new(Ipv4Addr::new(192, 0, 2, 45), 40000),
+            }
+        );
+        assert_eq!(teredo.to_addr(), TEREDO);
+    }
+
+    #[test]
+    fn teredo_obfuscation_of_extreme_values() {
+        let teredo = Teredo {
+            server: Ipv4Addr::UNSPECIFIED,
+            flags: 0,
+            client: SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0),
+        };
+        assert_eq!(
+            teredo.to_addr(),
+            "2001::ffff:ffff:ffff".parse::<Ipv6Addr>().unwrap()
+        );
+        let teredo = Teredo {
+            client: SocketAddrV4::new(Ipv4Addr::BROADCAST, u16::MAX),
+            ..teredo
+        };
+        assert_eq!(teredo.to_addr(), "2001::".parse::<Ipv6Addr>().unwrap());
+        assert_eq!(Teredo::from_addr(teredo.to_addr()), Some(teredo));
+    }
+
+    #[test]
+    fn teredo_prefix_only() {
+        assert!(is_teredo(TEREDO));
+        assert!(!is_teredo("2001:db8::1".parse().unwrap()));
+        assert_eq!(Teredo::from_addr("2001:1::1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn sixto4_router_address() {
+        let addr: Ipv6Addr = "2002:c000:221::1".parse().unwrap();
+        assert!(is_6to4(addr));
+        assert_eq!(sixto4_v4(addr), Some(Ipv4Addr::new(192, 0, 2, 33)));
+        assert_eq!(sixto4_v4("2001:db8::1".parse().unwrap()), None);
+        assert_eq!(
+            sixto4_v4("2002:ffff:ffff::".parse().unwrap()),
+            Some(Ipv4Addr::BROADCAST)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    io::{self, Read, Write},
+    marker::PhantomData,
+    net::{TcpListener, TcpStream, UdpSocket},
+};
+
+use crate::{socket::typed, AnyIpFamily, MulticastGroup};
+
+/// A [`TcpListener`] that only ever deals in `F::SocketAddr`.
+#[derive(Debug)]
+pub struct FamilyTcpListener<F: AnyIpFamily> {
+    inner: TcpListener,
+    family: PhantomData<F>,
+}
+
+impl<F: AnyIpFamily> FamilyTcpListener<F> {
+    pub fn bind(addr: F::SocketAddr) -> io::Result<Self> {
+        Ok(Self::wrap(TcpListener::bind(addr.into())?))
+    }
+
+    /// Fails if `listener` is bound to an address of the other family.
+    pub fn from_std(listener: TcpListener) -> io::Result<Self> {
+        typed::<F>(listener.local_addr()?)?;
+        Ok(Self::wrap(listener))
+    }
+
+    fn wrap(inner: TcpListener) -> Self {
+        Self {
+            inner,
+            family: PhantomData,
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.local_addr()?)
+    }
+
+    pub fn accept(&self) -> io::Result<(FamilyTcpStream<F>, F::SocketAddr)> {
+        let (stream, addr) = self.inner.accept()?;
+        Ok((FamilyTcpStream::wrap(stream), typed::<F>(addr)?))
+    }
+
+    pub fn get_ref(&self) -> &TcpListener {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> TcpListener {
+        self.inner
+    }
+}
+
+/// A [`TcpStream`] that only ever deals in `F::SocketAddr`.
+#[derive(Debug)]
+pub struct FamilyTcpStream<F: AnyIpFamily> {
+    inner: TcpStream,
+    family: PhantomData<F>,
+}
+
+impl<F: AnyIpFamily> FamilyTcpStream<F> {
+    pub fn connect(addr: F::SocketAddr) -> io::Result<Self> {
+        Ok(Self::wrap(TcpStream::connect(addr.into())?))
+    }
+
+    /// Fails if `stream` is connected to an address of the other family.
+    pub fn from_std(stream: TcpStream) -> io::Result<Self> {
+        typed::<F>(stream.local_addr()?)?;
+        Ok(Self::wrap(stream))
+    }
+
+    fn wrap(inner: TcpStream) -> Self {
+        Self {
+            inner,
+            family: PhantomData,
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.local_addr()?)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.peer_addr()?)
+    }
+
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut TcpStream {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> TcpStream {
+        self.inner
+    }
+}
+
+impl<F: AnyIpFamily> Read for FamilyTcpStream<F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<F: AnyIpFamily> Write for FamilyTcpStream<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A [`UdpSocket`] that only ever deals in `F::SocketAddr`.
+#[derive(Debug)]
+pub struct FamilyUdpSocket<F: AnyIpFamily> {
+    inner: UdpSocket,
+    family: PhantomData<F>,
+}
+
+impl<F: AnyIpFamily> FamilyUdpSocket<F> {
+    pub fn bind(addr: F::SocketAddr) -> io::Result<Self> {
+        Ok(Self::wrap(UdpSocket::bind(addr.into())?))
+    }
+
+    /// Fails if `socket` is bound to an address of the other family.
+    pub fn from_std(socket: UdpSocket) -> io::Result<Self> {
+        typed::<F>(socket.local_addr()?)?;
+        Ok(Self::wrap(socket))
+    }
+
+    fn wrap(inner: UdpSocket) -> Self {
+        Self {
+            inner,
+            family: PhantomData,
+        }
+    }
+
+    pub fn connect(&self, addr: F::SocketAddr) -> io::Result<()> {
+        self.inner.connect(addr.into())
+    }
+
+    pub fn local_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.local_addr()?)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<F::SocketAddr> {
+        typed::<F>(self.inner.peer_addr()?)
+    }
+
+    pub fn send_to(&self, buf: &[u8], target: F::SocketAddr) -> io::Result<usize> {
+        self.inner.send_to(buf, target.into())
+    }
+
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, F::SocketAddr)> {
+        let (len, addr) = self.inner.recv_from(buf)?;
+        Ok((len, typed::<F>(addr)?))
+    }
+
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.send(buf)
+    }
+
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.recv(buf)
+    }
+
+    /// `interface` is an address for IPv4 and an index for IPv6, see
+    /// [`MulticastGroup::Interface`].
+    pub fn join_multicast(
+        &self,
+        group: F::Addr,
+        interface: <F::Addr as MulticastGroup>::Interface,
+    ) -> io::Result<()>
+    where
+        F::Addr: MulticastGroup,
+    {
+        group.join(&self.inner, interface)
+    }
+
+    pub fn leave_multicast(
+        &self,
+        group: F::Addr,
+        interface: <F::Addr as MulticastGroup>::Interface,
+    ) -> io::Result<()>
+    where
+        F::Addr: MulticastGroup,
+    {
+        group.leave(&self.inner, interface)
+    }
+
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> UdpSocket {
+        self.inner
+    }
+}
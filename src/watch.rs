@@ -0,0 +1,208 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Debug, Formatter},
+    sync::mpsc,
+};
+
+use crate::{AllowedIps, IpPrefix};
+
+/// The entries added and removed by one revision of a [`WatchedSet`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SetChange {
+    pub revision: u64,
+    pub added: Vec<IpPrefix>,
+    pub removed: Vec<IpPrefix>,
+}
+
+type Subscriber = Box<dyn FnMut(&SetChange) -> bool + Send>;
+
+/// An [`AllowedIps`] that counts revisions and notifies subscribers of
+/// every change, for hot-reloading ACL or firewall state.
+///
+/// Changes that leave the set equal create no revision.
+#[derive(Default)]
+pub struct WatchedSet {
+    set: AllowedIps,
+    revision: u64,
+    subscribers: Vec<Subscriber>,
+}
+
+impl WatchedSet {
+    pub fn new(set: AllowedIps) -> Self {
+        Self {
+            set,
+            ..Self::default()
+        }
+    }
+
+    pub fn get(&self) -> &AllowedIps {
+        &self.set
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn subscribe(&mut self, mut callback: impl FnMut(&SetChange) + Send + 'static) {
+        self.subscribers.push(Box::new(move |change| {
+            callback(change);
+            true
+        }));
+    }
+
+    /// The subscription ends when the receiver is dropped.
+    pub fn subscribe_channel(&mut self) -> mpsc::Receiver<SetChange> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers
+            .push(Box::new(move |change| sender.send(change.clone()).is_ok()));
+        receiver
+    }
+
+    pub fn insert(&mut self, prefix: IpPrefix) -> Option<u64> {
+        self.update(|set| set.insert(prefix))
+    }
+
+    pub fn remove(&mut self, prefix: &IpPrefix) -> Option<u64> {
+        self.update(|set| {
+            set.remove(prefix);
+        })
+    }
+
+    pub fn replace(&mut self, set: AllowedIps) -> Option<u64> {
+        self.update(|current| *current = set)
+    }
+
+    /// Applies `f` to a copy of the set and publishes the difference.
+    /// Returns the new revision, or `None` if nothing changed.
+    pub fn update(&mut self, f: impl FnOnce(&mut AllowedIps)) -> Option<u64> {
+        let mut next = self.set.clone();
+        f(&mut next);
+        let (added, removed) = diff(self.set.prefixes(), next.prefixes());
+        if added.is_empty() && removed.is_empty() {
+            return None;
+        }
+        self.set = next;
+        self.revision += 1;
+        let change = SetChange {
+            revision: self.revision,
+            added,
+            removed,
+        };
+        self.subscribers
+            .retain_mut(|subscriber| subscriber(&change));
+        Some(self.revision)
+    }
+}
+
+impl Debug for WatchedSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WatchedSet")
+            .field("set", &self.set)
+            .field("revision", &self.revision)
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+// Both inputs are sorted.
+fn diff(old: &[IpPrefix], new: &[IpPrefix]) -> (Vec<IpPrefix>, Vec<IpPrefix>) {
+    let (mut added, mut removed) = (Vec::new(), Vec::new());
+    let (mut old, mut new) = (old.iter().peekable(), new.iter().peekable());
+    loop {
+        match (old.peek(), new.peek()) {
+            (Some(a), Some(b)) => match a.cmp(b) {
+                Ordering::Less => removed.extend(old.next()),
+                Ordering::Greater => added.extend(new.next()),
+                Ordering::Equal => {
+                    old.next();
+                    new.next();
+                }
+            },
+            (Some(_), None) => removed.extend(old.next()),
+            (None, Some(_)) => added.extend(new.next()),
+            (None, None) => break,
+        }
+    }
+    (added, removed)
+}
+
+#[cfg(all(test, feature = "v4", feature = "v6"))]
+mod tests {
+    use super::*;
+
+    fn prefixes(list: &[&str]) -> Vec<IpPrefix> {
+        let mut prefixes: Vec<IpPrefix> = list.iter().map(|s| s.parse().unwrap()).collect();
+        prefixes.sort();
+        prefixes
+    }
+
+    #[test]
+    fn diff_reports_added_entries() {
+        let old = prefixes(&["10.0.0.0/8", "2001:db8::/32"]);
+        let new = prefixes(&["10.0.0.0/8", "192.0.2.0/24", "2001:db8::/32", "fd00::/8"]);
+        assert_eq!(
+            diff(&old, &new),
+            (prefixes(&["192.0.2.0/24", "fd00::/8"]), vec![])
+        );
+    }
+
+    #[test]
+    fn diff_reports_removed_entries() {
+        let old = prefixes(&["10.0.0.0/8", "192.0.2.0/24", "2001:db8::/32"]);
+        let new = prefixes(&["192.0.2.0/24"]);
+        assert_eq!(
+            diff(&old, &new),
+            (vec![], prefixes(&["10.0.0.0/8", "2001:db8::/32"]))
+        );
+    }
+
+    #[test]
+    fn diff_of_one_family_leaves_the_other_alone() {
+        let old = prefixes(&["10.0.0.0/8", "192.0.2.0/24", "2001:db8::/32"]);
+        let new = prefixes(&["10.0.0.0/8", "192.0.2.0/24", "2001:db8::/48", "fd00::/8"]);
+        assert_eq!(
+            diff(&old, &new),
+            (
+                prefixes(&["2001:db8::/48", "fd00::/8"]),
+                prefixes(&["2001:db8::/32"])
+            )
+        );
+    }
+
+    #[test]
+    fn diff_of_equal_sets_is_empty() {
+        let set = prefixes(&["10.0.0.0/8", "2001:db8::/32"]);
+        assert_eq!(diff(&set, &set), (vec![], vec![]));
+        assert_eq!(diff(&[], &[]), (vec![], vec![]));
+    }
+
+    #[test]
+    fn publishes_each_revision() {
+        let mut watched = WatchedSet::default();
+        let changes = watched.subscribe_channel();
+        assert_eq!(watched.insert("192.0.2.0/24".parse().unwrap()), Some(1));
+        assert_eq!(watched.insert("192.0.2.0/24".parse().unwrap()), None);
+        assert_eq!(
+            watched.replace(prefixes(&["2001:db8::/32"]).into_iter().collect()),
+            Some(2)
+        );
+        assert_eq!(watched.revision(), 2);
+
+        let first = changes.recv().unwrap();
+        assert_eq!(first.revision, 1);
+        assert_eq!(first.added, prefixes(&["192.0.2.0/24"]));
+        let second = changes.recv().unwrap();
+        assert_eq!(second.revision, 2);
+        assert_eq!(second.added, prefixes(&["2001:db8::/32"]));
+        assert_eq!(second.removed, prefixes(&["192.0.2.0/24"]));
+        assert!(changes.try_recv().is_err());
+    }
+
+    #[test]
+    fn drops_closed_channels() {
+        let mut watched = WatchedSet::default();
+        drop(watched.subscribe_channel());
+        watched.insert("192.0.2.0/24".parse().unwrap());
+        assert_eq!(watched.subscribers.len(), 0);
+    }
+}
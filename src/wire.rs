@@ -0,0 +1,467 @@
+//! A compact, versioned binary encoding for addresses.
+//!
+//! Every value is one frame:
+//!
+//! | bytes | field                                                    |
+//! |-------|----------------------------------------------------------|
+//! | 1     | version, currently [`WIRE_VERSION`]                      |
+//! | 1     | tag: bits 0-1 family (`1` IPv4, `2` IPv6), flags above   |
+//! | 4/16  | address octets, absent with [`FLAG_FAMILY_ONLY`]         |
+//! | 2     | port, big endian, with [`FLAG_PORT`]                     |
+//! | 8     | flowinfo and scope id, big endian, with [`FLAG_V6_META`] |
+//! | 1     | prefix length, with [`FLAG_PREFIX`]                      |
+//!
+//! Bits 6 and 7 of the tag are reserved and must be zero. Readers reject
+//! frames whose flags do not match the requested type.
+//!
+//! [`compact`] has the smaller, unversioned layout of SOCKS5.
+
+use std::{
+    io::{self, Read, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+};
+
+use crate::{IpFamily, IpPrefix, WireError};
+
+pub mod compact;
+
+pub const WIRE_VERSION: u8 = 1;
+
+pub const FLAG_PORT: u8 = 0x04;
+/// Only on IPv6 socket addresses with a non-zero flowinfo or scope id.
+pub const FLAG_V6_META: u8 = 0x08;
+pub const FLAG_PREFIX: u8 = 0x10;
+pub const FLAG_FAMILY_ONLY: u8 = 0x20;
+const FAMILY_MASK: u8 = 0x03;
+const RESERVED_MASK: u8 = 0xc0;
+
+pub trait WireWrite {
+    fn wire_len(&self) -> usize;
+
+    fn write_wire<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    fn to_wire(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.wire_len());
+        self.write_wire(&mut buf)
+            .expect("writing to a Vec does not fail");
+        buf
+    }
+
+    /// Encodes into the front of `buf` and returns the bytes written.
+    fn write_wire_slice(&self, buf: &mut [u8]) -> Result<usize, WireError> {
+        let len = self.wire_len();
+        let mut dst = buf.get_mut(..len).ok_or(WireError::Truncated)?;
+        self.write_wire(&mut dst)?;
+        Ok(len)
+    }
+}
+
+pub trait WireRead: Sized {
+    fn read_wire<R: Read>(reader: &mut R) -> Result<Self, WireError>;
+
+    /// Decodes from the front of `buf` and returns the value and the bytes
+    /// consumed.
+    fn read_wire_slice(buf: &[u8]) -> Result<(Self, usize), WireError> {
+        let mut src = buf;
+        let value = Self::read_wire(&mut src)?;
+        Ok((value, buf.len() - src.len()))
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Frame {
+    family: u8,
+    flags: u8,
+    octets: [u8; 16],
+    port: u16,
+    flowinfo: u32,
+    scope_id: u32,
+    prefix_len: u8,
+}
+
+impl Frame {
+    fn of_ip(addr: IpAddr) -> Self {
+        let mut frame = Self::default();
+        match addr {
+            IpAddr::V4(addr) => {
+                frame.family = 1;
+                frame.octets[..4].copy_from_slice(&addr.octets());
+            }
+            IpAddr::V6(addr) => {
+                frame.family = 2;
+                frame.octets = addr.octets();
+            }
+        }
+        frame
+    }
+
+    fn of_socket(addr: SocketAddr) -> Self {
+        let mut frame = Self::of_ip(addr.ip());
+        frame.flags |= FLAG_PORT;
+        frame.port = addr.port();
+        if let SocketAddr::V6(addr) = addr {
+            if addr.flowinfo() != 0 || addr.scope_id() != 0 {
+                frame.flags |= FLAG_V6_META;
+                frame.flowinfo = addr.flowinfo();
+                frame.scope_id = addr.scope_id();
+            }
+        }
+        frame
+    }
+
+    fn addr_len(&self) -> usize {
+        match (self.flags & FLAG_FAMILY_ONLY != 0, self.family) {
+            (true, _) => 0,
+            (false, 1) => 4,
+            (false, _) => 16,
+        }
+    }
+
+    fn len(&self) -> usize {
+        let mut len = 2 + self.addr_len();
+        if self.flags & FLAG_PORT != 0 {
+            len += 2;
+        }
+        if self.flags & FLAG_V6_META != 0 {
+            len += 8;
+        }
+        if self.flags & FLAG_PREFIX != 0 {
+            len += 1;
+        }
+        len
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[WIRE_VERSION, self.family | self.flags])?;
+        writer.write_all(&self.octets[..self.addr_len()])?;
+        if self.flags & FLAG_PORT != 0 {
+            writer.write_all(&self.port.to_be_bytes())?;
+        }
+        if self.flags & FLAG_V6_META != 0 {
+            writer.write_all(&self.flowinfo.to_be_bytes())?;
+            writer.write_all(&self.scope_id.to_be_bytes())?;
+        }
+        if self.flags & FLAG_PREFIX != 0 {
+            writer.write_all(&[self.prefix_len])?;
+        }
+        Ok(())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self, WireError> {
+        let mut head = [0; 2];
+        reader.read_exact(&mut head)?;
+        let [version, tag] = head;
+        if version != WIRE_VERSION {
+            return Err(WireError::UnsupportedVersion(version));
+        }
+        if tag & RESERVED_MASK != 0 {
+            return Err(WireError::ReservedBits(tag));
+        }
+        let mut frame = Self {
+            family: tag & FAMILY_MASK,
+            flags: tag & !FAMILY_MASK,
+            ..Self::default()
+        };
+        let v6_meta = frame.flags & FLAG_V6_META != 0;
+        let valid = match frame.family {
+            1 => !v6_meta,
+            2 => !v6_meta || frame.flags & FLAG_PORT != 0,
+            _ => false,
+        };
+        if !valid {
+            return Err(WireError::InvalidTag(tag));
+        }
+        let addr_len = frame.addr_len();
+        reader.read_exact(&mut frame.octets[..addr_len])?;
+        if frame.flags & FLAG_PORT != 0 {
+            let mut port = [0; 2];
+            reader.read_exact(&mut port)?;
+            frame.port = u16::from_be_bytes(port);
+        }
+        if v6_meta {
+            let mut meta = [0; 8];
+            reader.read_exact(&mut meta)?;
+            let (flowinfo, scope_id) = meta.split_at(4);
+            frame.flowinfo = u32::from_be_bytes(flowinfo.try_into().expect("4 bytes"));
+            frame.scope_id = u32::from_be_bytes(scope_id.try_into().expect("4 bytes"));
+        }
+        if frame.flags & FLAG_PREFIX != 0 {
+            let mut len = [0; 1];
+            reader.read_exact(&mut len)?;
+            frame.prefix_len = len[0];
+        }
+        Ok(frame)
+    }
+
+    /// Fails unless the frame carries exactly `flags`, ignoring
+    /// [`FLAG_V6_META`] if `meta` is allowed.
+    fn expect(self, flags: u8, meta: bool) -> Result<Self, WireError> {
+        let found = if meta {
+            self.flags & !FLAG_V6_META
+        } else {
+            self.flags
+        };
+        if found == flags {
+            Ok(self)
+        } else {
+            Err(WireError::UnexpectedFrame)
+        }
+    }
+
+    fn ip(&self) -> IpAddr {
+        if self.family == 1 {
+            let [a, b, c, d, ..] = self.octets;
+            IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+        } else {
+            IpAddr::V6(Ipv6Addr::from(self.octets))
+        }
+    }
+
+    fn socket_addr(&self) -> SocketAddr {
+        match self.ip() {
+            IpAddr::V4(ip) => SocketAddr::V4(SocketAddrV4::new(ip, self.port)),
+            IpAddr::V6(ip) => SocketAddr::V6(SocketAddrV6::new(
+                ip,
+                self.port,
+                self.flowinfo,
+                self.scope_id,
+            )),
+        }
+    }
+}
+
+fn expect_family<T>(value: Result<T, ()>) -> Result<T, WireError> {
+    value.map_err(|()| WireError::UnexpectedFrame)
+}
+
+macro_rules! impl_wire {
+    ($ty:ty, |$value:ident| $to_frame:expr, |$frame:ident| $from_frame:expr) => {
+        impl WireWrite for $ty {
+            fn wire_len(&self) -> usize {
+                let $value = *self;
+                Frame::len(&$to_frame)
+            }
+
+            fn write_wire<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                let $value = *self;
+                $to_frame.write(writer)
+            }
+        }
+
+        impl WireRead for $ty {
+            fn read_wire<R: Read>(reader: &mut R) -> Result<Self, WireError> {
+                let $frame = Frame::read(reader)?;
+                $from_frame
+            }
+        }
+    };
+}
+
+impl_wire!(IpAddr, |value| Frame::of_ip(value), |frame| Ok(frame
+    .expect(0, false)?
+    .ip()));
+
+impl_wire!(Ipv4Addr, |value| Frame::of_ip(IpAddr::V4(value)), |frame| {
+    expect_family(match frame.expect(0, false)?.ip() {
+        IpAddr::V4(addr) => Ok(addr),
+        IpAddr::V6(_) => Err(()),
+    })
+});
+
+impl_wire!(Ipv6Addr, |value| Frame::of_ip(IpAddr::V6(value)), |frame| {
+    expect_family(match frame.expect(0, false)?.ip() {
+        IpAddr::V6(addr) => Ok(addr),
+        IpAddr::V4(_) => Err(()),
+    })
+});
+
+impl_wire!(SocketAddr, |value| Frame::of_socket(value), |frame| Ok(
+    frame.expect(FLAG_PORT, true)?.socket_addr()
+));
+
+impl_wire!(
+    SocketAddrV4,
+    |value| Frame::of_socket(SocketAddr::V4(value)),
+    |frame| expect_family(match frame.expect(FLAG_PORT, false)?.socket_addr() {
+        SocketAddr::V4(addr) => Ok(addr),
+        SocketAddr::V6(_) => Err(()),
+    })
+);
+
+impl_wire!(
+    SocketAddrV6,
+    |value| Frame::of_socket(SocketAddr::V6(value)),
+    |frame| expect_family(match frame.expect(FLAG_PORT, true)?.socket_addr() {
+        SocketAddr::V6(addr) => Ok(addr),
+        SocketAddr::V4(_) => Err(()),
+    })
+);
+
+impl_wire!(
+    IpFamily,
+    |value| Frame {
+        family: match value {
+            IpFamily::V4 => 1,
+            IpFamily::V6 => 2,
+        },
+        flags: FLAG_FAMILY_ONLY,
+        ..Frame::default()
+    },
+    |frame| Ok(match frame.expect(FLAG_FAMILY_ONLY, false)?.family {
+        1 => IpFamily::V4,
+        _ => IpFamily::V6,
+    })
+);
+
+impl_wire!(
+    IpPrefix,
+    |value| Frame {
+        flags: FLAG_PREFIX,
+        prefix_len: value.len(),
+        ..Frame::of_ip(value.addr())
+    },
+    |frame| {
+        let frame = frame.expect(FLAG_PREFIX, false)?;
+        let prefix = IpPrefix::new(frame.ip(), frame.prefix_len);
+        if prefix.len() != frame.prefix_len || prefix.addr() != frame.ip() {
+            return Err(WireError::InvalidPrefix);
+        }
+        Ok(prefix)
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: WireWrite + WireRead + PartialEq + std::fmt::Debug>(value: T) {
+        let wire = value.to_wire();
+        assert_eq!(wire.len(), value.wire_len());
+        assert_eq!(wire[0], WIRE_VERSION);
+        let (decoded, len) = T::read_wire_slice(&wire).unwrap();
+        assert_eq!((decoded, len), (value, wire.len()));
+    }
+
+    #[test]
+    fn frame_layout() {
+        let addr: SocketAddr = "192.0.2.1:443".parse().unwrap();
+        assert_eq!(
+            addr.to_wire(),
+            [WIRE_VERSION, 1 | FLAG_PORT, 192, 0, 2, 1, 0x01, 0xbb]
+        );
+        assert_eq!(IpFamily::V6.to_wire(), [WIRE_VERSION, 2 | FLAG_FAMILY_ONLY]);
+        let prefix = IpPrefix::new("10.0.0.0".parse().unwrap(), 8);
+        assert_eq!(
+            prefix.to_wire(),
+            [WIRE_VERSION, 1 | FLAG_PREFIX, 10, 0, 0, 0, 8]
+        );
+    }
+
+    #[test]
+    fn round_trips_every_type() {
+        round_trip(IpAddr::from(Ipv4Addr::new(192, 0, 2, 1)));
+        round_trip("2001:db8::1".parse::<Ipv6Addr>().unwrap());
+        round_trip("::ffff:192.0.2.1".parse::<Ipv6Addr>().unwrap());
+        round_trip(Ipv4Addr::BROADCAST);
+        round_trip("192.0.2.1:80".parse::<SocketAddrV4>().unwrap());
+        round_trip("[2001:db8::1]:443".parse::<SocketAddr>().unwrap());
+        round_trip(IpFamily::V4);
+        round_trip(IpFamily::V6);
+        round_trip(IpPrefix::new(Ipv6Addr::UNSPECIFIED.into(), 0));
+        round_trip(IpPrefix::new("2001:db8::1".parse().unwrap(), 128));
+    }
+
+    #[test]
+    fn v6_meta_only_when_needed() {
+        let plain = SocketAddrV6::new("2001:db8::1".parse().unwrap(), 443, 0, 0);
+        assert_eq!(plain.wire_len(), 2 + 16 + 2);
+        let scoped = SocketAddrV6::new("fe80::1".parse().unwrap(), 443, 7, 3);
+        assert_eq!(scoped.wire_len(), 2 + 16 + 2 + 8);
+        assert_eq!(scoped.to_wire()[1], 2 | FLAG_PORT | FLAG_V6_META);
+        round_trip(plain);
+        round_trip(scoped);
+        round_trip(SocketAddr::V6(scoped));
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        assert!(matches!(
+            IpAddr::read_wire_slice(&[2, 1, 192, 0, 2, 1]),
+            Err(WireError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            IpAddr::read_wire_slice(&[WIRE_VERSION, 0x41, 192, 0, 2, 1]),
+            Err(WireError::ReservedBits(0x41))
+        ));
+        assert!(matches!(
+            IpAddr::read_wire_slice(&[WIRE_VERSION, 3]),
+            Err(WireError::InvalidTag(3))
+        ));
+        // Flowinfo and scope ids need a port and IPv6.
+        assert!(matches!(
+            IpAddr::read_wire_slice(&[WIRE_VERSION, 2 | FLAG_V6_META]),
+            Err(WireError::InvalidTag(_))
+        ));
+        assert!(matches!(
+            SocketAddr::read_wire_slice(&[WIRE_VERSION, 1 | FLAG_PORT | FLAG_V6_META]),
+            Err(WireError::InvalidTag(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_frames_of_other_types() {
+        let socket = "192.0.2.1:80".parse::<SocketAddr>().unwrap().to_wire();
+        assert!(matches!(
+            IpAddr::read_wire_slice(&socket),
+            Err(WireError::UnexpectedFrame)
+        ));
+        assert!(matches!(
+            SocketAddrV6::read_wire_slice(&socket),
+            Err(WireError::UnexpectedFrame)
+        ));
+        let v6 = Ipv6Addr::LOCALHOST.to_wire();
+        assert!(matches!(
+            Ipv4Addr::read_wire_slice(&v6),
+            Err(WireError::UnexpectedFrame)
+        ));
+        assert!(matches!(
+            IpFamily::read_wire_slice(&v6),
+            Err(WireError::UnexpectedFrame)
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_prefixes() {
+        let too_long = [WIRE_VERSION, 1 | FLAG_PREFIX, 10, 0, 0, 0, 33];
+        assert!(matches!(
+            IpPrefix::read_wire_slice(&too_long),
+            Err(WireError::InvalidPrefix)
+        ));
+        let host_bits = [WIRE_VERSION, 1 | FLAG_PREFIX, 10, 1, 0, 0, 8];
+        assert!(matches!(
+            IpPrefix::read_wire_slice(&host_bits),
+            Err(WireError::InvalidPrefix)
+        ));
+    }
+
+    #[test]
+    fn reports_truncation() {
+        let wire = SocketAddrV6::new("fe80::1".parse().unwrap(), 443, 7, 3).to_wire();
+        for len in 0..wire.len() {
+            assert!(matches!(
+                SocketAddrV6::read_wire_slice(&wire[..len]),
+                Err(WireError::Truncated)
+            ));
+        }
+        let mut short = [0; 4];
+        assert!(matches!(
+            Ipv6Addr::LOCALHOST.write_wire_slice(&mut short),
+            Err(WireError::Truncated)
+        ));
+        let mut exact = [0; 18];
+        assert_eq!(
+            Ipv6Addr::LOCALHOST.write_wire_slice(&mut exact).unwrap(),
+            18
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! The unversioned layout of SOCKS5 (RFC 1928) and similar protocols:
+//!
+//! | bytes | field                                   |
+//! |-------|-----------------------------------------|
+//! | 1     | tag, [`TAG_V4`] or [`TAG_V6`]           |
+//! | 4/16  | address octets                          |
+//! | 2     | port, big endian, socket addresses only |
+//!
+//! IPv6 flowinfo and scope ids are not encoded.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::WireError;
+
+pub const TAG_V4: u8 = 0x01;
+pub const TAG_V6: u8 = 0x04;
+
+/// An encoded address, kept on the stack.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct WireAddr {
+    buf: [u8; Self::MAX_LEN],
+    len: u8,
+}
+
+impl WireAddr {
+    /// The length of an encoded IPv6 socket address.
+    pub const MAX_LEN: usize = 1 + 16 + 2;
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..usize::from(self.len)]
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        usize::from(self.len)
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        let len = usize::from(self.len);
+        self.buf[len..len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len() as u8;
+    }
+}
+
+impl AsRef<[u8]> for WireAddr {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+pub fn encode(addr: IpAddr) -> WireAddr {
+    let mut wire = WireAddr {
+        buf: [0; WireAddr::MAX_LEN],
+        len: 0,
+    };
+    match addr {
+        IpAddr::V4(addr) => {
+            wire.push(&[TAG_V4]);
+            wire.push(&addr.octets());
+        }
+        IpAddr::V6(addr) => {
+            wire.push(&[TAG_V6]);
+            wire.push(&addr.octets());
+        }
+    }
+    wire
+}
+
+pub fn encode_socket(addr: SocketAddr) -> WireAddr {
+    let mut wire = encode(addr.ip());
+    wire.push(&addr.port().to_be_bytes());
+    wire
+}
+
+/// Decodes from the front of `buf` and returns the address and the bytes
+/// consumed.
+pub fn decode(buf: &[u8]) -> Result<(IpAddr, usize), WireError> {
+    let (&tag, rest) = buf.split_first().ok_or(WireError::Truncated)?;
+    let addr = match tag {
+        TAG_V4 => {
+            let octets: [u8; 4] = take(rest)?;
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        TAG_V6 => {
+            let octets: [u8; 16] = take(rest)?;
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return Err(WireError::InvalidTag(tag)),
+    };
+    let len = match addr {
+        IpAddr::V4(_) => 1 + 4,
+        IpAddr::V6(_) => 1 + 16,
+    };
+    Ok((addr, len))
+}
+
+/// Like [`decode`], followed by the port.
+pub fn decode_socket(buf: &[u8]) -> Result<(SocketAddr, usize), WireError> {
+    let (ip, len) = decode(buf)?;
+    let port: [u8; 2] = take(&buf[len..])?;
+    Ok((SocketAddr::new(ip, u16::from_be_bytes(port)), len + 2))
+}
+
+fn take<const N: usize>(buf: &[u8]) -> Result<[u8; N], WireError> {
+    buf.get(..N)
+        .ok_or(WireError::Truncated)
+        .map(|bytes| bytes.try_into().expect("N bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddrV6;
+
+    use super::*;
+
+    #[test]
+    fn encodes_socks5_address_types() {
+        assert_eq!(
+            encode_socket("192.0.2.1:1080".parse().unwrap()).as_bytes(),
+            [TAG_V4, 192, 0, 2, 1, 0x04, 0x38]
+        );
+        let wire = encode("2001:db8::1".parse().unwrap());
+        assert_eq!(wire.len(), 17);
+        assert_eq!(wire.as_bytes()[..3], [TAG_V6, 0x20, 0x01]);
+        assert_eq!(wire.as_bytes()[16], 1);
+        assert_eq!(
+            encode_socket("[2001:db8::1]:443".parse().unwrap()).len(),
+            WireAddr::MAX_LEN
+        );
+    }
+
+    #[test]
+    fn round_trips_and_reports_consumed_bytes() {
+        for addr in [
+            "192.0.2.1:80",
+            "[2001:db8::1]:443",
+            "0.0.0.0:0",
+            "[::ffff:192.0.2.1]:65535",
+        ] {
+            let addr: SocketAddr = addr.parse().unwrap();
+            let mut buf = encode_socket(addr).as_bytes().to_vec();
+            let len = buf.len();
+            buf.extend_from_slice(b"payload");
+            assert_eq!(decode_socket(&buf).unwrap(), (addr, len));
+            assert_eq!(decode(&buf).unwrap(), (addr.ip(), len - 2));
+        }
+    }
+
+    #[test]
+    fn mapped_addresses_stay_ipv6() {
+        let mapped: IpAddr = "::ffff:192.0.2.1".parse().unwrap();
+        assert_eq!(encode(mapped).as_bytes()[0], TAG_V6);
+        assert_eq!(decode(encode(mapped).as_bytes()).unwrap().0, mapped);
+    }
+
+    #[test]
+    fn drops_flowinfo_and_scope_id() {
+        let addr = SocketAddrV6::new("fe80::1".parse().unwrap(), 443, 7, 3);
+        let (decoded, _) = decode_socket(encode_socket(addr.into()).as_bytes()).unwrap();
+        assert_eq!(decoded, SocketAddr::new((*addr.ip()).into(), 443));
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let wire = encode_socket("[2001:db8::1]:443".parse().unwrap());
+        for len in 0..wire.len() - 2 {
+            assert!(matches!(
+                decode(&wire.as_bytes()[..len]),
+                Err(WireError::Truncated)
+            ));
+        }
+        for len in 0..wire.len() {
+            assert!(matches!(
+                decode_socket(&wire.as_bytes()[..len]),
+                Err(WireError::Truncated)
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_domain_names_and_unknown_tags() {
+        // SOCKS5 uses 0x03 for domain names, which are not addresses.
+        assert!(matches!(
+            decode(&[0x03, 11, b'e']),
+            Err(WireError::InvalidTag(0x03))
+        ));
+        assert!(matches!(
+            decode(&[0x00, 0, 0, 0, 0]),
+            Err(WireError::InvalidTag(0x00))
+        ));
+    }
+}